@@ -0,0 +1,39 @@
+use crate::AppState;
+use crate::db::data_providers::generic_fetcher;
+use crate::models::shared::autocomplete::AutocompleteSuggestion;
+#[cfg(feature = "cache")]
+use crate::models::shared::autocomplete::{AutocompleteEntry, rank_autocomplete_suggestions};
+use crate::models::shared::game_system_enum::GameSystem;
+#[cfg(feature = "cache")]
+use cached::cached;
+
+/// With the cache enabled suggestions are ranked from an in-memory index, loaded once for
+/// each game system; otherwise they are ranked by the db, using the trigram indexes.
+pub async fn get_autocomplete_suggestions(
+    app_state: &AppState,
+    gs: GameSystem,
+    query: &str,
+    limit: usize,
+) -> Vec<AutocompleteSuggestion> {
+    #[cfg(feature = "cache")]
+    {
+        rank_autocomplete_suggestions(&get_autocomplete_index(app_state, gs).await, query, limit)
+    }
+    #[cfg(not(feature = "cache"))]
+    {
+        generic_fetcher::fetch_autocomplete_suggestions(&app_state.pool, gs, query.trim(), limit)
+            .await
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "cache")]
+#[cached(key = "i64", convert = r##"{ gs.into() }"##)]
+async fn get_autocomplete_index(app_state: &AppState, gs: GameSystem) -> Vec<AutocompleteEntry> {
+    generic_fetcher::fetch_autocomplete_index(&app_state.pool, gs)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(AutocompleteEntry::from)
+        .collect()
+}
@@ -12,10 +12,15 @@ use crate::models::creature::creature_metadata::type_enum::CreatureTypeEnum;
 use crate::models::creature::creature_metadata::variant_enum::CreatureVariant;
 use crate::models::creature::creature_struct::Creature;
 use crate::models::response_data::CreatureResponseDataModifiers;
+use crate::models::scales_struct::creature_scales::CreatureScales;
 use crate::models::shared::alignment_enum::AlignmentEnum;
 use crate::models::shared::condition_data::ConditionData;
+use crate::models::shared::facets::BestiaryFacets;
 use crate::models::shared::game_system_enum::GameSystem;
 use crate::models::shared::pf_version_enum::GameSystemVersionEnum;
+use crate::models::shared::text_search::TextSearchMatch;
+use crate::traits::url::has_sort_fields::HasSortFields;
+use crate::traits::url::paginated_request_ext::PaginatedRequestExt;
 use anyhow::Result;
 #[cfg(feature = "cache")]
 use cached::cached;
@@ -34,6 +39,18 @@ pub async fn get_creature_by_id(
         .ok()
 }
 
+/// Gets the creatures of `ids` that exist, in the same order, with a fixed number of queries.
+pub async fn get_creatures_by_ids(
+    app_state: &AppState,
+    gs: GameSystem,
+    ids: &[i64],
+    variant: CreatureVariant,
+    response_data_mods: &CreatureResponseDataModifiers,
+) -> Result<Vec<Creature>> {
+    creature_fetcher::fetch_creatures_by_ids(&app_state.pool, gs, variant, response_data_mods, ids)
+        .await
+}
+
 pub async fn get_weak_creature_by_id(
     app_state: &AppState,
     gs: GameSystem,
@@ -57,23 +74,23 @@ pub async fn get_paginated_creatures(
     gs: GameSystem,
     filters: &CreatureFieldFilters,
     pagination: &BestiaryPaginatedRequest,
-) -> Result<(u32, Vec<Creature>)> {
-    let (core_data, count) = creature_fetcher::fetch_paginated_creatures(
-        &app_state.pool,
-        gs,
-        filters,
-        pagination.bestiary_sort_data.sort_by.unwrap_or_default(),
-        pagination.bestiary_sort_data.order_by.unwrap_or_default(),
-        pagination.paginated_request.cursor,
-        pagination.paginated_request.page_size,
-    )
-    .await?;
+) -> Result<(u32, Vec<Creature>, Vec<TextSearchMatch>, Option<String>)> {
+    let (core_data, count, search_matches, last_keys) =
+        creature_fetcher::fetch_paginated_creatures(
+            &app_state.pool,
+            gs,
+            filters,
+            &pagination.bestiary_sort_data.sort_keys()?,
+            &pagination.listing_cursor()?,
+            pagination.paginated_request.page_size,
+        )
+        .await?;
     let count = count as u32;
     let creatures = core_data
         .into_iter()
         .map(|x| Creature::from_core(x, gs))
         .collect();
-    Ok((count, creatures))
+    Ok((count, creatures, search_matches, last_keys))
 }
 
 pub async fn get_creatures_passing_all_filters(
@@ -181,6 +198,20 @@ pub async fn get_bestiary_ranges(app_state: &AppState, gs: GameSystem) -> Option
         .ok()
 }
 
+pub async fn get_bestiary_facets(
+    app_state: &AppState,
+    gs: GameSystem,
+    filters: &CreatureFieldFilters,
+) -> Option<BestiaryFacets> {
+    creature_fetcher::fetch_creature_facets(&app_state.pool, gs, filters)
+        .await
+        .ok()
+}
+
+pub async fn get_creature_scales(app_state: &AppState) -> Result<CreatureScales> {
+    creature_fetcher::fetch_creature_scales(&app_state.pool).await
+}
+
 pub async fn get_conditions_list(
     app_state: &AppState,
     gs: GameSystem,
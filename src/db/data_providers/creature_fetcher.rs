@@ -1,12 +1,16 @@
 use crate::db::data_providers::generic_fetcher::{
-    enrich_with_traits, fetch_actions_from_cores, fetch_all_with_binds,
-    fetch_all_with_binds_and_count, fetch_armor_runes, fetch_armor_traits, fetch_col_range,
-    fetch_entity_traits, fetch_item_traits, fetch_shield_traits, fetch_weapon_actions,
-    fetch_weapon_damage_data, fetch_weapon_runes, fetch_weapon_traits,
+    enrich_with_traits, fetch_actions_batch, fetch_actions_from_cores, fetch_all_with_binds,
+    fetch_all_with_binds_and_count, fetch_armor_runes, fetch_armor_runes_batch, fetch_armor_traits,
+    fetch_col_range, fetch_entity_traits, fetch_entity_traits_batch, fetch_facet_values,
+    fetch_grouped_by_owner, fetch_grouped_values, fetch_item_traits, fetch_range_with_binds,
+    fetch_shield_traits, fetch_weapon_actions, fetch_weapon_damage_data,
+    fetch_weapon_damage_data_batch, fetch_weapon_runes, fetch_weapon_runes_batch,
+    fetch_weapon_traits, without_filter,
 };
 use crate::db::data_providers::raw_query_builder::{
-    format_pagination_clause, prepare_filtered_get_creatures_core,
-    prepare_paginated_get_creatures_listing,
+    format_pagination_clause, prepare_creature_facet_query, prepare_creature_range_query,
+    prepare_creature_role_facet_query, prepare_creature_trait_facet_query,
+    prepare_filtered_get_creatures_core, prepare_paginated_get_creatures_listing,
 };
 use crate::models::bestiary_structs::{BestiaryFilterQuery, BestiaryRanges, CreatureSortEnum};
 use crate::models::creature::creature_component::creature_combat::{
@@ -35,7 +39,6 @@ use crate::models::item::item_struct::Item;
 use crate::models::item::shield_struct::Shield;
 use crate::models::item::weapon_struct::Weapon;
 use crate::models::response_data::CreatureResponseDataModifiers;
-use crate::models::routers_validator_structs::OrderEnum;
 use crate::models::scales_struct::ability_scales::AbilityScales;
 use crate::models::scales_struct::ac_scales::AcScales;
 use crate::models::scales_struct::area_dmg_scales::AreaDmgScales;
@@ -52,11 +55,19 @@ use crate::models::scales_struct::strike_dmg_scales::StrikeDmgScales;
 use crate::models::shared::action::{Action, CoreAction};
 use crate::models::shared::alignment_enum::ALIGNMENT_TRAITS;
 use crate::models::shared::condition_data::ConditionData;
+use crate::models::shared::facets::BestiaryFacets;
 use crate::models::shared::game_system_enum::GameSystem;
+use crate::models::shared::listing_cursor::ListingCursor;
+use crate::models::shared::sort_spec::SortKeySpec;
+use crate::models::shared::text_search::TextSearchMatch;
 use crate::models::shared::trait_data::TraitData;
-use anyhow::Result;
+use crate::traits::proficiency_without_level::ProficiencyWithoutLevel;
+use anyhow::{Result, bail};
 use futures::future::join_all;
+use futures::try_join;
+use itertools::Itertools;
 use sqlx::PgPool;
+use std::collections::HashMap;
 
 async fn fetch_creature_immunities(
     pool: &PgPool,
@@ -471,7 +482,7 @@ pub async fn fetch_creature_spells(
     creature_id: i64,
     spellcaster_entry_id: i64,
 ) -> Result<Vec<Spell>> {
-    Ok(sqlx::query_as(sqlx::AssertSqlSafe(format!(
+    let spells = sqlx::query_as(sqlx::AssertSqlSafe(format!(
         "
         SELECT
             st.*,
@@ -486,7 +497,25 @@ pub async fn fetch_creature_spells(
     .bind(creature_id)
     .bind(spellcaster_entry_id)
     .fetch_all(pool)
-    .await?)
+    .await?;
+    with_spell_traits(pool, gs, spells).await
+}
+
+/// Fills the traits of the given spells, in a single round trip
+async fn with_spell_traits(
+    pool: &PgPool,
+    gs: GameSystem,
+    spells: Vec<Spell>,
+) -> Result<Vec<Spell>> {
+    let spell_ids: Vec<i64> = spells.iter().map(|x| x.id).collect();
+    let mut traits = fetch_entity_traits_batch(pool, gs, "spell", &spell_ids).await?;
+    Ok(spells
+        .into_iter()
+        .map(|spell| Spell {
+            traits: traits.remove(&spell.id).unwrap_or_default(),
+            ..spell
+        })
+        .collect())
 }
 
 async fn fetch_creature_spellcaster_entries(
@@ -596,13 +625,592 @@ pub async fn fetch_creature_by_id(
             None
         },
         game_system: gs,
-    }
-    .convert_creature_to_variant(variant);
-    Ok(if response_data_mods.is_pwl_on.unwrap_or(false) {
-        cr.convert_creature_to_pwl()
+    };
+    Ok(apply_response_data_mods(cr, variant, response_data_mods))
+}
+
+/// Converts a base creature to the requested variant, then to PWL when it is enabled.
+fn apply_response_data_mods(
+    cr: Creature,
+    variant: CreatureVariant,
+    response_data_mods: &CreatureResponseDataModifiers,
+) -> Creature {
+    cr.convert_creature_to_variant(variant)
+        .with_pwl(response_data_mods.is_pwl_on.unwrap_or(false))
+}
+
+/// Gets every creature of `ids` that exists, in the same order (repeated ids are repeated).
+///
+/// Each requested section is loaded with a fixed number of `= ANY($1)` queries, however many
+/// creatures are asked for. Variant and PWL are applied like in `fetch_creature_by_id`.
+pub async fn fetch_creatures_by_ids(
+    pool: &PgPool,
+    gs: GameSystem,
+    variant: CreatureVariant,
+    response_data_mods: &CreatureResponseDataModifiers,
+    ids: &[i64],
+) -> Result<Vec<Creature>> {
+    let unique_ids: Vec<i64> = ids.iter().copied().unique().collect();
+    let core_data: Vec<CreatureCoreData> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "SELECT * FROM {gs}_creature_core WHERE id = ANY($1) AND status = 'valid'"
+    )))
+    .bind(&unique_ids)
+    .fetch_all(pool)
+    .await?;
+    let core_data = update_creatures_core_with_traits(pool, gs, core_data).await;
+    let with_extra_data = response_data_mods.extra_data.is_some_and(|x| x);
+    let with_combat_data = response_data_mods.combat_data.is_some_and(|x| x);
+    let stats = if with_extra_data || with_combat_data {
+        fetch_creatures_stats_batch(pool, gs, &unique_ids).await?
     } else {
-        cr
-    })
+        HashMap::new()
+    };
+    let extra_data = if with_extra_data {
+        fetch_creatures_extra_data_batch(pool, gs, &stats).await?
+    } else {
+        HashMap::new()
+    };
+    let combat_data = if with_combat_data {
+        fetch_creatures_combat_data_batch(pool, gs, &stats).await?
+    } else {
+        HashMap::new()
+    };
+    let spellcaster_data = if response_data_mods.spellcasting_data.is_some_and(|x| x) {
+        fetch_creatures_spellcaster_data_batch(pool, gs, &unique_ids).await?
+    } else {
+        HashMap::new()
+    };
+    assemble_creatures(
+        gs,
+        variant,
+        response_data_mods,
+        ids,
+        core_data,
+        CreatureSections {
+            extra_data,
+            combat_data,
+            spellcaster_data,
+        },
+    )
+}
+
+/// Sections of the creatures fetched in batch, keyed by creature id
+struct CreatureSections {
+    extra_data: HashMap<i64, CreatureExtraData>,
+    combat_data: HashMap<i64, CreatureCombatData>,
+    spellcaster_data: HashMap<i64, CreatureSpellcasterData>,
+}
+
+/// Builds the creatures of `ids`, in their order and repeating the duplicated ones.
+/// Ids without a valid core row are left out, as `fetch_creature_by_id` would not find them,
+/// while a requested section missing for a fetched core is an error, as it is there.
+fn assemble_creatures(
+    gs: GameSystem,
+    variant: CreatureVariant,
+    response_data_mods: &CreatureResponseDataModifiers,
+    ids: &[i64],
+    core_data: Vec<CreatureCoreData>,
+    mut sections: CreatureSections,
+) -> Result<Vec<Creature>> {
+    let mut creatures: HashMap<i64, Creature> = HashMap::with_capacity(core_data.len());
+    for core in core_data {
+        let id = core.essential.id;
+        let extra_data = sections.extra_data.remove(&id);
+        let combat_data = sections.combat_data.remove(&id);
+        if (response_data_mods.extra_data.is_some_and(|x| x) && extra_data.is_none())
+            || (response_data_mods.combat_data.is_some_and(|x| x) && combat_data.is_none())
+        {
+            bail!("Creature {id} has no row in {gs}_creature_table");
+        }
+        let cr = Creature {
+            extra_data,
+            combat_data,
+            spellcaster_data: sections.spellcaster_data.remove(&id),
+            ..Creature::from_core(core, gs)
+        };
+        creatures.insert(
+            id,
+            apply_response_data_mods(cr, variant, response_data_mods),
+        );
+    }
+    Ok(ids
+        .iter()
+        .filter_map(|id| creatures.get(id).cloned())
+        .collect())
+}
+
+/// Scalar stats of a creature, read in a single row of `{gs}_creature_table`.
+#[derive(sqlx::FromRow)]
+struct CreatureStatsRow {
+    id: i64,
+    #[sqlx(flatten)]
+    saving_throws: SavingThrows,
+    #[sqlx(flatten)]
+    ability_scores: AbilityScores,
+    hp_detail: Option<String>,
+    ac_detail: Option<String>,
+    language_detail: Option<String>,
+    perception: i32,
+    perception_detail: Option<String>,
+    vision: bool,
+    ac: i32,
+}
+
+async fn fetch_creatures_stats_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    creature_ids: &[i64],
+) -> Result<HashMap<i64, CreatureStatsRow>> {
+    let rows: Vec<CreatureStatsRow> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "SELECT * FROM {gs}_creature_table WHERE id = ANY($1)"
+    )))
+    .bind(creature_ids)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|x| (x.id, x)).collect())
+}
+
+/// Quantities of the given entity held by the creatures, keyed by (creature id, entity id).
+async fn fetch_creatures_quantities_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    creature_ids: &[i64],
+    entity: &str,
+) -> Result<HashMap<(i64, i64), i64>> {
+    let rows: Vec<(i64, i64, i32)> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "SELECT creature_id, {entity}_id, quantity FROM {gs}_{entity}_creature_association_table
+        WHERE creature_id = ANY($1)"
+    )))
+    .bind(creature_ids)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(creature_id, entity_id, quantity)| ((creature_id, entity_id), i64::from(quantity)))
+        .collect())
+}
+
+async fn fetch_creatures_extra_data_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    stats: &HashMap<i64, CreatureStatsRow>,
+) -> Result<HashMap<i64, CreatureExtraData>> {
+    let ids: Vec<i64> = stats.keys().copied().collect();
+    let mut actions = fetch_actions_batch(
+        pool,
+        gs,
+        "creature_action_association_table",
+        "creature_id",
+        &ids,
+    )
+    .await?;
+    let mut skills: HashMap<i64, Vec<Skill>> = fetch_grouped_by_owner(
+        pool,
+        format!(
+            "SELECT creature_id AS owner_id, name, description, modifier, proficiency
+            FROM {gs}_skill_table WHERE creature_id = ANY($1)"
+        ),
+        &ids,
+    )
+    .await?;
+    let mut items = fetch_creatures_items_batch(pool, gs, &ids).await?;
+    let mut languages: HashMap<i64, Vec<String>> = fetch_grouped_values(
+        pool,
+        format!(
+            "SELECT DISTINCT lca.creature_id, lt.name FROM {gs}_language_table lt
+            JOIN {gs}_language_creature_association_table lca ON lca.language_id = lt.name
+            WHERE lca.creature_id = ANY($1) ORDER BY lt.name"
+        ),
+        &ids,
+    )
+    .await?;
+    let mut senses: HashMap<i64, Vec<Sense>> = fetch_grouped_by_owner(
+        pool,
+        format!(
+            "SELECT sca.creature_id AS owner_id, st.* FROM {gs}_sense_table st
+            JOIN {gs}_sense_creature_association_table sca ON sca.sense_id = st.id
+            WHERE sca.creature_id = ANY($1)"
+        ),
+        &ids,
+    )
+    .await?;
+    let mut speeds: HashMap<i64, Vec<RawSpeed>> = fetch_grouped_by_owner(
+        pool,
+        format!(
+            "SELECT creature_id AS owner_id, name, value FROM {gs}_speed_table
+            WHERE creature_id = ANY($1)"
+        ),
+        &ids,
+    )
+    .await?;
+    Ok(stats
+        .iter()
+        .map(|(id, stats)| {
+            let extra_data = CreatureExtraData {
+                actions: actions.remove(id).unwrap_or_default(),
+                skills: skills.remove(id).unwrap_or_default(),
+                items: items.remove(id).unwrap_or_default(),
+                languages: languages.remove(id).unwrap_or_default(),
+                senses: senses.remove(id).unwrap_or_default(),
+                speeds: speeds
+                    .remove(id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|x| (x.name, x.value as i16))
+                    .collect(),
+                ability_scores: stats.ability_scores.clone(),
+                hp_detail: stats.hp_detail.clone(),
+                ac_detail: stats.ac_detail.clone(),
+                language_detail: stats.language_detail.clone(),
+                perception: stats.perception,
+                perception_detail: stats.perception_detail.clone(),
+                has_vision: stats.vision,
+            };
+            (*id, extra_data)
+        })
+        .collect())
+}
+
+async fn fetch_creatures_combat_data_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    stats: &HashMap<i64, CreatureStatsRow>,
+) -> Result<HashMap<i64, CreatureCombatData>> {
+    let ids: Vec<i64> = stats.keys().copied().collect();
+    let mut weapons = fetch_creatures_weapons_batch(pool, gs, &ids).await?;
+    let mut armors = fetch_creatures_armors_batch(pool, gs, &ids).await?;
+    let mut shields = fetch_creatures_shields_batch(pool, gs, &ids).await?;
+    let mut resistances = fetch_creatures_resistances_batch(pool, gs, &ids).await?;
+    let mut immunities: HashMap<i64, Vec<String>> = fetch_grouped_values(
+        pool,
+        format!(
+            "SELECT DISTINCT ica.creature_id, it.name FROM {gs}_immunity_table it
+            JOIN {gs}_immunity_creature_association_table ica ON ica.immunity_id = it.name
+            WHERE ica.creature_id = ANY($1) ORDER BY it.name"
+        ),
+        &ids,
+    )
+    .await?;
+    let mut weaknesses: HashMap<i64, Vec<RawWeakness>> = fetch_grouped_by_owner(
+        pool,
+        format!(
+            "SELECT creature_id AS owner_id, name, value FROM {gs}_weakness_table
+            WHERE creature_id = ANY($1)"
+        ),
+        &ids,
+    )
+    .await?;
+    let mut conditions: HashMap<i64, Vec<ConditionData>> = fetch_grouped_by_owner(
+        pool,
+        format!(
+            "SELECT DISTINCT cca.creature_id AS owner_id, ct.*
+            FROM {gs}_creature_condition_association_table cca
+            JOIN {gs}_condition_table ct ON cca.condition_id = ct.name
+            WHERE cca.creature_id = ANY($1)"
+        ),
+        &ids,
+    )
+    .await?;
+    Ok(stats
+        .iter()
+        .map(|(id, stats)| {
+            let combat_data = CreatureCombatData {
+                weapons: weapons.remove(id).unwrap_or_default(),
+                armors: armors.remove(id).unwrap_or_default(),
+                shields: shields.remove(id).unwrap_or_default(),
+                resistances: resistances.remove(id).unwrap_or_default(),
+                immunities: immunities.remove(id).unwrap_or_default(),
+                weaknesses: weaknesses
+                    .remove(id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|x| (x.name, i16::try_from(x.value).unwrap_or(0)))
+                    .collect(),
+                saving_throws: stats.saving_throws.clone(),
+                ac: stats.ac,
+                conditions: conditions.remove(id).unwrap_or_default(),
+            };
+            (*id, combat_data)
+        })
+        .collect())
+}
+
+async fn fetch_creatures_spellcaster_data_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    creature_ids: &[i64],
+) -> Result<HashMap<i64, CreatureSpellcasterData>> {
+    let mut entries: HashMap<i64, Vec<SpellcasterData>> = fetch_grouped_by_owner(
+        pool,
+        format!(
+            "SELECT creature_id AS owner_id,
+            id, spellcasting_name, is_spellcasting_flexible, type_of_spellcaster,
+            spellcasting_dc_mod, spellcasting_atk_mod, spellcasting_tradition, heighten_level
+            FROM {gs}_spellcasting_entry_table WHERE creature_id = ANY($1)"
+        ),
+        creature_ids,
+    )
+    .await?;
+    let spells: Vec<Spell> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "
+        SELECT
+            st.*,
+            rt.id AS range_id, rt.value as range_value, rt.increment as range_increment, rt.max AS range_max
+        FROM {gs}_spell_table st
+        INNER JOIN {gs}_spell_range_association_table sr ON sr.spell_id = st.id
+        INNER JOIN {gs}_range_table rt ON rt.id = sr.range_id
+        WHERE st.creature_id = ANY($1)
+        "
+    )))
+    .bind(creature_ids)
+    .fetch_all(pool)
+    .await?;
+    let spells = with_spell_traits(pool, gs, spells).await?;
+    let mut spells_by_entry: HashMap<i64, Vec<Spell>> = spells
+        .into_iter()
+        .into_group_map_by(|x| x.spellcasting_entry_id);
+    Ok(creature_ids
+        .iter()
+        .map(|id| {
+            let spellcaster_entries = entries
+                .remove(id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|sce| SpellcasterEntry {
+                    spells: spells_by_entry.remove(&sce.id).unwrap_or_default(),
+                    spellcaster_data: sce,
+                })
+                .collect();
+            (
+                *id,
+                CreatureSpellcasterData {
+                    spellcaster_entries,
+                },
+            )
+        })
+        .collect())
+}
+
+async fn fetch_creatures_resistances_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    creature_ids: &[i64],
+) -> Result<HashMap<i64, Vec<Resistance>>> {
+    let cores: HashMap<i64, Vec<CoreResistanceData>> = fetch_grouped_by_owner(
+        pool,
+        format!(
+            "SELECT creature_id AS owner_id, id, name, value FROM {gs}_resistance_table
+            WHERE creature_id = ANY($1)"
+        ),
+        creature_ids,
+    )
+    .await?;
+    let resistance_ids: Vec<i64> = cores.values().flatten().map(|x| x.id).collect();
+    let mut double_vs: HashMap<i64, Vec<String>> = fetch_grouped_values(
+        pool,
+        format!(
+            "SELECT resistance_id, vs_name FROM {gs}_resistance_double_vs_table
+            WHERE resistance_id = ANY($1)"
+        ),
+        &resistance_ids,
+    )
+    .await?;
+    let mut exception_vs: HashMap<i64, Vec<String>> = fetch_grouped_values(
+        pool,
+        format!(
+            "SELECT resistance_id, vs_name FROM {gs}_resistance_exception_vs_table
+            WHERE resistance_id = ANY($1)"
+        ),
+        &resistance_ids,
+    )
+    .await?;
+    Ok(cores
+        .into_iter()
+        .map(|(creature_id, cores)| {
+            let resistances = cores
+                .into_iter()
+                .map(|core| Resistance {
+                    double_vs: double_vs.remove(&core.id).unwrap_or_default(),
+                    exception_vs: exception_vs.remove(&core.id).unwrap_or_default(),
+                    core,
+                })
+                .collect();
+            (creature_id, resistances)
+        })
+        .collect())
+}
+
+async fn fetch_creatures_weapons_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    creature_ids: &[i64],
+) -> Result<HashMap<i64, Vec<Weapon>>> {
+    let mut weapons: HashMap<i64, Vec<Weapon>> = fetch_grouped_by_owner(
+        pool,
+        format!(
+            "
+            SELECT DISTINCT
+                ica.creature_id AS owner_id,
+                wt.id AS weapon_id, wt.to_hit_bonus, wt.splash_dmg, wt.n_of_potency_runes,
+                wt.n_of_striking_runes, wt.reload, wt.weapon_type, wt.base_item_id,
+                it.*,
+                rt.id AS range_id, rt.value AS range_value, rt.increment AS range_increment,
+                rt.max AS range_max
+            FROM {gs}_weapon_creature_association_table ica
+            LEFT JOIN {gs}_weapon_table wt                      ON wt.id = ica.weapon_id
+            LEFT JOIN {gs}_item_table it                        ON it.id = wt.base_item_id
+            LEFT JOIN {gs}_weapon_range_association_table wr    ON wr.weapon_id = wt.id
+            LEFT JOIN {gs}_range_table rt                       ON rt.id = wr.range_id
+            WHERE ica.creature_id = ANY($1)
+            ORDER BY name
+            "
+        ),
+        creature_ids,
+    )
+    .await?;
+    let weapon_ids: Vec<i64> = weapons
+        .values()
+        .flatten()
+        .map(|x| x.weapon_data.id)
+        .unique()
+        .collect();
+    let traits = fetch_entity_traits_batch(pool, gs, "weapon", &weapon_ids).await?;
+    let runes = fetch_weapon_runes_batch(pool, gs, &weapon_ids).await?;
+    let damage_data = fetch_weapon_damage_data_batch(pool, gs, &weapon_ids).await?;
+    let attack_effects = fetch_actions_batch(
+        pool,
+        gs,
+        "weapon_action_association_table",
+        "weapon_id",
+        &weapon_ids,
+    )
+    .await?;
+    let quantities = fetch_creatures_quantities_batch(pool, gs, creature_ids, "weapon").await?;
+    for (creature_id, weapons) in &mut weapons {
+        for el in weapons {
+            let id = el.weapon_data.id;
+            el.item_core.traits = traits.get(&id).cloned().unwrap_or_default();
+            el.item_core.quantity = quantities.get(&(*creature_id, id)).copied().unwrap_or(1);
+            el.weapon_data.property_runes = runes.get(&id).cloned().unwrap_or_default();
+            el.weapon_data.damage_data = damage_data.get(&id).cloned().unwrap_or_default();
+            el.weapon_data.attack_effects = attack_effects.get(&id).cloned().unwrap_or_default();
+        }
+    }
+    Ok(weapons)
+}
+
+async fn fetch_creatures_armors_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    creature_ids: &[i64],
+) -> Result<HashMap<i64, Vec<Armor>>> {
+    let mut armors: HashMap<i64, Vec<Armor>> = fetch_grouped_by_owner(
+        pool,
+        format!(
+            "
+            SELECT DISTINCT aca.creature_id AS owner_id,
+            at.id AS armor_id, at.bonus_ac, at.check_penalty, at.dex_cap, at.n_of_potency_runes,
+            at.n_of_resilient_runes, at.speed_penalty, at.strength_required, at.base_item_id,
+            it.*
+            FROM {gs}_armor_creature_association_table aca
+            LEFT JOIN {gs}_armor_table at ON at.id = aca.armor_id
+            LEFT JOIN {gs}_item_table it ON it.id = at.base_item_id
+            WHERE aca.creature_id = ANY($1)
+            ORDER BY name
+            "
+        ),
+        creature_ids,
+    )
+    .await?;
+    let armor_ids: Vec<i64> = armors
+        .values()
+        .flatten()
+        .map(|x| x.armor_data.id)
+        .unique()
+        .collect();
+    let traits = fetch_entity_traits_batch(pool, gs, "armor", &armor_ids).await?;
+    let runes = fetch_armor_runes_batch(pool, gs, &armor_ids).await?;
+    let quantities = fetch_creatures_quantities_batch(pool, gs, creature_ids, "armor").await?;
+    for (creature_id, armors) in &mut armors {
+        for el in armors {
+            let id = el.armor_data.id;
+            el.item_core.traits = traits.get(&id).cloned().unwrap_or_default();
+            el.item_core.quantity = quantities.get(&(*creature_id, id)).copied().unwrap_or(1);
+            el.armor_data.property_runes = runes.get(&id).cloned().unwrap_or_default();
+        }
+    }
+    Ok(armors)
+}
+
+async fn fetch_creatures_shields_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    creature_ids: &[i64],
+) -> Result<HashMap<i64, Vec<Shield>>> {
+    let mut shields: HashMap<i64, Vec<Shield>> = fetch_grouped_by_owner(
+        pool,
+        format!(
+            "
+            SELECT DISTINCT sca.creature_id AS owner_id,
+            st.id AS shield_id, st.bonus_ac, st.n_of_reinforcing_runes, st.speed_penalty,
+            it.*
+            FROM {gs}_shield_creature_association_table sca
+            LEFT JOIN {gs}_shield_table st ON st.id = sca.shield_id
+            LEFT JOIN {gs}_item_table it ON it.id = st.base_item_id
+            WHERE sca.creature_id = ANY($1)
+            ORDER BY name
+            "
+        ),
+        creature_ids,
+    )
+    .await?;
+    let shield_ids: Vec<i64> = shields
+        .values()
+        .flatten()
+        .map(|x| x.shield_data.id)
+        .unique()
+        .collect();
+    let traits = fetch_entity_traits_batch(pool, gs, "shield", &shield_ids).await?;
+    let quantities = fetch_creatures_quantities_batch(pool, gs, creature_ids, "shield").await?;
+    for (creature_id, shields) in &mut shields {
+        for el in shields {
+            let id = el.shield_data.id;
+            el.item_core.traits = traits.get(&id).cloned().unwrap_or_default();
+            el.item_core.quantity = quantities.get(&(*creature_id, id)).copied().unwrap_or(1);
+        }
+    }
+    Ok(shields)
+}
+
+async fn fetch_creatures_items_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    creature_ids: &[i64],
+) -> Result<HashMap<i64, Vec<Item>>> {
+    let mut items: HashMap<i64, Vec<Item>> = fetch_grouped_by_owner(
+        pool,
+        format!(
+            "SELECT ica.creature_id AS owner_id, it.* FROM {gs}_item_table it
+            JOIN {gs}_item_creature_association_table ica ON ica.item_id = it.id
+            WHERE ica.creature_id = ANY($1)
+            AND it.id NOT IN (SELECT base_item_id FROM {gs}_armor_table)
+            AND it.id NOT IN (SELECT base_item_id FROM {gs}_weapon_table)
+            AND it.id NOT IN (SELECT base_item_id FROM {gs}_shield_table)
+            "
+        ),
+        creature_ids,
+    )
+    .await?;
+    let item_ids: Vec<i64> = items.values().flatten().map(|x| x.id).unique().collect();
+    let traits = fetch_entity_traits_batch(pool, gs, "item", &item_ids).await?;
+    let quantities = fetch_creatures_quantities_batch(pool, gs, creature_ids, "item").await?;
+    for (creature_id, items) in &mut items {
+        for el in items {
+            el.traits = traits.get(&el.id).cloned().unwrap_or_default();
+            el.quantity = quantities.get(&(*creature_id, el.id)).copied().unwrap_or(1);
+        }
+    }
+    Ok(items)
 }
 
 pub async fn fetch_creatures_core_data_with_filters(
@@ -810,31 +1418,260 @@ pub async fn fetch_creature_scales(pool: &PgPool) -> Result<CreatureScales> {
 }
 
 /// Returns the requested page of creatures alongside the total count of creatures matching
-/// `filters` (ignoring pagination), fetched in a single round trip via `COUNT(*) OVER()`.
+/// `filters` (ignoring pagination), fetched concurrently.
 pub async fn fetch_paginated_creatures(
     pool: &PgPool,
     gs: GameSystem,
     filters: &CreatureFieldFilters,
-    sort_by: CreatureSortEnum,
-    order_by: OrderEnum,
-    cursor: u32,
+    sort: &[SortKeySpec<CreatureSortEnum>],
+    cursor: &ListingCursor,
     page_size: i16,
-) -> Result<(Vec<CreatureCoreData>, i64)> {
-    let (query, binds) =
-        prepare_paginated_get_creatures_listing(gs, filters, sort_by, order_by, cursor, page_size);
-    let (cr_core, total_count): (Vec<CreatureCoreData>, i64) =
-        fetch_all_with_binds_and_count(pool, query, binds).await?;
+) -> Result<(
+    Vec<CreatureCoreData>,
+    i64,
+    Vec<TextSearchMatch>,
+    Option<String>,
+)> {
+    let queries = prepare_paginated_get_creatures_listing(gs, filters, sort, cursor, page_size)?;
+    let (cr_core, total_count, search_matches, last_keys) =
+        fetch_all_with_binds_and_count::<CreatureCoreData>(pool, queries).await?;
     Ok((
         update_creatures_core_with_traits(pool, gs, cr_core).await,
         total_count,
+        search_matches,
+        last_keys,
     ))
 }
 
+/// Returns, for every filterable dimension, the values available under the other active filters
+/// and the number of creatures they would lead to.
+pub async fn fetch_creature_facets(
+    pool: &PgPool,
+    gs: GameSystem,
+    filters: &CreatureFieldFilters,
+) -> Result<BestiaryFacets> {
+    let (source, family, rarity, size, creature_type, traits, role, level, ranges) = try_join!(
+        fetch_facet_values(
+            pool,
+            prepare_creature_facet_query(
+                gs,
+                &without_filter(filters, |f| f.source_filter = None),
+                "source",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_creature_facet_query(
+                gs,
+                &without_filter(filters, |f| f.family_filter = None),
+                "family",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_creature_facet_query(
+                gs,
+                &without_filter(filters, |f| f.rarity_filter = None),
+                "rarity",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_creature_facet_query(
+                gs,
+                &without_filter(filters, |f| f.size_filter = None),
+                "size",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_creature_facet_query(
+                gs,
+                &without_filter(filters, |f| f.type_filter = None),
+                "cr_type",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_creature_trait_facet_query(
+                gs,
+                &without_filter(filters, |f| {
+                    f.trait_whitelist_filter = None;
+                    f.trait_blacklist_filter = None;
+                    f.trait_expression_filter = None;
+                }),
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_creature_role_facet_query(
+                gs,
+                &without_filter(filters, |f| f.role_filter = None),
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_creature_facet_query(
+                gs,
+                &without_filter(filters, |f| {
+                    f.min_level_filter = None;
+                    f.max_level_filter = None;
+                }),
+                "level",
+            ),
+        ),
+        fetch_filtered_creature_ranges(pool, gs, filters),
+    )?;
+    Ok(BestiaryFacets {
+        source,
+        family,
+        rarity,
+        size,
+        creature_type,
+        traits,
+        role,
+        level,
+        ranges,
+    })
+}
+
+/// Like `fetch_creature_ranges`, but only over the creatures passing the filters.
+/// The range of each stat ignores the bounds on that same stat.
+pub async fn fetch_filtered_creature_ranges(
+    pool: &PgPool,
+    gs: GameSystem,
+    filters: &CreatureFieldFilters,
+) -> Result<BestiaryRanges> {
+    let range = |range_table: &'static str, column: &'static str, f: CreatureFieldFilters| {
+        fetch_range_with_binds::<i64>(
+            pool,
+            prepare_creature_range_query(gs, &f, range_table, column),
+        )
+    };
+    let (
+        (min_hp, max_hp),
+        (min_level, max_level),
+        (min_focus_points, max_focus_points),
+        (min_ac, max_ac),
+        (min_fortitude, max_fortitude),
+        (min_reflex, max_reflex),
+        (min_will, max_will),
+        (min_perception, max_perception),
+        (min_skill_modifier, max_skill_modifier),
+    ) = try_join!(
+        range(
+            "creature_core",
+            "hp",
+            without_filter(filters, |f| {
+                f.min_hp_filter = None;
+                f.max_hp_filter = None;
+            }),
+        ),
+        range(
+            "creature_core",
+            "level",
+            without_filter(filters, |f| {
+                f.min_level_filter = None;
+                f.max_level_filter = None;
+            }),
+        ),
+        range(
+            "creature_core",
+            "focus_points",
+            without_filter(filters, |f| {
+                f.min_focus_points_filter = None;
+                f.max_focus_points_filter = None;
+            }),
+        ),
+        range(
+            "creature_table",
+            "ac",
+            without_filter(filters, |f| {
+                f.min_ac_filter = None;
+                f.max_ac_filter = None;
+            }),
+        ),
+        range(
+            "creature_table",
+            "fortitude",
+            without_filter(filters, |f| {
+                f.min_fortitude_filter = None;
+                f.max_fortitude_filter = None;
+            }),
+        ),
+        range(
+            "creature_table",
+            "reflex",
+            without_filter(filters, |f| {
+                f.min_reflex_filter = None;
+                f.max_reflex_filter = None;
+            }),
+        ),
+        range(
+            "creature_table",
+            "will",
+            without_filter(filters, |f| {
+                f.min_will_filter = None;
+                f.max_will_filter = None;
+            }),
+        ),
+        range(
+            "creature_table",
+            "perception",
+            without_filter(filters, |f| {
+                f.min_perception_filter = None;
+                f.max_perception_filter = None;
+            }),
+        ),
+        range(
+            "skill_table",
+            "modifier",
+            without_filter(filters, |f| {
+                f.min_skill_modifier_filter = None;
+                f.max_skill_modifier_filter = None;
+            }),
+        ),
+    )?;
+    Ok(BestiaryRanges {
+        min_hp,
+        max_hp,
+        min_level,
+        max_level,
+        min_focus_points,
+        max_focus_points,
+        min_ac,
+        max_ac,
+        min_fortitude,
+        max_fortitude,
+        min_reflex,
+        max_reflex,
+        min_will,
+        max_will,
+        min_perception,
+        max_perception,
+        min_skill_modifier,
+        max_skill_modifier,
+    })
+}
+
 pub async fn fetch_creature_ranges(pool: &PgPool, gs: GameSystem) -> Result<BestiaryRanges> {
     let from = format!("FROM {gs}_creature_core WHERE status = 'valid'");
     let (min_hp, max_hp) = fetch_col_range(pool, "hp", &from).await?;
     let (min_level, max_level) = fetch_col_range(pool, "level", &from).await?;
     let (min_focus_points, max_focus_points) = fetch_col_range(pool, "focus_points", &from).await?;
+    let stats_from = format!(
+        "FROM {gs}_creature_table WHERE id IN (SELECT id FROM {gs}_creature_core WHERE status = 'valid')"
+    );
+    let (min_ac, max_ac) = fetch_col_range(pool, "ac", &stats_from).await?;
+    let (min_fortitude, max_fortitude) = fetch_col_range(pool, "fortitude", &stats_from).await?;
+    let (min_reflex, max_reflex) = fetch_col_range(pool, "reflex", &stats_from).await?;
+    let (min_will, max_will) = fetch_col_range(pool, "will", &stats_from).await?;
+    let (min_perception, max_perception) = fetch_col_range(pool, "perception", &stats_from).await?;
+    let skills_from = format!(
+        "FROM {gs}_skill_table WHERE creature_id IN (SELECT id FROM {gs}_creature_core WHERE status = 'valid')"
+    );
+    let (min_skill_modifier, max_skill_modifier) =
+        fetch_col_range(pool, "modifier", &skills_from).await?;
     Ok(BestiaryRanges {
         min_hp,
         max_hp,
@@ -842,5 +1679,79 @@ pub async fn fetch_creature_ranges(pool: &PgPool, gs: GameSystem) -> Result<Best
         max_level,
         min_focus_points,
         max_focus_points,
+        min_ac,
+        max_ac,
+        min_fortitude,
+        max_fortitude,
+        min_reflex,
+        max_reflex,
+        min_will,
+        max_will,
+        min_perception,
+        max_perception,
+        min_skill_modifier,
+        max_skill_modifier,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn core(id: i64) -> CreatureCoreData {
+        serde_json::from_value(serde_json::json!({
+            "essential": {
+                "id": id, "aon_id": null, "name": format!("Creature {id}"), "hp": 20,
+                "base_level": 1, "size": "Medium", "family": "-", "rarity": "Common",
+                "license": "ORC", "remaster": true, "source": "Monster Core",
+                "cr_type": "Creature", "alignment": "No Alignment", "focus_points": 0,
+                "status": "Valid"
+            },
+            "derived": {"archive_link": null, "attack_data": {}, "role_data": {}},
+            "traits": []
+        }))
+        .unwrap()
+    }
+
+    fn no_sections() -> CreatureSections {
+        CreatureSections {
+            extra_data: HashMap::new(),
+            combat_data: HashMap::new(),
+            spellcaster_data: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn creatures_follow_the_requested_ids() {
+        let creatures = assemble_creatures(
+            GameSystem::Pathfinder,
+            CreatureVariant::Base,
+            &CreatureResponseDataModifiers::default(),
+            &[2, 1, 2, 3],
+            vec![core(1), core(2)],
+            no_sections(),
+        )
+        .unwrap();
+        let ids: Vec<i64> = creatures.iter().map(|x| x.core_data.essential.id).collect();
+        assert_eq!(ids, [2, 1, 2]);
+    }
+
+    #[test]
+    fn creatures_missing_a_requested_section_are_an_error() {
+        let mods = CreatureResponseDataModifiers {
+            combat_data: Some(true),
+            ..Default::default()
+        };
+        let result = assemble_creatures(
+            GameSystem::Pathfinder,
+            CreatureVariant::Base,
+            &mods,
+            &[1],
+            vec![core(1)],
+            no_sections(),
+        );
+        assert!(
+            result.is_err_and(|x| x.to_string() == "Creature 1 has no row in pf_creature_table")
+        );
+    }
+}
@@ -1,14 +1,20 @@
-use crate::db::data_providers::raw_query_builder::BindValue;
+use crate::db::data_providers::raw_query_builder::{
+    BindValue, ListingQueries, escape_like_pattern,
+};
 use crate::models::item::weapon_struct::DamageData;
 use crate::models::shared::action::{Action, CoreAction};
 use crate::models::shared::alignment_enum::ALIGNMENT_TRAITS;
+use crate::models::shared::autocomplete::AutocompleteSuggestion;
 use crate::models::shared::condition_data::ConditionData;
+use crate::models::shared::facets::FacetValue;
 use crate::models::shared::game_system_enum::GameSystem;
+use crate::models::shared::name_match_mode_enum::FUZZY_NAME_THRESHOLD;
+use crate::models::shared::text_search::TextSearchMatch;
 use crate::models::shared::trait_data::TraitData;
 use crate::traits::traits_enrichable::TraitsEnrichable;
 use anyhow::Result;
-use sqlx::PgPool;
 use sqlx::postgres::PgRow;
+use sqlx::{FromRow, PgPool, Row};
 use std::collections::HashMap;
 
 /// Executes a query built by `raw_query_builder`, binding the values produced
@@ -31,34 +37,85 @@ where
     Ok(query.fetch_all(pool).await?)
 }
 
-/// Executes a paginated listing query that selects `COUNT(*) OVER() AS total_count`.
+/// Executes the page and count queries of a listing built by `raw_query_builder`, concurrently.
 ///
-/// Built by `raw_query_builder`, this returns both the page of rows and the total count of rows
-/// matching the filter (before pagination) from a single round trip. Returns 0 if cursor is past total
+/// Returns the page of rows and the total count of rows matching the filter (before pagination).
+/// When the query is a full-text search, the rank and snippet of every row are returned as well,
+/// a row without a rank is an error and one without a snippet gets an empty one.
+/// Last come the `keyset_keys` of the last row, from which the next page token is built.
 pub async fn fetch_all_with_binds_and_count<O>(
     pool: &PgPool,
-    sql: String,
-    binds: Vec<BindValue>,
-) -> Result<(Vec<O>, i64)>
+    queries: ListingQueries,
+) -> Result<(Vec<O>, i64, Vec<TextSearchMatch>, Option<String>)>
 where
     O: for<'r> sqlx::FromRow<'r, PgRow> + Send + Unpin,
 {
-    use sqlx::Row;
-
-    let mut query = sqlx::query(sqlx::AssertSqlSafe(sql));
-    for value in binds {
-        query = match value {
-            BindValue::Text(s) => query.bind(s),
-            BindValue::TextArray(values) => query.bind(values),
+    let ListingQueries {
+        page: (page_sql, page_binds),
+        count: (count_sql, count_binds),
+    } = queries;
+    let mut page = sqlx::query(sqlx::AssertSqlSafe(page_sql));
+    for value in page_binds {
+        page = match value {
+            BindValue::Text(s) => page.bind(s),
+            BindValue::TextArray(values) => page.bind(values),
         };
     }
-    let rows = query.fetch_all(pool).await?;
-    let total_count = match rows.first() {
-        Some(row) => row.try_get("total_count")?,
-        None => 0,
-    };
+    let mut count = sqlx::query_scalar(sqlx::AssertSqlSafe(count_sql));
+    for value in count_binds {
+        count = match value {
+            BindValue::Text(s) => count.bind(s),
+            BindValue::TextArray(values) => count.bind(values),
+        };
+    }
+    let (rows, total_count) = futures::try_join!(page.fetch_all(pool), count.fetch_one(pool))?;
     let items = rows.iter().map(O::from_row).collect::<Result<_, _>>()?;
-    Ok((items, total_count))
+    let search_matches = rows
+        .iter()
+        .filter(|row| row.try_column("search_rank").is_ok())
+        .map(|row| {
+            Ok(TextSearchMatch {
+                id: row.try_get("id")?,
+                rank: row.try_get("search_rank")?,
+                snippet: row
+                    .try_get::<Option<String>, _>("search_snippet")?
+                    .unwrap_or_default(),
+            })
+        })
+        .collect::<Result<_, sqlx::Error>>()?;
+    let last_keys = rows.last().and_then(|row| row.try_get("keyset_keys").ok());
+    Ok((items, total_count, search_matches, last_keys))
+}
+
+/// Returns a copy of the filters with one dimension cleared by `clear`, used to compute the facets
+/// of that dimension under every other active filter.
+pub(crate) fn without_filter<F: Clone>(filters: &F, clear: impl FnOnce(&mut F)) -> F {
+    let mut filters = filters.clone();
+    clear(&mut filters);
+    filters
+}
+
+/// Executes a facet query built by `raw_query_builder`.
+pub(crate) async fn fetch_facet_values(
+    pool: &PgPool,
+    (sql, binds): (String, Vec<BindValue>),
+) -> Result<Vec<FacetValue>> {
+    fetch_all_with_binds(pool, sql, binds).await
+}
+
+/// Executes a range query built by `raw_query_builder`, returning its MIN and MAX.
+pub(crate) async fn fetch_range_with_binds<T>(
+    pool: &PgPool,
+    (sql, binds): (String, Vec<BindValue>),
+) -> Result<(T, T)>
+where
+    (T, T): for<'r> sqlx::FromRow<'r, PgRow> + Send + Unpin,
+{
+    fetch_all_with_binds(pool, sql, binds)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Range query returned no rows"))
 }
 
 /// Fetches traits for any entity using the shared `{gs}_trait_{entity}_association_table` convention.
@@ -78,42 +135,178 @@ pub(crate) async fn fetch_entity_traits(
     .await?)
 }
 
-#[derive(sqlx::FromRow)]
-struct EntityTraitRow {
-    entity_id: i64,
-    name: String,
-    description: Option<String>,
-    display_name: Option<String>,
+/// Row of a batched query, tagged with the id of the entity it belongs to.
+/// The query must select that id as `owner_id`, alongside the columns `T` is built from.
+pub(crate) struct OwnedRow<T> {
+    pub owner_id: i64,
+    pub data: T,
+}
+
+impl<'r, T: FromRow<'r, PgRow>> FromRow<'r, PgRow> for OwnedRow<T> {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            owner_id: row.try_get("owner_id")?,
+            data: T::from_row(row)?,
+        })
+    }
+}
+
+/// Runs `query` with `owner_ids` bound as `$1` (to be matched with `= ANY($1)`),
+/// grouping the rows by their `owner_id` while keeping the order they were returned in.
+pub(crate) async fn fetch_grouped_by_owner<T>(
+    pool: &PgPool,
+    query: String,
+    owner_ids: &[i64],
+) -> Result<HashMap<i64, Vec<T>>>
+where
+    T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+{
+    if owner_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows: Vec<OwnedRow<T>> = sqlx::query_as(sqlx::AssertSqlSafe(query))
+        .bind(owner_ids)
+        .fetch_all(pool)
+        .await?;
+    Ok(group_by_owner(
+        rows.into_iter().map(|x| (x.owner_id, x.data)),
+    ))
+}
+
+/// Like `fetch_grouped_by_owner`, for queries selecting the owner id and a single value.
+pub(crate) async fn fetch_grouped_values<T>(
+    pool: &PgPool,
+    query: String,
+    owner_ids: &[i64],
+) -> Result<HashMap<i64, Vec<T>>>
+where
+    (i64, T): for<'r> FromRow<'r, PgRow> + Send + Unpin,
+{
+    if owner_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows: Vec<(i64, T)> = sqlx::query_as(sqlx::AssertSqlSafe(query))
+        .bind(owner_ids)
+        .fetch_all(pool)
+        .await?;
+    Ok(group_by_owner(rows))
+}
+
+fn group_by_owner<T>(rows: impl IntoIterator<Item = (i64, T)>) -> HashMap<i64, Vec<T>> {
+    let mut by_owner: HashMap<i64, Vec<T>> = HashMap::new();
+    for (owner_id, data) in rows {
+        by_owner.entry(owner_id).or_default().push(data);
+    }
+    by_owner
 }
 
 /// Fetches traits for every id in `entity_ids` in a single round trip, keyed by entity id.
 /// Used by `enrich_with_traits` to avoid issuing one query per row of a listing page.
-async fn fetch_entity_traits_batch(
+pub(crate) async fn fetch_entity_traits_batch(
     pool: &PgPool,
     gs: GameSystem,
     entity: &str,
     entity_ids: &[i64],
 ) -> Result<HashMap<i64, Vec<TraitData>>> {
-    if entity_ids.is_empty() {
-        return Ok(HashMap::new());
-    }
-    let rows: Vec<EntityTraitRow> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
-        "SELECT a.{entity}_id AS entity_id, t.name, t.description, t.display_name
-        FROM {gs}_trait_table t JOIN {gs}_trait_{entity}_association_table a ON a.trait_id = t.name
-        WHERE a.{entity}_id = ANY($1) ORDER BY t.name"
-    )))
-    .bind(entity_ids)
-    .fetch_all(pool)
+    fetch_grouped_by_owner(
+        pool,
+        format!(
+            "SELECT a.{entity}_id AS owner_id, t.name, t.description, t.display_name
+            FROM {gs}_trait_table t JOIN {gs}_trait_{entity}_association_table a ON a.trait_id = t.name
+            WHERE a.{entity}_id = ANY($1) ORDER BY t.name"
+        ),
+        entity_ids,
+    )
+    .await
+}
+
+/// Fetches the actions linked to every id in `owner_ids` through `{gs}_{association_table}`,
+/// along with their traits, in two round trips.
+pub(crate) async fn fetch_actions_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    association_table: &str,
+    owner_column: &str,
+    owner_ids: &[i64],
+) -> Result<HashMap<i64, Vec<Action>>> {
+    let core_actions: HashMap<i64, Vec<CoreAction>> = fetch_grouped_by_owner(
+        pool,
+        format!(
+            "SELECT x.{owner_column} AS owner_id, a.* FROM {gs}_action_table AS a
+            JOIN {gs}_{association_table} AS x ON x.action_id = a.id
+            WHERE x.{owner_column} = ANY($1) ORDER BY a.id"
+        ),
+        owner_ids,
+    )
     .await?;
-    let mut by_id: HashMap<i64, Vec<TraitData>> = HashMap::new();
-    for row in rows {
-        by_id.entry(row.entity_id).or_default().push(TraitData {
-            name: row.name,
-            description: row.description,
-            display_name: row.display_name,
-        });
-    }
-    Ok(by_id)
+    let action_ids: Vec<i64> = core_actions.values().flatten().map(|x| x.id).collect();
+    let traits = fetch_entity_traits_batch(pool, gs, "action", &action_ids).await?;
+    Ok(core_actions
+        .into_iter()
+        .map(|(owner_id, actions)| {
+            let actions = actions
+                .into_iter()
+                .map(|core_action| Action {
+                    traits: traits.get(&core_action.id).cloned().unwrap_or_default(),
+                    core_action,
+                })
+                .collect();
+            (owner_id, actions)
+        })
+        .collect())
+}
+
+/// Fetches the property runes of every weapon in `weapon_ids`, keyed by weapon id.
+pub(crate) async fn fetch_weapon_runes_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    weapon_ids: &[i64],
+) -> Result<HashMap<i64, Vec<String>>> {
+    fetch_grouped_values(
+        pool,
+        format!(
+            "SELECT a.weapon_id, r.name FROM {gs}_rune_table r
+            JOIN {gs}_rune_weapon_association_table a ON a.rune_id = r.name
+            WHERE a.weapon_id = ANY($1) ORDER BY r.name"
+        ),
+        weapon_ids,
+    )
+    .await
+}
+
+/// Fetches the property runes of every armor in `armor_ids`, keyed by armor id.
+pub(crate) async fn fetch_armor_runes_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    armor_ids: &[i64],
+) -> Result<HashMap<i64, Vec<String>>> {
+    fetch_grouped_values(
+        pool,
+        format!(
+            "SELECT a.armor_id, r.name FROM {gs}_rune_table r
+            JOIN {gs}_rune_armor_association_table a ON a.rune_id = r.name
+            WHERE a.armor_id = ANY($1) ORDER BY r.name"
+        ),
+        armor_ids,
+    )
+    .await
+}
+
+/// Fetches the damage of every weapon in `weapon_ids`, keyed by weapon id.
+pub(crate) async fn fetch_weapon_damage_data_batch(
+    pool: &PgPool,
+    gs: GameSystem,
+    weapon_ids: &[i64],
+) -> Result<HashMap<i64, Vec<DamageData>>> {
+    fetch_grouped_by_owner(
+        pool,
+        format!(
+            "SELECT weapon_id AS owner_id, id, bonus_dmg, dmg_type, number_of_dice, die_size
+            FROM {gs}_weapon_damage_table WHERE weapon_id = ANY($1) ORDER BY id"
+        ),
+        weapon_ids,
+    )
+    .await
 }
 
 pub async fn fetch_item_traits(
@@ -246,6 +439,63 @@ pub async fn fetch_unique_values_of_field(
         .await?)
 }
 
+/// Union of the names that can be suggested, with their id and entity type.
+fn prepare_autocomplete_entries_query(gs: GameSystem) -> String {
+    format!(
+        "SELECT id, name, 'Creature' AS entity_type FROM {gs}_creature_core WHERE status = 'valid'
+        UNION ALL SELECT id, name, 'Hazard' AS entity_type FROM {gs}_hazard_table
+        UNION ALL SELECT id, name, 'Item' AS entity_type FROM {gs}_item_table
+            WHERE is_derived = false AND status = 'valid'
+        UNION ALL SELECT NULL::bigint AS id, name, 'Trait' AS entity_type FROM {gs}_trait_table"
+    )
+}
+
+/// Fetches every name that can be suggested, to be ranked in memory.
+pub async fn fetch_autocomplete_index(
+    pool: &PgPool,
+    gs: GameSystem,
+) -> Result<Vec<AutocompleteSuggestion>> {
+    Ok(
+        sqlx::query_as(sqlx::AssertSqlSafe(prepare_autocomplete_entries_query(gs)))
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+/// Fetches the best `limit` suggestions for `query`, ranked like `rank_autocomplete_suggestions`
+/// does: prefix matches, then substring matches, then trigram similarity.
+///
+/// Matches are found with `ILIKE` and the `<%` operator, both served by the trigram indexes,
+/// `word_similarity` is only computed to rank them.
+pub async fn fetch_autocomplete_suggestions(
+    pool: &PgPool,
+    gs: GameSystem,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<AutocompleteSuggestion>> {
+    let entries = prepare_autocomplete_entries_query(gs);
+    let mut tx = pool.begin().await?;
+    sqlx::query(sqlx::AssertSqlSafe(format!(
+        "SET LOCAL pg_trgm.word_similarity_threshold = {FUZZY_NAME_THRESHOLD}"
+    )))
+    .execute(&mut *tx)
+    .await?;
+    let suggestions = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "SELECT id, name, entity_type FROM ({entries}) entries
+        WHERE name ILIKE $1 ESCAPE '\\' OR $3 <% name
+        ORDER BY name ILIKE $2 ESCAPE '\\' DESC, name ILIKE $1 ESCAPE '\\' DESC,
+            word_similarity($3, name) DESC, LENGTH(name), name
+        LIMIT {limit}"
+    )))
+    .bind(format!("%{}%", escape_like_pattern(query)))
+    .bind(format!("{}%", escape_like_pattern(query)))
+    .bind(query)
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(suggestions)
+}
+
 /// Fetches MIN and MAX of an integer column in a single round trip.
 /// `from_clause` is the `FROM table WHERE ...` portion of the SQL.
 pub async fn fetch_col_range(pool: &PgPool, column: &str, from_clause: &str) -> Result<(i64, i64)> {
@@ -1,21 +1,30 @@
 use crate::db::data_providers::generic_fetcher::{
-    enrich_with_traits, fetch_action_traits, fetch_all_with_binds, fetch_all_with_binds_and_count,
-    fetch_col_range, fetch_entity_traits,
+    enrich_with_traits, fetch_action_traits, fetch_actions_batch, fetch_all_with_binds,
+    fetch_all_with_binds_and_count, fetch_col_range, fetch_entity_traits, fetch_facet_values,
+    fetch_range_with_binds, without_filter,
 };
 use crate::db::data_providers::raw_query_builder::{
-    format_pagination_clause, prepare_filtered_get_hazards, prepare_paginated_get_hazards_listing,
+    format_pagination_clause, prepare_filtered_get_hazards, prepare_hazard_facet_query,
+    prepare_hazard_range_query, prepare_hazard_trait_facet_query,
+    prepare_paginated_get_hazards_listing,
 };
 use crate::models::hazard::hazard_field_filter::HazardFieldFilters;
 use crate::models::hazard::hazard_listing_struct::{HazardFilterQuery, HazardSortEnum};
 use crate::models::hazard::hazard_struct::{Hazard, HazardRanges};
 use crate::models::response_data::ResponseHazard;
-use crate::models::routers_validator_structs::OrderEnum;
 use crate::models::shared::action::{Action, CoreAction};
 use crate::models::shared::alignment_enum::ALIGNMENT_TRAITS;
+use crate::models::shared::facets::HazardFacets;
 use crate::models::shared::game_system_enum::GameSystem;
+use crate::models::shared::listing_cursor::ListingCursor;
+use crate::models::shared::sort_spec::SortKeySpec;
+use crate::models::shared::text_search::TextSearchMatch;
 use crate::models::shared::trait_data::TraitData;
 use anyhow::Result;
+use futures::try_join;
+use itertools::Itertools;
 use sqlx::PgPool;
+use std::collections::HashMap;
 
 async fn fetch_hazard_actions(
     pool: &PgPool,
@@ -93,6 +102,46 @@ pub async fn fetch_hazard_by_id(pool: &PgPool, gs: GameSystem, id: i64) -> Resul
     })
 }
 
+/// Gets every hazard of `ids` that exists, in the same order (repeated ids are repeated),
+/// with their traits and actions loaded by `= ANY($1)` queries.
+pub async fn fetch_hazards_by_ids(
+    pool: &PgPool,
+    gs: GameSystem,
+    ids: &[i64],
+) -> Result<Vec<ResponseHazard>> {
+    let unique_ids: Vec<i64> = ids.iter().copied().unique().collect();
+    let hazards: Vec<Hazard> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "SELECT * FROM {gs}_hazard_table WHERE id = ANY($1)"
+    )))
+    .bind(&unique_ids)
+    .fetch_all(pool)
+    .await?;
+    let mut actions = fetch_actions_batch(
+        pool,
+        gs,
+        "action_hazard_association_table",
+        "hazard_id",
+        &unique_ids,
+    )
+    .await?;
+    let hazards: HashMap<i64, Hazard> = enrich_with_traits(pool, gs, hazards, false)
+        .await
+        .into_iter()
+        .map(|mut x| {
+            x.actions = actions.remove(&x.essential.id).unwrap_or_default();
+            (x.essential.id, x)
+        })
+        .collect();
+    Ok(ids
+        .iter()
+        .filter_map(|id| hazards.get(id).cloned())
+        .map(|core_hazard| ResponseHazard {
+            core_hazard,
+            game: gs,
+        })
+        .collect())
+}
+
 pub async fn fetch_hazard_core_data_with_filters(
     pool: &PgPool,
     gs: GameSystem,
@@ -120,23 +169,23 @@ pub async fn fetch_hazards_data(
 }
 
 /// Returns the requested page of hazards alongside the total count of hazards matching
-/// `filters` (ignoring pagination), fetched in a single round trip via `COUNT(*) OVER()`.
+/// `filters` (ignoring pagination), fetched concurrently.
 pub async fn fetch_paginated_hazards(
     pool: &PgPool,
     gs: GameSystem,
     filters: &HazardFieldFilters,
-    sort_by: HazardSortEnum,
-    order_by: OrderEnum,
-    cursor: u32,
+    sort: &[SortKeySpec<HazardSortEnum>],
+    cursor: &ListingCursor,
     page_size: i16,
-) -> Result<(Vec<Hazard>, i64)> {
-    let (query, binds) =
-        prepare_paginated_get_hazards_listing(gs, filters, sort_by, order_by, cursor, page_size);
-    let (hazards, total_count): (Vec<Hazard>, i64) =
-        fetch_all_with_binds_and_count(pool, query, binds).await?;
+) -> Result<(Vec<Hazard>, i64, Vec<TextSearchMatch>, Option<String>)> {
+    let queries = prepare_paginated_get_hazards_listing(gs, filters, sort, cursor, page_size)?;
+    let (hazards, total_count, search_matches, last_keys) =
+        fetch_all_with_binds_and_count::<Hazard>(pool, queries).await?;
     Ok((
         update_hazards_core_with_traits(pool, gs, hazards).await,
         total_count,
+        search_matches,
+        last_keys,
     ))
 }
 
@@ -169,3 +218,166 @@ pub async fn fetch_hazard_ranges(pool: &PgPool, gs: GameSystem) -> Result<Hazard
         max_fortitude,
     })
 }
+
+/// Returns, for every filterable dimension, the values available under the other active filters
+/// and the number of hazards they would lead to.
+pub async fn fetch_hazard_facets(
+    pool: &PgPool,
+    gs: GameSystem,
+    filters: &HazardFieldFilters,
+) -> Result<HazardFacets> {
+    let (source, rarity, size, traits, level, ranges) = try_join!(
+        fetch_facet_values(
+            pool,
+            prepare_hazard_facet_query(
+                gs,
+                &without_filter(filters, |f| f.source_filter = None),
+                "source",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_hazard_facet_query(
+                gs,
+                &without_filter(filters, |f| f.rarity_filter = None),
+                "rarity",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_hazard_facet_query(
+                gs,
+                &without_filter(filters, |f| f.size_filter = None),
+                "size",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_hazard_trait_facet_query(
+                gs,
+                &without_filter(filters, |f| {
+                    f.trait_whitelist_filter = None;
+                    f.trait_blacklist_filter = None;
+                    f.trait_expression_filter = None;
+                }),
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_hazard_facet_query(
+                gs,
+                &without_filter(filters, |f| {
+                    f.min_level_filter = None;
+                    f.max_level_filter = None;
+                }),
+                "level",
+            ),
+        ),
+        fetch_filtered_hazard_ranges(pool, gs, filters),
+    )?;
+    Ok(HazardFacets {
+        source,
+        rarity,
+        size,
+        traits,
+        level,
+        ranges,
+    })
+}
+
+/// Like `fetch_hazard_ranges`, but only over the hazards passing the filters.
+/// The range of each stat ignores the bounds on that same stat.
+pub async fn fetch_filtered_hazard_ranges(
+    pool: &PgPool,
+    gs: GameSystem,
+    filters: &HazardFieldFilters,
+) -> Result<HazardRanges> {
+    let range = |column: &'static str, f: HazardFieldFilters| {
+        fetch_range_with_binds::<i64>(pool, prepare_hazard_range_query(gs, &f, column))
+    };
+    let (
+        (min_ac, max_ac),
+        (min_hardness, max_hardness),
+        (min_hp, max_hp),
+        (min_stealth, max_stealth),
+        (min_level, max_level),
+        (min_will, max_will),
+        (min_reflex, max_reflex),
+        (min_fortitude, max_fortitude),
+    ) = try_join!(
+        range(
+            "ac",
+            without_filter(filters, |f| {
+                f.min_ac_filter = None;
+                f.max_ac_filter = None;
+            }),
+        ),
+        range(
+            "hardness",
+            without_filter(filters, |f| {
+                f.min_hardness_filter = None;
+                f.max_hardness_filter = None;
+            }),
+        ),
+        range(
+            "hp",
+            without_filter(filters, |f| {
+                f.min_hp_filter = None;
+                f.max_hp_filter = None;
+            }),
+        ),
+        range(
+            "stealth",
+            without_filter(filters, |f| {
+                f.min_stealth_filter = None;
+                f.max_stealth_filter = None;
+            }),
+        ),
+        range(
+            "level",
+            without_filter(filters, |f| {
+                f.min_level_filter = None;
+                f.max_level_filter = None;
+            }),
+        ),
+        range(
+            "will",
+            without_filter(filters, |f| {
+                f.min_will_filter = None;
+                f.max_will_filter = None;
+            }),
+        ),
+        range(
+            "reflex",
+            without_filter(filters, |f| {
+                f.min_reflex_filter = None;
+                f.max_reflex_filter = None;
+            }),
+        ),
+        range(
+            "fortitude",
+            without_filter(filters, |f| {
+                f.min_fortitude_filter = None;
+                f.max_fortitude_filter = None;
+            }),
+        ),
+    )?;
+    Ok(HazardRanges {
+        min_ac,
+        max_ac,
+        min_hardness,
+        max_hardness,
+        min_hp,
+        max_hp,
+        min_stealth,
+        max_stealth,
+        min_level,
+        max_level,
+        min_will,
+        max_will,
+        min_reflex,
+        max_reflex,
+        min_fortitude,
+        max_fortitude,
+    })
+}
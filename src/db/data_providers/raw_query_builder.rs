@@ -1,8 +1,12 @@
+use crate::db::text_search_initializer::TEXT_SEARCH_CONFIG;
 use crate::models::bestiary_structs::{
     BestiaryFilterQuery, CreatureSortEnum, CreatureTableFieldsFilter,
 };
 use crate::models::creature::creature_field_filter::CreatureFieldFilters;
 use crate::models::creature::creature_metadata::creature_role::CreatureRoleEnum;
+use crate::models::creature::creature_metadata::spellcasting_enum::{
+    SpellTraditionEnum, SpellcasterTypeEnum,
+};
 use crate::models::hazard::hazard_field_filter::{HazardComplexityEnum, HazardFieldFilters};
 use crate::models::hazard::hazard_listing_struct::{
     HazardFilterQuery, HazardSortEnum, HazardTableFieldsFilter,
@@ -12,7 +16,14 @@ use crate::models::item::item_metadata::type_enum::ItemTypeEnum;
 use crate::models::item::shop_structs::{ItemSortEnum, ItemTableFieldsFilter, ShopFilterQuery};
 use crate::models::routers_validator_structs::OrderEnum;
 use crate::models::shared::game_system_enum::GameSystem;
+use crate::models::shared::listing_cursor::{KeysetCursor, ListingCursor};
+use crate::models::shared::name_match_mode_enum::{FUZZY_NAME_THRESHOLD, NameMatchModeEnum};
 use crate::models::shared::pf_version_enum::GameSystemVersionEnum;
+use crate::models::shared::sort_spec::SortKeySpec;
+use crate::models::shared::trait_filter_expression::TraitFilterExpression;
+use anyhow::{Result, bail, ensure};
+use serde_json::Value;
+use strum::IntoEnumIterator;
 use tracing::debug;
 
 /// A value bound to a query built by this module, paired with a
@@ -67,45 +78,35 @@ pub fn prepare_filtered_get_items(
         gs,
         &ItemTypeEnum::Equipment,
         shop_filter_query.n_of_equipment,
-        &shop_filter_query.item_table_fields_filter,
-        shop_filter_query.trait_whitelist_filter.iter(),
-        shop_filter_query.trait_blacklist_filter.iter(),
+        shop_filter_query,
         &mut binds,
     );
     let consumable_query = prepare_item_subquery(
         gs,
         &ItemTypeEnum::Consumable,
         shop_filter_query.n_of_consumables,
-        &shop_filter_query.item_table_fields_filter,
-        shop_filter_query.trait_whitelist_filter.iter(),
-        shop_filter_query.trait_blacklist_filter.iter(),
+        shop_filter_query,
         &mut binds,
     );
     let weapon_query = prepare_item_subquery(
         gs,
         &ItemTypeEnum::Weapon,
         shop_filter_query.n_of_weapons,
-        &shop_filter_query.item_table_fields_filter,
-        shop_filter_query.trait_whitelist_filter.iter(),
-        shop_filter_query.trait_blacklist_filter.iter(),
+        shop_filter_query,
         &mut binds,
     );
     let armor_query = prepare_item_subquery(
         gs,
         &ItemTypeEnum::Armor,
         shop_filter_query.n_of_armors,
-        &shop_filter_query.item_table_fields_filter,
-        shop_filter_query.trait_whitelist_filter.iter(),
-        shop_filter_query.trait_blacklist_filter.iter(),
+        shop_filter_query,
         &mut binds,
     );
     let shield_query = prepare_item_subquery(
         gs,
         &ItemTypeEnum::Shield,
         shop_filter_query.n_of_shields,
-        &shop_filter_query.item_table_fields_filter,
-        shop_filter_query.trait_whitelist_filter.iter(),
-        shop_filter_query.trait_blacklist_filter.iter(),
+        shop_filter_query,
         &mut binds,
     );
     let query = format!(
@@ -129,6 +130,7 @@ pub fn prepare_filtered_get_creatures_core(
     // appear in `where_query` below, so that bind values line up with their
     // placeholders once `finalize_placeholders` numbers them.
     let creature_fields_filter_query = prepare_creature_filter_statement(
+        gs,
         &bestiary_filter_query.creature_table_fields_filter,
         &mut binds,
     );
@@ -148,8 +150,23 @@ pub fn prepare_filtered_get_creatures_core(
     } else {
         format!("AND {trait_query_tmp}")
     };
+    let trait_expression_query = bestiary_filter_query
+        .trait_expression_filter
+        .as_ref()
+        .map_or_else(String::new, |expr| {
+            format!(
+                "AND {}",
+                prepare_trait_expression_statement(
+                    gs,
+                    "creature_id",
+                    "trait_creature_association_table",
+                    expr,
+                    &mut binds,
+                )
+            )
+        });
     let where_query = format!(
-        "{initial_statement} WHERE status = 'valid' AND {creature_fields_filter_query} {trait_query}"
+        "{initial_statement} WHERE status = 'valid' AND {creature_fields_filter_query} {trait_query} {trait_expression_query}"
     );
     let query = format!(
         "
@@ -195,8 +212,24 @@ pub fn prepare_filtered_get_hazards(
     } else {
         format!("AND {trait_query_tmp}")
     };
-    let where_query =
-        format!("{initial_statement} WHERE {creature_fields_filter_query} {trait_query}");
+    let trait_expression_query = bestiary_filter_query
+        .trait_expression_filter
+        .as_ref()
+        .map_or_else(String::new, |expr| {
+            format!(
+                "AND {}",
+                prepare_trait_expression_statement(
+                    gs,
+                    "hazard_id",
+                    "trait_hazard_association_table",
+                    expr,
+                    &mut binds,
+                )
+            )
+        });
+    let where_query = format!(
+        "{initial_statement} WHERE {creature_fields_filter_query} {trait_query} {trait_expression_query}"
+    );
     let query = format!(
         "
     WITH HazardRankedByLevel AS (
@@ -364,34 +397,46 @@ where
     format!("{column_name} IN ({joined})")
 }
 
-fn prepare_item_subquery<I, S>(
+fn prepare_item_subquery(
     gs: GameSystem,
     item_type: &ItemTypeEnum,
     n_of_item: i64,
-    shop_filter_vectors: &ItemTableFieldsFilter,
-    trait_whitelist_filter: I,
-    trait_blacklist_filter: I,
+    shop_filter_query: &ShopFilterQuery,
     binds: &mut Vec<BindValue>,
-) -> String
-where
-    I: Iterator<Item = S>,
-    S: ToString,
-{
+) -> String {
     let item_type_query = prepare_get_id_matching_item_type_query(item_type, gs);
     let initial_statement = format!("SELECT id FROM {gs}_item_table");
     // Order matters: pushed in the same left-to-right order as they appear below.
-    let item_fields_filter_query = prepare_item_filter_statement(shop_filter_vectors, binds);
-    let whitelist_query = prepare_item_trait_filter(gs, trait_whitelist_filter, binds);
-    let blacklist_query = prepare_item_trait_filter(gs, trait_blacklist_filter, binds);
+    let item_fields_filter_query =
+        prepare_item_filter_statement(&shop_filter_query.item_table_fields_filter, binds);
+    let whitelist_query =
+        prepare_item_trait_filter(gs, shop_filter_query.trait_whitelist_filter.iter(), binds);
+    let blacklist_query =
+        prepare_item_trait_filter(gs, shop_filter_query.trait_blacklist_filter.iter(), binds);
     let trait_query_tmp = prepare_trait_filter_statement(&whitelist_query, &blacklist_query);
     let trait_query = if trait_query_tmp.is_empty() {
         String::new()
     } else {
         format!("AND {trait_query_tmp}")
     };
+    let trait_expression_query = shop_filter_query
+        .trait_expression_filter
+        .as_ref()
+        .map_or_else(String::new, |expr| {
+            format!(
+                "AND {}",
+                prepare_trait_expression_statement(
+                    gs,
+                    "item_id",
+                    "trait_item_association_table",
+                    expr,
+                    binds,
+                )
+            )
+        });
     format!(
         "{initial_statement} WHERE {item_fields_filter_query}
-         AND id IN ( {item_type_query} ) {trait_query} ORDER BY RANDOM() LIMIT {n_of_item}"
+         AND id IN ( {item_type_query} ) {trait_query} {trait_expression_query} ORDER BY RANDOM() LIMIT {n_of_item}"
     )
 }
 
@@ -447,6 +492,7 @@ fn prepare_item_filter_statement(
 }
 
 fn prepare_creature_filter_statement(
+    gs: GameSystem,
     bestiary_filter_vectors: &CreatureTableFieldsFilter,
     binds: &mut Vec<BindValue>,
 ) -> String {
@@ -507,6 +553,39 @@ fn prepare_creature_filter_statement(
         ),
     ]
     .into_iter()
+    .chain(prepare_creature_defense_statements(
+        gs,
+        (
+            &bestiary_filter_vectors.immunity_whitelist_filter,
+            &bestiary_filter_vectors.immunity_blacklist_filter,
+        ),
+        (
+            &bestiary_filter_vectors.resistance_whitelist_filter,
+            &bestiary_filter_vectors.resistance_blacklist_filter,
+        ),
+        (
+            &bestiary_filter_vectors.weakness_whitelist_filter,
+            &bestiary_filter_vectors.weakness_blacklist_filter,
+        ),
+        binds,
+    ))
+    .chain(prepare_creature_environment_statements(
+        gs,
+        &bestiary_filter_vectors.speed_type_filter,
+        bestiary_filter_vectors.min_speed_filter,
+        &bestiary_filter_vectors.sense_filter,
+        &bestiary_filter_vectors.language_filter,
+        binds,
+    ))
+    .chain(prepare_creature_spellcasting_statements(
+        gs,
+        &bestiary_filter_vectors.spell_tradition_filter,
+        &bestiary_filter_vectors.spellcaster_type_filter,
+        &bestiary_filter_vectors.spell_name_filter,
+        bestiary_filter_vectors.min_spell_dc_filter,
+        bestiary_filter_vectors.max_spell_dc_filter,
+        binds,
+    ))
     .filter(|query| !query.is_empty())
     .collect::<Vec<String>>()
     .join(" AND ");
@@ -585,6 +664,170 @@ fn prepare_hazard_filter_statement(
     }
 }
 
+/// Prepares the statements filtering creatures by their immunities, resistances and weaknesses.
+/// Each pair is (whitelist, blacklist): a whitelist keeps the creatures having at least one of
+/// the given defenses, a blacklist excludes them. Example
+/// ```SQL
+/// id NOT IN (SELECT creature_id FROM pf_resistance_table WHERE UPPER(name) = ANY($1))
+/// ```
+fn prepare_creature_defense_statements(
+    gs: GameSystem,
+    immunities: (&[String], &[String]),
+    resistances: (&[String], &[String]),
+    weaknesses: (&[String], &[String]),
+    binds: &mut Vec<BindValue>,
+) -> Vec<String> {
+    [
+        (
+            "immunity_creature_association_table",
+            "immunity_id",
+            immunities,
+        ),
+        ("resistance_table", "name", resistances),
+        ("weakness_table", "name", weaknesses),
+    ]
+    .into_iter()
+    .flat_map(|(table, column, (whitelist, blacklist))| {
+        [("IN", whitelist), ("NOT IN", blacklist)].map(|(op, values)| (table, column, op, values))
+    })
+    .map(|(table, column, op, values)| {
+        let s = prepare_case_insensitive_in_statement(column, values.iter(), binds);
+        if s.is_empty() {
+            s
+        } else {
+            format!("id {op} (SELECT creature_id FROM {gs}_{table} WHERE {s})")
+        }
+    })
+    .filter(|s| !s.is_empty())
+    .collect()
+}
+
+/// Prepares the statements filtering creatures by movement, senses and languages.
+/// Every list is satisfied by at least one match; the minimum speed applies to the
+/// given speed types, or to any speed if no type is given. Example
+/// ```SQL
+/// id IN (SELECT creature_id FROM pf_speed_table WHERE UPPER(name) = ANY($1) AND value >= 30)
+/// ```
+fn prepare_creature_environment_statements(
+    gs: GameSystem,
+    speed_types: &[String],
+    min_speed: Option<i64>,
+    senses: &[String],
+    languages: &[String],
+    binds: &mut Vec<BindValue>,
+) -> Vec<String> {
+    let mut statements = vec![];
+    let speed_query = [
+        prepare_case_insensitive_in_statement("name", speed_types.iter(), binds),
+        min_speed.map_or_else(String::new, |v| format!("value >= {v}")),
+    ]
+    .into_iter()
+    .filter(|query| !query.is_empty())
+    .collect::<Vec<String>>()
+    .join(" AND ");
+    if !speed_query.is_empty() {
+        statements.push(format!(
+            "id IN (SELECT creature_id FROM {gs}_speed_table WHERE {speed_query})"
+        ));
+    }
+    let sense_query = prepare_case_insensitive_in_statement("st.name", senses.iter(), binds);
+    if !sense_query.is_empty() {
+        statements.push(format!(
+            "id IN (SELECT sca.creature_id FROM {gs}_sense_creature_association_table sca \
+             JOIN {gs}_sense_table st ON sca.sense_id = st.id WHERE {sense_query})"
+        ));
+    }
+    let language_query =
+        prepare_case_insensitive_in_statement("language_id", languages.iter(), binds);
+    if !language_query.is_empty() {
+        statements.push(format!(
+            "id IN (SELECT creature_id FROM {gs}_language_creature_association_table \
+             WHERE {language_query})"
+        ));
+    }
+    statements
+}
+
+/// Prepares the statements filtering creatures by their spellcasting.
+/// Tradition, type of spellcaster and spell DC bounds must all be satisfied by the same
+/// spellcasting entry, while the spell names can be known by any entry. Example
+/// ```SQL
+/// id IN (SELECT creature_id FROM pf_spellcasting_entry_table
+///     WHERE UPPER(spellcasting_tradition) = ANY($1) AND spellcasting_dc_mod >= 20)
+/// AND id IN (SELECT creature_id FROM pf_spell_table WHERE UPPER(name) = ANY($2))
+/// ```
+fn prepare_creature_spellcasting_statements(
+    gs: GameSystem,
+    traditions: &[SpellTraditionEnum],
+    spellcaster_types: &[SpellcasterTypeEnum],
+    spell_names: &[String],
+    min_spell_dc: Option<i64>,
+    max_spell_dc: Option<i64>,
+    binds: &mut Vec<BindValue>,
+) -> Vec<String> {
+    let mut statements = vec![];
+    let entry_query = [
+        prepare_case_insensitive_in_statement("spellcasting_tradition", traditions.iter(), binds),
+        prepare_case_insensitive_in_statement(
+            "type_of_spellcaster",
+            spellcaster_types.iter(),
+            binds,
+        ),
+        prepare_bounded_check_with_optional_limiters(
+            "spellcasting_dc_mod",
+            min_spell_dc,
+            max_spell_dc,
+        ),
+    ]
+    .into_iter()
+    .filter(|query| !query.is_empty())
+    .collect::<Vec<String>>()
+    .join(" AND ");
+    if !entry_query.is_empty() {
+        statements.push(format!(
+            "id IN (SELECT creature_id FROM {gs}_spellcasting_entry_table WHERE {entry_query})"
+        ));
+    }
+    let spell_query = prepare_case_insensitive_in_statement("name", spell_names.iter(), binds);
+    if !spell_query.is_empty() {
+        statements.push(format!(
+            "id IN (SELECT creature_id FROM {gs}_spell_table WHERE {spell_query})"
+        ));
+    }
+    statements
+}
+
+/// Prepares a statement filtering creatures by a skill modifier. Without a skill name the
+/// bounds can be satisfied by any skill, without bounds it checks that the skill is known. Example
+/// ```SQL
+/// id IN (SELECT creature_id FROM pf_skill_table WHERE UPPER(name) = UPPER($1) AND modifier >= 20)
+/// ```
+fn prepare_creature_skill_statement(
+    gs: GameSystem,
+    skill: Option<&str>,
+    min_modifier: Option<i64>,
+    max_modifier: Option<i64>,
+    binds: &mut Vec<BindValue>,
+) -> String {
+    let name_query = skill.map_or_else(String::new, |name| {
+        binds.push(BindValue::Text(name.to_string()));
+        format!("UPPER(name) = UPPER({BIND_PLACEHOLDER})")
+    });
+    let skill_query = [
+        name_query,
+        prepare_bounded_check_with_optional_limiters("modifier", min_modifier, max_modifier),
+    ]
+    .into_iter()
+    .filter(|query| !query.is_empty())
+    .collect::<Vec<String>>()
+    .join(" AND ");
+    if skill_query.is_empty() {
+        skill_query
+    } else {
+        format!("id IN (SELECT creature_id FROM {gs}_skill_table WHERE {skill_query})")
+    }
+}
+
 /// Prepares an 'in' statement, with the following logic
 /// ```SQL
 /// id NOT IN (bl_id1, bl_id2, bl_idn) AND id IN (wl_id1, wl_id2, wl_idn)
@@ -606,6 +849,84 @@ where
     }
 }
 
+/// Compiles a boolean trait expression into a statement over the entity ids, ex
+/// ```SQL
+/// (id IN (SELECT creature_id FROM pf_trait_creature_association_table WHERE UPPER(trait_id) = UPPER($1))
+/// AND NOT (id IN (SELECT creature_id FROM pf_trait_creature_association_table WHERE UPPER(trait_id) = UPPER($2))))
+/// ```
+fn prepare_trait_expression_statement(
+    gs: GameSystem,
+    id_column: &str,
+    association_table_name: &str,
+    expression: &TraitFilterExpression,
+    binds: &mut Vec<BindValue>,
+) -> String {
+    match expression {
+        TraitFilterExpression::Trait(name) => {
+            binds.push(BindValue::Text(name.clone()));
+            format!(
+                "id IN (SELECT {id_column} FROM {gs}_{association_table_name} WHERE UPPER(trait_id) = UPPER({BIND_PLACEHOLDER}))"
+            )
+        }
+        TraitFilterExpression::AllOf(children) => {
+            format!(
+                "({})",
+                prepare_trait_expression_group(
+                    gs,
+                    id_column,
+                    association_table_name,
+                    children,
+                    " AND ",
+                    binds
+                )
+            )
+        }
+        TraitFilterExpression::AnyOf(children) => {
+            format!(
+                "({})",
+                prepare_trait_expression_group(
+                    gs,
+                    id_column,
+                    association_table_name,
+                    children,
+                    " OR ",
+                    binds
+                )
+            )
+        }
+        TraitFilterExpression::NoneOf(children) => {
+            format!(
+                "NOT ({})",
+                prepare_trait_expression_group(
+                    gs,
+                    id_column,
+                    association_table_name,
+                    children,
+                    " OR ",
+                    binds
+                )
+            )
+        }
+    }
+}
+
+fn prepare_trait_expression_group(
+    gs: GameSystem,
+    id_column: &str,
+    association_table_name: &str,
+    children: &[TraitFilterExpression],
+    separator: &str,
+    binds: &mut Vec<BindValue>,
+) -> String {
+    children
+        .iter()
+        .map(|x| {
+            prepare_trait_expression_statement(gs, id_column, association_table_name, x, binds)
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
 fn prepare_get_id_matching_item_type_query(item_type: &ItemTypeEnum, gs: GameSystem) -> String {
     let (item_id_field, type_query) = match item_type {
         ItemTypeEnum::Consumable | ItemTypeEnum::Equipment => {
@@ -718,6 +1039,346 @@ const fn order_direction(order_by: OrderEnum) -> &'static str {
     }
 }
 
+/// A key of a listing ordering. The same keys build the ORDER BY, the `keyset_keys` returned
+/// with every row and the condition that resumes the listing after a given row.
+struct SortKey {
+    expression: String,
+    /// Type the key value read back from a page token is cast to
+    sql_type: &'static str,
+    order_by: OrderEnum,
+    explicit_nulls_last: bool,
+    /// Columns that are never NULL, ex the id, need no NULL branch when resuming after them
+    non_null: bool,
+}
+
+impl SortKey {
+    fn new(expression: impl Into<String>, sql_type: &'static str, order_by: OrderEnum) -> Self {
+        Self {
+            expression: expression.into(),
+            sql_type,
+            order_by,
+            explicit_nulls_last: false,
+            non_null: false,
+        }
+    }
+
+    const fn nulls_last(mut self) -> Self {
+        self.explicit_nulls_last = true;
+        self
+    }
+
+    const fn non_null(mut self) -> Self {
+        self.non_null = true;
+        self
+    }
+
+    /// Postgres puts NULLs last when ascending and first when descending, unless told otherwise
+    const fn sorts_nulls_last(&self) -> bool {
+        self.explicit_nulls_last || matches!(self.order_by, OrderEnum::Ascending)
+    }
+
+    fn order_term(&self) -> String {
+        let nulls = if self.explicit_nulls_last {
+            " NULLS LAST"
+        } else {
+            ""
+        };
+        format!(
+            "{} {}{nulls}",
+            self.expression,
+            order_direction(self.order_by)
+        )
+    }
+
+    fn cast_placeholder(&self, value: &str, binds: &mut Vec<BindValue>) -> String {
+        binds.push(BindValue::Text(value.to_string()));
+        format!("CAST({BIND_PLACEHOLDER} AS {})", self.sql_type)
+    }
+
+    /// Rows whose key comes strictly after `value`
+    fn after(&self, value: Option<&str>, binds: &mut Vec<BindValue>) -> String {
+        let e = &self.expression;
+        match value {
+            None if self.sorts_nulls_last() => "FALSE".to_string(),
+            None => format!("{e} IS NOT NULL"),
+            Some(v) => {
+                let op = match self.order_by {
+                    OrderEnum::Ascending => ">",
+                    OrderEnum::Descending => "<",
+                };
+                let comparison = format!("{e} {op} {}", self.cast_placeholder(v, binds));
+                if self.sorts_nulls_last() && !self.non_null {
+                    format!("({comparison} OR {e} IS NULL)")
+                } else {
+                    comparison
+                }
+            }
+        }
+    }
+
+    /// Rows whose key is equal to `value`
+    fn equal(&self, value: Option<&str>, binds: &mut Vec<BindValue>) -> String {
+        let e = &self.expression;
+        value.map_or_else(
+            || format!("{e} IS NULL"),
+            |v| format!("{e} = {}", self.cast_placeholder(v, binds)),
+        )
+    }
+}
+
+/// Expands every key of the sort spec with `to_keys`, then appends the id as the final
+/// tie breaker, so that every row has a unique position
+fn prepare_sort_keys<S>(
+    sort: &[SortKeySpec<S>],
+    to_keys: impl Fn(&S, OrderEnum) -> Vec<SortKey>,
+) -> Vec<SortKey> {
+    let mut keys: Vec<SortKey> = sort
+        .iter()
+        .flat_map(|k| to_keys(&k.sort_by, k.order_by))
+        .collect();
+    if !keys.iter().any(|k| k.expression == "id") {
+        let order_by = sort.last().map(|k| k.order_by).unwrap_or_default();
+        keys.push(SortKey::new("id", "bigint", order_by).non_null());
+    }
+    keys
+}
+
+/// Prepares the condition matching the rows that come after the cursor row, ex
+/// for `level ASC, id ASC`
+/// ```SQL
+/// (level > CAST($1 AS bigint)) OR (level = CAST($2 AS bigint) AND id > CAST($3 AS bigint))
+/// ```
+fn prepare_keyset_condition(
+    keys: &[SortKey],
+    cursor: &KeysetCursor,
+    binds: &mut Vec<BindValue>,
+) -> Result<String> {
+    ensure!(
+        cursor.keys.len() == keys.len(),
+        "The page token does not match the requested listing"
+    );
+    let values = cursor
+        .keys
+        .iter()
+        .map(|v| match v {
+            Value::Null => Ok(None),
+            Value::String(s) => Ok(Some(s.clone())),
+            Value::Number(_) | Value::Bool(_) => Ok(Some(v.to_string())),
+            _ => bail!("The page token contains an invalid sort key"),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let alternatives: Vec<String> = (0..keys.len())
+        .map(|i| {
+            let mut parts: Vec<String> = keys[..i]
+                .iter()
+                .zip(&values)
+                .map(|(k, v)| k.equal(v.as_deref(), binds))
+                .collect();
+            parts.push(keys[i].after(values[i].as_deref(), binds));
+            format!("({})", parts.join(" AND "))
+        })
+        .collect();
+    Ok(format!("({})", alternatives.join(" OR ")))
+}
+
+/// Escapes the `LIKE` wildcards of `value`, so that it is matched literally with `ESCAPE '\'`
+pub fn escape_like_pattern(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Prepares the name check, a case-insensitive substring match that in fuzzy mode also accepts
+/// names with a trigram word similarity above the threshold, ex
+/// ```SQL
+/// (name ILIKE $1 ESCAPE '\' OR word_similarity($2, name) >= 0.3)
+/// ```
+fn prepare_name_statement(
+    name: &str,
+    match_mode: NameMatchModeEnum,
+    binds: &mut Vec<BindValue>,
+) -> String {
+    binds.push(BindValue::Text(format!("%{}%", escape_like_pattern(name))));
+    match match_mode {
+        NameMatchModeEnum::Substring => format!("name ILIKE {BIND_PLACEHOLDER} ESCAPE '\\'"),
+        NameMatchModeEnum::Fuzzy => {
+            binds.push(BindValue::Text(name.to_string()));
+            format!(
+                "(name ILIKE {BIND_PLACEHOLDER} ESCAPE '\\' OR word_similarity({BIND_PLACEHOLDER}, name) >= {FUZZY_NAME_THRESHOLD})"
+            )
+        }
+    }
+}
+
+/// Prepares the full-text search CTE over `{gs}_{entity}_search_table`, ex
+/// ```SQL
+/// WITH text_search AS (
+///     SELECT id AS search_id, content AS search_content, tsq AS search_query,
+///         ts_rank(document, tsq) AS search_rank
+///     FROM pf_creature_search_table, websearch_to_tsquery('english', $1) tsq
+///     WHERE document @@ tsq
+/// )
+/// ```
+/// Its columns are prefixed, so that it can be joined with the listed table without ambiguity.
+fn prepare_text_search_cte(
+    gs: GameSystem,
+    entity: &str,
+    text: &str,
+    binds: &mut Vec<BindValue>,
+) -> String {
+    binds.push(BindValue::Text(text.to_string()));
+    format!(
+        "WITH text_search AS (
+            SELECT id AS search_id, content AS search_content, tsq AS search_query,
+                ts_rank(document, tsq) AS search_rank
+            FROM {gs}_{entity}_search_table,
+                websearch_to_tsquery('{TEXT_SEARCH_CONFIG}', {BIND_PLACEHOLDER}) tsq
+            WHERE document @@ tsq
+        )"
+    )
+}
+
+/// What a listing is read from: `table`, optionally narrowed by a text search CTE and,
+/// for fuzzy name filters, with the `name_similarity` of every row, ex
+/// ```SQL
+/// (SELECT *, word_similarity($2, name) AS name_similarity FROM pf_creature_core) pf_creature_core
+/// ```
+/// Both precede the where clause, so their binds are pushed before its ones.
+struct ListingSource {
+    table: String,
+    text_search_cte: Option<String>,
+    name_similarity_from: Option<String>,
+}
+
+impl ListingSource {
+    fn new(
+        gs: GameSystem,
+        entity: &str,
+        table: String,
+        text_search: Option<&str>,
+        name: Option<(&str, NameMatchModeEnum)>,
+        binds: &mut Vec<BindValue>,
+    ) -> Self {
+        let text_search_cte = text_search
+            .filter(|text| !text.trim().is_empty())
+            .map(|text| prepare_text_search_cte(gs, entity, text, binds));
+        let name_similarity_from = name
+            .filter(|(_, match_mode)| *match_mode == NameMatchModeEnum::Fuzzy)
+            .map(|(name, _)| {
+                binds.push(BindValue::Text(name.to_string()));
+                format!(
+                    "(SELECT *, word_similarity({BIND_PLACEHOLDER}, name) AS name_similarity \
+                    FROM {table}) {table}"
+                )
+            });
+        Self {
+            table,
+            text_search_cte,
+            name_similarity_from,
+        }
+    }
+}
+
+/// Queries of a listing: its page and the count of every row of every page
+pub struct ListingQueries {
+    pub page: (String, Vec<BindValue>),
+    pub count: (String, Vec<BindValue>),
+}
+
+/// Prepares the paginated listing queries of `source`. With a text search CTE only the matches are
+/// listed, the most relevant first, and each row gets its `search_rank` and `search_snippet`.
+/// With a fuzzy name filter the closest names come next.
+/// Every row also gets its `keyset_keys`, the JSON array of its `sort_keys` values, and with a
+/// keyset cursor the listing resumes right after the row it was generated from: the condition
+/// sits next to the filters, so deep pages read no more rows than the first one.
+/// The count query counts every row passing the where clause, wherever the cursor is.
+/// The binds of the source and of the where clause must already be in `binds`.
+fn prepare_listing_query(
+    source: &ListingSource,
+    where_clause: &str,
+    mut sort_keys: Vec<SortKey>,
+    cursor: &ListingCursor,
+    page_size: i16,
+    mut binds: Vec<BindValue>,
+) -> Result<ListingQueries> {
+    let table = source.table.as_str();
+    let from = source.name_similarity_from.as_deref().unwrap_or(table);
+    if source.name_similarity_from.is_some() {
+        sort_keys.insert(
+            0,
+            SortKey::new("name_similarity", "real", OrderEnum::Descending).non_null(),
+        );
+    }
+    if source.text_search_cte.is_some() {
+        sort_keys.insert(
+            0,
+            SortKey::new("search_rank", "real", OrderEnum::Descending).non_null(),
+        );
+    }
+    let (cte, joined_from) = source.text_search_cte.as_deref().map_or_else(
+        || (String::new(), from.to_string()),
+        |cte| {
+            (
+                cte.to_string(),
+                format!("{from} JOIN text_search ON search_id = id"),
+            )
+        },
+    );
+    let count = (
+        finalize_placeholders(&format!(
+            "{cte} SELECT COUNT(*) FROM {joined_from} WHERE {where_clause}"
+        )),
+        binds.clone(),
+    );
+    let (keyset_condition, offset) = match cursor {
+        ListingCursor::Offset(offset) => (String::new(), *offset),
+        ListingCursor::Keyset(keyset) => (
+            format!(
+                " AND {}",
+                prepare_keyset_condition(&sort_keys, keyset, &mut binds)?
+            ),
+            0,
+        ),
+    };
+    let order_clause = sort_keys
+        .iter()
+        .map(SortKey::order_term)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let keyset_keys = sort_keys
+        .iter()
+        .map(|k| k.expression.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let keyset_keys = format!("CAST(json_build_array({keyset_keys}) AS TEXT) AS keyset_keys");
+    let pagination = format_pagination_clause(i64::from(offset), page_size);
+    let selected = if source.text_search_cte.is_some() {
+        format!(
+            "{table}.*, search_rank,
+                ts_headline('{TEXT_SEARCH_CONFIG}', search_content, search_query,
+                    'StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MaxWords=20, MinWords=5'
+                ) AS search_snippet"
+        )
+    } else {
+        "*".to_string()
+    };
+    let page = finalize_placeholders(&format!(
+        "{cte}
+        SELECT {selected}, {keyset_keys} FROM {joined_from}
+        WHERE ({where_clause}){keyset_condition}
+        ORDER BY {order_clause} {pagination}"
+    ));
+    Ok(ListingQueries {
+        page: (page, binds),
+        count,
+    })
+}
+
 fn prepare_creature_listing_where(
     gs: GameSystem,
     filters: &CreatureFieldFilters,
@@ -726,8 +1387,11 @@ fn prepare_creature_listing_where(
     let mut conditions = vec!["status = 'valid'".to_string()];
 
     if let Some(name) = &filters.name_filter {
-        binds.push(BindValue::Text(format!("%{name}%")));
-        conditions.push(format!("name ILIKE {BIND_PLACEHOLDER}"));
+        conditions.push(prepare_name_statement(
+            name,
+            filters.name_match_mode.unwrap_or_default(),
+            binds,
+        ));
     }
     if let Some(sources) = &filters.source_filter {
         let s = prepare_case_insensitive_in_statement("source", sources.iter(), binds);
@@ -779,6 +1443,51 @@ fn prepare_creature_listing_where(
     if let Some(v) = filters.max_level_filter {
         conditions.push(format!("level <= {v}"));
     }
+    if let Some(v) = filters.min_focus_points_filter {
+        conditions.push(format!("focus_points >= {v}"));
+    }
+    if let Some(v) = filters.max_focus_points_filter {
+        conditions.push(format!("focus_points <= {v}"));
+    }
+    let stats_query = [
+        ("ac", filters.min_ac_filter, filters.max_ac_filter),
+        (
+            "fortitude",
+            filters.min_fortitude_filter,
+            filters.max_fortitude_filter,
+        ),
+        (
+            "reflex",
+            filters.min_reflex_filter,
+            filters.max_reflex_filter,
+        ),
+        ("will", filters.min_will_filter, filters.max_will_filter),
+        (
+            "perception",
+            filters.min_perception_filter,
+            filters.max_perception_filter,
+        ),
+    ]
+    .into_iter()
+    .map(|(col, min, max)| prepare_bounded_check_with_optional_limiters(col, min, max))
+    .filter(|query| !query.is_empty())
+    .collect::<Vec<String>>()
+    .join(" AND ");
+    if !stats_query.is_empty() {
+        conditions.push(format!(
+            "id IN (SELECT id FROM {gs}_creature_table WHERE {stats_query})"
+        ));
+    }
+    let skill_query = prepare_creature_skill_statement(
+        gs,
+        filters.skill_filter.as_deref(),
+        filters.min_skill_modifier_filter,
+        filters.max_skill_modifier_filter,
+        binds,
+    );
+    if !skill_query.is_empty() {
+        conditions.push(skill_query);
+    }
     if let Some(attacks) = &filters.attack_data_filter {
         for (attack, has_attack) in attacks {
             if let Some(has) = has_attack {
@@ -792,6 +1501,63 @@ fn prepare_creature_listing_where(
             }
         }
     }
+    conditions.extend(prepare_creature_defense_statements(
+        gs,
+        (
+            filters
+                .immunity_whitelist_filter
+                .as_deref()
+                .unwrap_or_default(),
+            filters
+                .immunity_blacklist_filter
+                .as_deref()
+                .unwrap_or_default(),
+        ),
+        (
+            filters
+                .resistance_whitelist_filter
+                .as_deref()
+                .unwrap_or_default(),
+            filters
+                .resistance_blacklist_filter
+                .as_deref()
+                .unwrap_or_default(),
+        ),
+        (
+            filters
+                .weakness_whitelist_filter
+                .as_deref()
+                .unwrap_or_default(),
+            filters
+                .weakness_blacklist_filter
+                .as_deref()
+                .unwrap_or_default(),
+        ),
+        binds,
+    ));
+    conditions.extend(prepare_creature_environment_statements(
+        gs,
+        filters.speed_type_filter.as_deref().unwrap_or_default(),
+        filters.min_speed_filter,
+        filters.sense_filter.as_deref().unwrap_or_default(),
+        filters.language_filter.as_deref().unwrap_or_default(),
+        binds,
+    ));
+    conditions.extend(prepare_creature_spellcasting_statements(
+        gs,
+        filters
+            .spell_tradition_filter
+            .as_deref()
+            .unwrap_or_default(),
+        filters
+            .spellcaster_type_filter
+            .as_deref()
+            .unwrap_or_default(),
+        filters.spell_name_filter.as_deref().unwrap_or_default(),
+        filters.min_spell_dc_filter,
+        filters.max_spell_dc_filter,
+        binds,
+    ));
     if let Some(roles) = &filters.role_filter
         && !roles.is_empty()
     {
@@ -827,56 +1593,86 @@ fn prepare_creature_listing_where(
             ));
         }
     }
+    if let Some(expression) = &filters.trait_expression_filter {
+        conditions.push(prepare_trait_expression_statement(
+            gs,
+            "creature_id",
+            "trait_creature_association_table",
+            expression,
+            binds,
+        ));
+    }
     conditions.join(" AND ")
 }
 
-fn prepare_creature_listing_order(
+fn prepare_creature_sort_key(
     gs: GameSystem,
     sort_by: CreatureSortEnum,
     order_by: OrderEnum,
-) -> String {
-    let dir = order_direction(order_by);
+) -> Vec<SortKey> {
+    let key = |expression: &str, sql_type| SortKey::new(expression, sql_type, order_by);
     match sort_by {
-        CreatureSortEnum::Id => format!("id {dir}"),
-        CreatureSortEnum::Name => format!("name {dir}"),
-        CreatureSortEnum::Level => format!("level {dir}"),
-        CreatureSortEnum::Trait => format!(
-            "(SELECT STRING_AGG(trait_id, ', ' ORDER BY trait_id) \
-             FROM {gs}_trait_creature_association_table WHERE creature_id = id) {dir} NULLS LAST"
-        ),
-        CreatureSortEnum::Size => format!("size {dir}"),
-        CreatureSortEnum::Type => format!("cr_type {dir}"),
-        CreatureSortEnum::Hp => format!("hp {dir}"),
-        CreatureSortEnum::Rarity => format!("rarity {dir}"),
-        CreatureSortEnum::Family => format!("family {dir}"),
-        CreatureSortEnum::Alignment => format!("alignment {dir}"),
-        CreatureSortEnum::Attack => {
-            format!("is_melee {dir}, is_ranged {dir}, is_spellcaster {dir}")
-        }
-        CreatureSortEnum::Role => format!(
+        CreatureSortEnum::Id => vec![key("id", "bigint").non_null()],
+        CreatureSortEnum::Name => vec![key("name", "text")],
+        CreatureSortEnum::Level => vec![key("level", "bigint")],
+        CreatureSortEnum::Trait => vec![
+            key(
+                &format!(
+                    "(SELECT STRING_AGG(trait_id, ', ' ORDER BY trait_id) \
+                     FROM {gs}_trait_creature_association_table WHERE creature_id = id)"
+                ),
+                "text",
+            )
+            .nulls_last(),
+        ],
+        CreatureSortEnum::Size => vec![key("size", "text")],
+        CreatureSortEnum::Type => vec![key("cr_type", "text")],
+        CreatureSortEnum::Hp => vec![key("hp", "bigint")],
+        CreatureSortEnum::Rarity => vec![key("rarity", "text")],
+        CreatureSortEnum::Family => vec![key("family", "text")],
+        CreatureSortEnum::Alignment => vec![key("alignment", "text")],
+        CreatureSortEnum::Attack => vec![
+            key("is_melee", "boolean"),
+            key("is_ranged", "boolean"),
+            key("is_spellcaster", "boolean"),
+        ],
+        CreatureSortEnum::Role => vec![key(
             "GREATEST(brute_percentage, magical_striker_percentage, skill_paragon_percentage, \
-             skirmisher_percentage, sniper_percentage, soldier_percentage, spellcaster_percentage) {dir}"
-        ),
+             skirmisher_percentage, sniper_percentage, soldier_percentage, spellcaster_percentage)",
+            "bigint",
+        )],
+        CreatureSortEnum::RolePercentage(role) => vec![key(&role.to_db_column(), "bigint")],
     }
 }
 
 pub fn prepare_paginated_get_creatures_listing(
     gs: GameSystem,
     filters: &CreatureFieldFilters,
-    sort_by: CreatureSortEnum,
-    order_by: OrderEnum,
-    cursor: u32,
+    sort: &[SortKeySpec<CreatureSortEnum>],
+    cursor: &ListingCursor,
     page_size: i16,
-) -> (String, Vec<BindValue>) {
+) -> Result<ListingQueries> {
     let mut binds = Vec::new();
-    let where_clause = prepare_creature_listing_where(gs, filters, &mut binds);
-    let order_clause = prepare_creature_listing_order(gs, sort_by, order_by);
-    let pagination = format_pagination_clause(i64::from(cursor), page_size);
-    let query = format!(
-        "SELECT *, COUNT(*) OVER() AS total_count FROM {gs}_creature_core
-         WHERE {where_clause} ORDER BY {order_clause} {pagination}"
+    let source = ListingSource::new(
+        gs,
+        "creature",
+        format!("{gs}_creature_core"),
+        filters.text_search_filter.as_deref(),
+        filters
+            .name_filter
+            .as_deref()
+            .map(|name| (name, filters.name_match_mode.unwrap_or_default())),
+        &mut binds,
     );
-    (finalize_placeholders(&query), binds)
+    let where_clause = prepare_creature_listing_where(gs, filters, &mut binds);
+    prepare_listing_query(
+        &source,
+        &where_clause,
+        prepare_sort_keys(sort, |s, o| prepare_creature_sort_key(gs, *s, o)),
+        cursor,
+        page_size,
+        binds,
+    )
 }
 
 fn prepare_hazard_listing_where(
@@ -887,8 +1683,11 @@ fn prepare_hazard_listing_where(
     let mut conditions: Vec<String> = vec![];
 
     if let Some(name) = &filters.name_filter {
-        binds.push(BindValue::Text(format!("%{name}%")));
-        conditions.push(format!("name ILIKE {BIND_PLACEHOLDER}"));
+        conditions.push(prepare_name_statement(
+            name,
+            filters.name_match_mode.unwrap_or_default(),
+            binds,
+        ));
     }
     if let Some(sources) = &filters.source_filter {
         let s = prepare_case_insensitive_in_statement("source", sources.iter(), binds);
@@ -987,6 +1786,15 @@ fn prepare_hazard_listing_where(
             ));
         }
     }
+    if let Some(expression) = &filters.trait_expression_filter {
+        conditions.push(prepare_trait_expression_statement(
+            gs,
+            "hazard_id",
+            "trait_hazard_association_table",
+            expression,
+            binds,
+        ));
+    }
     if conditions.is_empty() {
         "TRUE".to_string()
     } else {
@@ -994,51 +1802,68 @@ fn prepare_hazard_listing_where(
     }
 }
 
-fn prepare_hazard_listing_order(
+fn prepare_hazard_sort_key(
     gs: GameSystem,
-    sort_by: HazardSortEnum,
+    sort_by: &HazardSortEnum,
     order_by: OrderEnum,
-) -> String {
-    let dir = order_direction(order_by);
+) -> Vec<SortKey> {
+    let key = |expression: &str, sql_type| SortKey::new(expression, sql_type, order_by);
     match sort_by {
-        HazardSortEnum::Id => format!("id {dir}"),
-        HazardSortEnum::Name => format!("name {dir}"),
-        HazardSortEnum::Ac => format!("ac {dir}"),
-        HazardSortEnum::Hardness => format!("hardness {dir}"),
-        HazardSortEnum::Hp => format!("hp {dir}"),
-        HazardSortEnum::Complexity => format!("is_complex {dir}"),
-        HazardSortEnum::Level => format!("level {dir}"),
-        HazardSortEnum::Trait => format!(
-            "(SELECT STRING_AGG(trait_id, ', ' ORDER BY trait_id) \
-             FROM {gs}_trait_hazard_association_table WHERE hazard_id = id) {dir} NULLS LAST"
-        ),
-        HazardSortEnum::Rarity => format!("rarity {dir}"),
-        HazardSortEnum::Size => format!("size {dir}"),
-        HazardSortEnum::Source => format!("source {dir}"),
-        HazardSortEnum::Fortitude => format!("fortitude {dir} NULLS LAST"),
-        HazardSortEnum::Reflex => format!("reflex {dir} NULLS LAST"),
-        HazardSortEnum::Will => format!("will {dir} NULLS LAST"),
-        HazardSortEnum::Stealth => format!("stealth {dir}"),
+        HazardSortEnum::Id => vec![key("id", "bigint").non_null()],
+        HazardSortEnum::Name => vec![key("name", "text")],
+        HazardSortEnum::Ac => vec![key("ac", "bigint")],
+        HazardSortEnum::Hardness => vec![key("hardness", "bigint")],
+        HazardSortEnum::Hp => vec![key("hp", "bigint")],
+        HazardSortEnum::Complexity => vec![key("is_complex", "boolean")],
+        HazardSortEnum::Level => vec![key("level", "bigint")],
+        HazardSortEnum::Trait => vec![
+            key(
+                &format!(
+                    "(SELECT STRING_AGG(trait_id, ', ' ORDER BY trait_id) \
+                     FROM {gs}_trait_hazard_association_table WHERE hazard_id = id)"
+                ),
+                "text",
+            )
+            .nulls_last(),
+        ],
+        HazardSortEnum::Rarity => vec![key("rarity", "text")],
+        HazardSortEnum::Size => vec![key("size", "text")],
+        HazardSortEnum::Source => vec![key("source", "text")],
+        HazardSortEnum::Fortitude => vec![key("fortitude", "bigint").nulls_last()],
+        HazardSortEnum::Reflex => vec![key("reflex", "bigint").nulls_last()],
+        HazardSortEnum::Will => vec![key("will", "bigint").nulls_last()],
+        HazardSortEnum::Stealth => vec![key("stealth", "bigint")],
     }
 }
 
 pub fn prepare_paginated_get_hazards_listing(
     gs: GameSystem,
     filters: &HazardFieldFilters,
-    sort_by: HazardSortEnum,
-    order_by: OrderEnum,
-    cursor: u32,
+    sort: &[SortKeySpec<HazardSortEnum>],
+    cursor: &ListingCursor,
     page_size: i16,
-) -> (String, Vec<BindValue>) {
+) -> Result<ListingQueries> {
     let mut binds = Vec::new();
-    let where_clause = prepare_hazard_listing_where(gs, filters, &mut binds);
-    let order_clause = prepare_hazard_listing_order(gs, sort_by, order_by);
-    let pagination = format_pagination_clause(i64::from(cursor), page_size);
-    let query = format!(
-        "SELECT *, COUNT(*) OVER() AS total_count FROM {gs}_hazard_table
-         WHERE {where_clause} ORDER BY {order_clause} {pagination}"
+    let source = ListingSource::new(
+        gs,
+        "hazard",
+        format!("{gs}_hazard_table"),
+        filters.text_search_filter.as_deref(),
+        filters
+            .name_filter
+            .as_deref()
+            .map(|name| (name, filters.name_match_mode.unwrap_or_default())),
+        &mut binds,
     );
-    (finalize_placeholders(&query), binds)
+    let where_clause = prepare_hazard_listing_where(gs, filters, &mut binds);
+    prepare_listing_query(
+        &source,
+        &where_clause,
+        prepare_sort_keys(sort, |s, o| prepare_hazard_sort_key(gs, s, o)),
+        cursor,
+        page_size,
+        binds,
+    )
 }
 
 fn prepare_item_listing_where(
@@ -1052,8 +1877,11 @@ fn prepare_item_listing_where(
     ];
 
     if let Some(name) = &filters.name_filter {
-        binds.push(BindValue::Text(format!("%{name}%")));
-        conditions.push(format!("name ILIKE {BIND_PLACEHOLDER}"));
+        conditions.push(prepare_name_statement(
+            name,
+            filters.name_match_mode.unwrap_or_default(),
+            binds,
+        ));
     }
     if let Some(categories) = &filters.category_filter
         && !categories.is_empty()
@@ -1153,51 +1981,347 @@ fn prepare_item_listing_where(
             ));
         }
     }
+    if let Some(expression) = &filters.trait_expression_filter {
+        conditions.push(prepare_trait_expression_statement(
+            gs,
+            "item_id",
+            "trait_item_association_table",
+            expression,
+            binds,
+        ));
+    }
     conditions.join(" AND ")
 }
 
-fn prepare_item_listing_order(
+fn prepare_item_sort_key(
     gs: GameSystem,
     sort_by: ItemSortEnum,
     order_by: OrderEnum,
-) -> String {
-    let dir = order_direction(order_by);
+) -> Vec<SortKey> {
+    let key = |expression: &str, sql_type| SortKey::new(expression, sql_type, order_by);
     match sort_by {
-        ItemSortEnum::Id => format!("id {dir}"),
-        ItemSortEnum::Name => format!("name {dir}"),
-        ItemSortEnum::Level => format!("level {dir}"),
-        ItemSortEnum::Trait => format!(
-            "(SELECT STRING_AGG(trait_id, ', ' ORDER BY trait_id) \
-             FROM {gs}_trait_item_association_table WHERE item_id = id) {dir} NULLS LAST"
-        ),
-        ItemSortEnum::Type => format!("item_type {dir}"),
-        ItemSortEnum::Rarity => format!("rarity {dir}"),
-        ItemSortEnum::Source => format!("source {dir}"),
+        ItemSortEnum::Id => vec![key("id", "bigint").non_null()],
+        ItemSortEnum::Name => vec![key("name", "text")],
+        ItemSortEnum::Level => vec![key("level", "bigint")],
+        ItemSortEnum::Trait => vec![
+            key(
+                &format!(
+                    "(SELECT STRING_AGG(trait_id, ', ' ORDER BY trait_id) \
+                     FROM {gs}_trait_item_association_table WHERE item_id = id)"
+                ),
+                "text",
+            )
+            .nulls_last(),
+        ],
+        ItemSortEnum::Type => vec![key("item_type", "text")],
+        ItemSortEnum::Rarity => vec![key("rarity", "text")],
+        ItemSortEnum::Source => vec![key("source", "text")],
+        ItemSortEnum::Price => vec![key("price", "bigint")],
+        ItemSortEnum::Bulk => vec![key("bulk", "float8")],
+        ItemSortEnum::Hp => vec![key("hp", "bigint")],
+        // The outer id has to be qualified, armors and shields have their own
+        ItemSortEnum::Ac => vec![
+            key(
+                &format!(
+                    "COALESCE(\
+                     (SELECT MAX(bonus_ac) FROM {gs}_armor_table WHERE base_item_id = {gs}_item_table.id), \
+                     (SELECT MAX(bonus_ac) FROM {gs}_shield_table WHERE base_item_id = {gs}_item_table.id))"
+                ),
+                "bigint",
+            )
+            .nulls_last(),
+        ],
     }
 }
 
 pub fn prepare_paginated_get_items_listing(
     gs: GameSystem,
     filters: &ItemFieldFilters,
-    sort_by: ItemSortEnum,
-    order_by: OrderEnum,
-    cursor: u32,
+    sort: &[SortKeySpec<ItemSortEnum>],
+    cursor: &ListingCursor,
     page_size: i16,
-) -> (String, Vec<BindValue>) {
+) -> Result<ListingQueries> {
     let mut binds = Vec::new();
+    let source = ListingSource::new(
+        gs,
+        "item",
+        format!("{gs}_item_table"),
+        filters.text_search_filter.as_deref(),
+        filters
+            .name_filter
+            .as_deref()
+            .map(|name| (name, filters.name_match_mode.unwrap_or_default())),
+        &mut binds,
+    );
     let where_clause = prepare_item_listing_where(gs, filters, &mut binds);
-    let order_clause = prepare_item_listing_order(gs, sort_by, order_by);
-    let pagination = format_pagination_clause(i64::from(cursor), page_size);
+    prepare_listing_query(
+        &source,
+        &where_clause,
+        prepare_sort_keys(sort, |s, o| prepare_item_sort_key(gs, *s, o)),
+        cursor,
+        page_size,
+        binds,
+    )
+}
+
+/// Narrows the where clause of facet and range queries to the text search matches,
+/// that listings get from their CTE instead, ex
+/// ```SQL
+/// (status = 'valid' AND ...) AND id IN (SELECT id FROM pf_creature_search_table
+///     WHERE document @@ websearch_to_tsquery('english', $3))
+/// ```
+fn prepare_text_search_where(
+    gs: GameSystem,
+    entity: &str,
+    where_clause: String,
+    text_search: Option<&str>,
+    binds: &mut Vec<BindValue>,
+) -> String {
+    let Some(text) = text_search.filter(|text| !text.trim().is_empty()) else {
+        return where_clause;
+    };
+    binds.push(BindValue::Text(text.to_string()));
+    format!(
+        "({where_clause}) AND id IN (SELECT id FROM {gs}_{entity}_search_table
+            WHERE document @@ websearch_to_tsquery('{TEXT_SEARCH_CONFIG}', {BIND_PLACEHOLDER}))"
+    )
+}
+
+fn prepare_creature_facet_where(
+    gs: GameSystem,
+    filters: &CreatureFieldFilters,
+    binds: &mut Vec<BindValue>,
+) -> String {
+    let where_clause = prepare_creature_listing_where(gs, filters, binds);
+    let text_search = filters.text_search_filter.as_deref();
+    prepare_text_search_where(gs, "creature", where_clause, text_search, binds)
+}
+
+fn prepare_hazard_facet_where(
+    gs: GameSystem,
+    filters: &HazardFieldFilters,
+    binds: &mut Vec<BindValue>,
+) -> String {
+    let where_clause = prepare_hazard_listing_where(gs, filters, binds);
+    let text_search = filters.text_search_filter.as_deref();
+    prepare_text_search_where(gs, "hazard", where_clause, text_search, binds)
+}
+
+fn prepare_item_facet_where(
+    gs: GameSystem,
+    filters: &ItemFieldFilters,
+    binds: &mut Vec<BindValue>,
+) -> String {
+    let where_clause = prepare_item_listing_where(gs, filters, binds);
+    let text_search = filters.text_search_filter.as_deref();
+    prepare_text_search_where(gs, "item", where_clause, text_search, binds)
+}
+
+/// Prepares the count of the entities passing the filters for every value of `value_column`, ex
+/// ```SQL
+/// SELECT CAST(rarity AS TEXT) AS value, COUNT(*) AS count FROM pf_creature_core
+/// WHERE (status = 'valid' AND ...) AND rarity IS NOT NULL GROUP BY rarity ORDER BY rarity
+/// ```
+fn prepare_facet_query(table: &str, where_clause: &str, value_column: &str) -> String {
+    format!(
+        "SELECT CAST({value_column} AS TEXT) AS value, COUNT(*) AS count FROM {table}
+         WHERE ({where_clause}) AND {value_column} IS NOT NULL
+         GROUP BY {value_column} ORDER BY {value_column}"
+    )
+}
+
+/// Prepares the count of the entities passing the filters for every trait, ex
+/// ```SQL
+/// SELECT trait_id AS value, COUNT(*) AS count FROM pf_trait_creature_association_table
+/// WHERE creature_id IN (SELECT id FROM pf_creature_core WHERE ...)
+/// GROUP BY trait_id ORDER BY trait_id
+/// ```
+fn prepare_trait_facet_query(
+    gs: GameSystem,
+    entity: &str,
+    table: &str,
+    where_clause: &str,
+) -> String {
+    format!(
+        "SELECT trait_id AS value, COUNT(*) AS count FROM {gs}_trait_{entity}_association_table
+         WHERE {entity}_id IN (SELECT id FROM {table} WHERE {where_clause})
+         GROUP BY trait_id ORDER BY trait_id"
+    )
+}
+
+/// Prepares MIN and MAX of `column` of `range_table` over the entities passing the filters, ex
+/// ```SQL
+/// SELECT COALESCE(MIN(ac), 0)::bigint, COALESCE(MAX(ac), 0)::bigint FROM pf_creature_table
+/// WHERE id IN (SELECT id FROM pf_creature_core WHERE ...)
+/// ```
+fn prepare_range_query(
+    range_table: &str,
+    id_column: &str,
+    column: &str,
+    sql_type: &str,
+    table: &str,
+    where_clause: &str,
+) -> String {
+    format!(
+        "SELECT COALESCE(MIN({column}), 0)::{sql_type}, COALESCE(MAX({column}), 0)::{sql_type}
+         FROM {range_table} WHERE {id_column} IN (SELECT id FROM {table} WHERE {where_clause})"
+    )
+}
+
+pub fn prepare_creature_facet_query(
+    gs: GameSystem,
+    filters: &CreatureFieldFilters,
+    value_column: &str,
+) -> (String, Vec<BindValue>) {
+    let mut binds = Vec::new();
+    let where_clause = prepare_creature_facet_where(gs, filters, &mut binds);
+    let query = prepare_facet_query(&format!("{gs}_creature_core"), &where_clause, value_column);
+    (finalize_placeholders(&query), binds)
+}
+
+pub fn prepare_creature_trait_facet_query(
+    gs: GameSystem,
+    filters: &CreatureFieldFilters,
+) -> (String, Vec<BindValue>) {
+    let mut binds = Vec::new();
+    let where_clause = prepare_creature_facet_where(gs, filters, &mut binds);
+    let query = prepare_trait_facet_query(
+        gs,
+        "creature",
+        &format!("{gs}_creature_core"),
+        &where_clause,
+    );
+    (finalize_placeholders(&query), binds)
+}
+
+/// Prepares the count of the creatures passing the filters for every role, a creature counts
+/// for a role if its percentage is at least the requested threshold, ex
+/// ```SQL
+/// WITH filtered AS (SELECT * FROM pf_creature_core WHERE ...)
+/// SELECT 'Brute' AS value, COUNT(*) AS count FROM filtered WHERE brute_percentage >= 50
+/// UNION ALL ...
+/// ```
+pub fn prepare_creature_role_facet_query(
+    gs: GameSystem,
+    filters: &CreatureFieldFilters,
+) -> (String, Vec<BindValue>) {
+    let mut binds = Vec::new();
+    let where_clause = prepare_creature_facet_where(gs, filters, &mut binds);
+    let threshold = filters.role_threshold.unwrap_or(0);
+    let roles_query = CreatureRoleEnum::iter()
+        .map(|role| {
+            format!(
+                "SELECT '{role}' AS value, COUNT(*) AS count FROM filtered WHERE {} >= {threshold}",
+                role.to_db_column()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
     let query = format!(
-        "SELECT *, COUNT(*) OVER() AS total_count FROM {gs}_item_table
-         WHERE {where_clause} ORDER BY {order_clause} {pagination}"
+        "WITH filtered AS (SELECT * FROM {gs}_creature_core WHERE {where_clause}) {roles_query}"
     );
     (finalize_placeholders(&query), binds)
 }
 
+/// `range_table` is one of `creature_core`, `creature_table` (linked by id) and
+/// `skill_table` (linked by `creature_id`).
+pub fn prepare_creature_range_query(
+    gs: GameSystem,
+    filters: &CreatureFieldFilters,
+    range_table: &str,
+    column: &str,
+) -> (String, Vec<BindValue>) {
+    let mut binds = Vec::new();
+    let where_clause = prepare_creature_facet_where(gs, filters, &mut binds);
+    let id_column = if range_table == "skill_table" {
+        "creature_id"
+    } else {
+        "id"
+    };
+    let query = prepare_range_query(
+        &format!("{gs}_{range_table}"),
+        id_column,
+        column,
+        "bigint",
+        &format!("{gs}_creature_core"),
+        &where_clause,
+    );
+    (finalize_placeholders(&query), binds)
+}
+
+pub fn prepare_hazard_facet_query(
+    gs: GameSystem,
+    filters: &HazardFieldFilters,
+    value_column: &str,
+) -> (String, Vec<BindValue>) {
+    let mut binds = Vec::new();
+    let where_clause = prepare_hazard_facet_where(gs, filters, &mut binds);
+    let query = prepare_facet_query(&format!("{gs}_hazard_table"), &where_clause, value_column);
+    (finalize_placeholders(&query), binds)
+}
+
+pub fn prepare_hazard_trait_facet_query(
+    gs: GameSystem,
+    filters: &HazardFieldFilters,
+) -> (String, Vec<BindValue>) {
+    let mut binds = Vec::new();
+    let where_clause = prepare_hazard_facet_where(gs, filters, &mut binds);
+    let query =
+        prepare_trait_facet_query(gs, "hazard", &format!("{gs}_hazard_table"), &where_clause);
+    (finalize_placeholders(&query), binds)
+}
+
+pub fn prepare_hazard_range_query(
+    gs: GameSystem,
+    filters: &HazardFieldFilters,
+    column: &str,
+) -> (String, Vec<BindValue>) {
+    let mut binds = Vec::new();
+    let where_clause = prepare_hazard_facet_where(gs, filters, &mut binds);
+    let table = format!("{gs}_hazard_table");
+    let query = prepare_range_query(&table, "id", column, "bigint", &table, &where_clause);
+    (finalize_placeholders(&query), binds)
+}
+
+pub fn prepare_item_facet_query(
+    gs: GameSystem,
+    filters: &ItemFieldFilters,
+    value_column: &str,
+) -> (String, Vec<BindValue>) {
+    let mut binds = Vec::new();
+    let where_clause = prepare_item_facet_where(gs, filters, &mut binds);
+    let query = prepare_facet_query(&format!("{gs}_item_table"), &where_clause, value_column);
+    (finalize_placeholders(&query), binds)
+}
+
+pub fn prepare_item_trait_facet_query(
+    gs: GameSystem,
+    filters: &ItemFieldFilters,
+) -> (String, Vec<BindValue>) {
+    let mut binds = Vec::new();
+    let where_clause = prepare_item_facet_where(gs, filters, &mut binds);
+    let query = prepare_trait_facet_query(gs, "item", &format!("{gs}_item_table"), &where_clause);
+    (finalize_placeholders(&query), binds)
+}
+
+/// `sql_type` is the type the bounds are cast to, `bigint` or `float8` (for bulk).
+pub fn prepare_item_range_query(
+    gs: GameSystem,
+    filters: &ItemFieldFilters,
+    column: &str,
+    sql_type: &str,
+) -> (String, Vec<BindValue>) {
+    let mut binds = Vec::new();
+    let where_clause = prepare_item_facet_where(gs, filters, &mut binds);
+    let table = format!("{gs}_item_table");
+    let query = prepare_range_query(&table, "id", column, sql_type, &table, &where_clause);
+    (finalize_placeholders(&query), binds)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::shared::rarity_enum::RarityEnum;
 
     #[test]
     fn case_insensitive_in_statement_binds_values_instead_of_inlining() {
@@ -1262,12 +2386,28 @@ mod tests {
             is_melee_filter: vec![true, false],
             is_ranged_filter: vec![true, false],
             is_spellcaster_filter: vec![true, false],
+            immunity_whitelist_filter: vec![],
+            immunity_blacklist_filter: vec![],
+            resistance_whitelist_filter: vec![],
+            resistance_blacklist_filter: vec![],
+            weakness_whitelist_filter: vec![],
+            weakness_blacklist_filter: vec![],
+            speed_type_filter: vec![],
+            min_speed_filter: None,
+            sense_filter: vec![],
+            language_filter: vec![],
+            spell_tradition_filter: vec![],
+            spellcaster_type_filter: vec![],
+            spell_name_filter: vec![],
+            min_spell_dc_filter: None,
+            max_spell_dc_filter: None,
             supported_version: vec!["true".to_string()],
             level_filter: vec![1],
         };
         filters.family_filter = vec!["Dragon".to_string(), "Giant".to_string()];
         let mut binds = Vec::new();
-        let statement = prepare_creature_filter_statement(&filters, &mut binds);
+        let statement =
+            prepare_creature_filter_statement(GameSystem::Pathfinder, &filters, &mut binds);
         let finalized = finalize_placeholders(&statement);
         // source (TextArray, $1) is textually before family (TextArray, $2);
         // this filter statement is the exact-match path used by random
@@ -1320,4 +2460,468 @@ mod tests {
             other => panic!("expected five binds in order, got {other:?}"),
         }
     }
+
+    #[test]
+    fn creature_defense_statements_bind_whitelist_before_blacklist() {
+        let fire = vec!["fire".to_string()];
+        let cold_iron = vec!["cold-iron".to_string()];
+        let mut binds = Vec::new();
+        let statements = prepare_creature_defense_statements(
+            GameSystem::Pathfinder,
+            (&[], &fire),
+            (&[], &fire),
+            (&cold_iron, &[]),
+            &mut binds,
+        );
+        let finalized = finalize_placeholders(&statements.join(" AND "));
+        assert_eq!(
+            finalized,
+            "id NOT IN (SELECT creature_id FROM pf_immunity_creature_association_table \
+             WHERE UPPER(immunity_id) = ANY($1)) AND \
+             id NOT IN (SELECT creature_id FROM pf_resistance_table WHERE UPPER(name) = ANY($2)) AND \
+             id IN (SELECT creature_id FROM pf_weakness_table WHERE UPPER(name) = ANY($3))"
+        );
+        match &binds[..] {
+            [
+                BindValue::TextArray(immunities),
+                BindValue::TextArray(resistances),
+                BindValue::TextArray(weaknesses),
+            ] => {
+                assert_eq!(immunities, &["FIRE".to_string()]);
+                assert_eq!(resistances, &["FIRE".to_string()]);
+                assert_eq!(weaknesses, &["COLD-IRON".to_string()]);
+            }
+            other => panic!("expected three binds in order, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn creature_environment_statements_bind_in_order() {
+        let speeds = vec!["swim".to_string(), "burrow".to_string()];
+        let senses = vec!["darkvision".to_string()];
+        let languages = vec!["Draconic".to_string()];
+        let mut binds = Vec::new();
+        let statements = prepare_creature_environment_statements(
+            GameSystem::Pathfinder,
+            &speeds,
+            Some(30),
+            &senses,
+            &languages,
+            &mut binds,
+        );
+        let finalized = finalize_placeholders(&statements.join(" AND "));
+        assert!(finalized.contains(
+            "id IN (SELECT creature_id FROM pf_speed_table WHERE UPPER(name) = ANY($1) AND value >= 30)"
+        ));
+        assert!(finalized.contains("UPPER(st.name) = ANY($2)"));
+        assert!(finalized.contains("UPPER(language_id) = ANY($3)"));
+        assert_eq!(binds.len(), 3);
+    }
+
+    #[test]
+    fn creature_environment_min_speed_without_types_checks_any_speed() {
+        let mut binds = Vec::new();
+        let statements = prepare_creature_environment_statements(
+            GameSystem::Starfinder,
+            &[],
+            Some(40),
+            &[],
+            &[],
+            &mut binds,
+        );
+        assert_eq!(
+            statements,
+            vec!["id IN (SELECT creature_id FROM sf_speed_table WHERE value >= 40)".to_string()]
+        );
+        assert!(binds.is_empty());
+    }
+
+    #[test]
+    fn creature_spellcasting_statements_group_entry_conditions() {
+        let spells = vec!["Fireball".to_string()];
+        let mut binds = Vec::new();
+        let statements = prepare_creature_spellcasting_statements(
+            GameSystem::Pathfinder,
+            &[SpellTraditionEnum::Arcane],
+            &[SpellcasterTypeEnum::Prepared],
+            &spells,
+            Some(20),
+            None,
+            &mut binds,
+        );
+        let finalized = finalize_placeholders(&statements.join(" AND "));
+        assert_eq!(
+            finalized,
+            "id IN (SELECT creature_id FROM pf_spellcasting_entry_table \
+             WHERE UPPER(spellcasting_tradition) = ANY($1) AND UPPER(type_of_spellcaster) = ANY($2) \
+             AND spellcasting_dc_mod >= 20) AND \
+             id IN (SELECT creature_id FROM pf_spell_table WHERE UPPER(name) = ANY($3))"
+        );
+        match &binds[..] {
+            [
+                BindValue::TextArray(traditions),
+                BindValue::TextArray(types),
+                BindValue::TextArray(spells),
+            ] => {
+                assert_eq!(traditions, &["ARCANE".to_string()]);
+                assert_eq!(types, &["PREPARED".to_string()]);
+                assert_eq!(spells, &["FIREBALL".to_string()]);
+            }
+            other => panic!("expected three binds in order, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn creature_listing_where_groups_stat_bounds_and_binds_skill() {
+        let filters = CreatureFieldFilters {
+            min_ac_filter: Some(20),
+            max_will_filter: Some(15),
+            skill_filter: Some("Stealth".to_string()),
+            min_skill_modifier_filter: Some(20),
+            ..Default::default()
+        };
+        let mut binds = Vec::new();
+        let statement =
+            prepare_creature_listing_where(GameSystem::Pathfinder, &filters, &mut binds);
+        let finalized = finalize_placeholders(&statement);
+        assert!(
+            finalized
+                .contains("id IN (SELECT id FROM pf_creature_table WHERE ac >= 20 AND will <= 15)")
+        );
+        assert!(finalized.contains(
+            "id IN (SELECT creature_id FROM pf_skill_table WHERE UPPER(name) = UPPER($1) AND modifier >= 20)"
+        ));
+        match &binds[..] {
+            [BindValue::Text(skill)] => assert_eq!(skill, "Stealth"),
+            other => panic!("expected a single Text bind, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn trait_expression_statement_nests_groups_and_binds_in_order() {
+        let expression: TraitFilterExpression = "undead & (incorporeal | spirit) & !mindless"
+            .parse()
+            .unwrap();
+        let mut binds = Vec::new();
+        let statement = prepare_trait_expression_statement(
+            GameSystem::Pathfinder,
+            "creature_id",
+            "trait_creature_association_table",
+            &expression,
+            &mut binds,
+        );
+        let trait_check = |n: usize| {
+            format!(
+                "id IN (SELECT creature_id FROM pf_trait_creature_association_table WHERE UPPER(trait_id) = UPPER(${n}))"
+            )
+        };
+        assert_eq!(
+            finalize_placeholders(&statement),
+            format!(
+                "({} AND ({} OR {}) AND NOT ({}))",
+                trait_check(1),
+                trait_check(2),
+                trait_check(3),
+                trait_check(4)
+            )
+        );
+        let bound: Vec<_> = binds
+            .iter()
+            .map(|b| match b {
+                BindValue::Text(x) => x.as_str(),
+                BindValue::TextArray(_) => panic!("expected only Text binds"),
+            })
+            .collect();
+        assert_eq!(bound, ["undead", "incorporeal", "spirit", "mindless"]);
+    }
+
+    #[test]
+    fn text_search_listing_binds_search_before_filters_and_ranks_first() {
+        let filters = HazardFieldFilters {
+            text_search_filter: Some("teleport".to_string()),
+            name_filter: Some("trap".to_string()),
+            ..Default::default()
+        };
+        let (query, binds) = prepare_paginated_get_hazards_listing(
+            GameSystem::Pathfinder,
+            &filters,
+            &[SortKeySpec::new(HazardSortEnum::Name, OrderEnum::Ascending)],
+            &ListingCursor::Offset(0),
+            20,
+        )
+        .unwrap()
+        .page;
+        assert!(query.contains("FROM pf_hazard_search_table"));
+        assert!(query.contains("websearch_to_tsquery('english', $1)"));
+        assert!(query.contains("name ILIKE $2"));
+        assert!(query.contains("ORDER BY search_rank DESC, name ASC"));
+        match &binds[..] {
+            [BindValue::Text(search), BindValue::Text(name)] => {
+                assert_eq!(search, "teleport");
+                assert_eq!(name, "%trap%");
+            }
+            other => panic!("expected two Text binds in order, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn blank_text_search_keeps_plain_listing() {
+        let filters = ItemFieldFilters {
+            text_search_filter: Some("   ".to_string()),
+            ..Default::default()
+        };
+        let (query, binds) = prepare_paginated_get_items_listing(
+            GameSystem::Pathfinder,
+            &filters,
+            &[SortKeySpec::new(ItemSortEnum::Id, OrderEnum::Ascending)],
+            &ListingCursor::Offset(0),
+            20,
+        )
+        .unwrap()
+        .page;
+        assert!(!query.contains("text_search"));
+        assert!(binds.is_empty());
+    }
+
+    #[test]
+    fn fuzzy_name_statement_accepts_similar_names() {
+        let mut binds = Vec::new();
+        let statement = prepare_name_statement("goblen", NameMatchModeEnum::Fuzzy, &mut binds);
+        assert_eq!(
+            finalize_placeholders(&statement),
+            "(name ILIKE $1 ESCAPE '\\' OR word_similarity($2, name) >= 0.3)"
+        );
+        match &binds[..] {
+            [BindValue::Text(pattern), BindValue::Text(name)] => {
+                assert_eq!(pattern, "%goblen%");
+                assert_eq!(name, "goblen");
+            }
+            other => panic!("expected two Text binds in order, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn name_statement_matches_wildcards_literally() {
+        let mut binds = Vec::new();
+        prepare_name_statement(r"100%_\", NameMatchModeEnum::Substring, &mut binds);
+        match &binds[..] {
+            [BindValue::Text(pattern)] => assert_eq!(pattern, r"%100\%\_\\%"),
+            other => panic!("expected one Text bind, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn fuzzy_listing_lists_the_closest_names_first() {
+        let filters = CreatureFieldFilters {
+            name_filter: Some("goblen".to_string()),
+            name_match_mode: Some(NameMatchModeEnum::Fuzzy),
+            ..Default::default()
+        };
+        let (query, binds) = prepare_paginated_get_creatures_listing(
+            GameSystem::Pathfinder,
+            &filters,
+            &[SortKeySpec::new(
+                CreatureSortEnum::Name,
+                OrderEnum::Ascending,
+            )],
+            &ListingCursor::Offset(0),
+            20,
+        )
+        .unwrap()
+        .page;
+        assert!(query.contains(
+            "FROM (SELECT *, word_similarity($1, name) AS name_similarity \
+            FROM pf_creature_core) pf_creature_core"
+        ));
+        assert!(query.contains("ORDER BY name_similarity DESC, name ASC"));
+        match &binds[..] {
+            [
+                BindValue::Text(similarity),
+                BindValue::Text(pattern),
+                BindValue::Text(name),
+            ] => {
+                assert_eq!(similarity, "goblen");
+                assert_eq!(pattern, "%goblen%");
+                assert_eq!(name, "goblen");
+            }
+            other => panic!("expected three Text binds in order, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn facet_queries_ignore_their_own_filter_only() {
+        let filters = CreatureFieldFilters {
+            rarity_filter: Some(vec![RarityEnum::Rare]),
+            source_filter: Some(vec!["Bestiary".to_string()]),
+            ..Default::default()
+        };
+        let cleared = CreatureFieldFilters {
+            rarity_filter: None,
+            ..filters.clone()
+        };
+        let (query, binds) =
+            prepare_creature_facet_query(GameSystem::Pathfinder, &cleared, "rarity");
+        assert!(query.starts_with("SELECT CAST(rarity AS TEXT) AS value, COUNT(*) AS count"));
+        assert!(query.contains("UPPER(source) = ANY($1)"));
+        assert_eq!(binds.len(), 1);
+
+        let (query, _) = prepare_creature_role_facet_query(GameSystem::Pathfinder, &filters);
+        assert!(query.starts_with("WITH filtered AS (SELECT * FROM pf_creature_core WHERE"));
+        assert_eq!(
+            query.matches(" UNION ALL ").count(),
+            CreatureRoleEnum::iter().count() - 1
+        );
+    }
+
+    #[test]
+    fn facet_queries_keep_the_text_search() {
+        let filters = ItemFieldFilters {
+            text_search_filter: Some("flaming".to_string()),
+            source_filter: Some(vec!["Core".to_string()]),
+            ..Default::default()
+        };
+        let (query, binds) = prepare_item_trait_facet_query(GameSystem::Pathfinder, &filters);
+        assert!(query.contains(
+            "AND id IN (SELECT id FROM pf_item_search_table
+            WHERE document @@ websearch_to_tsquery('english', $2))"
+        ));
+        match &binds[..] {
+            [BindValue::TextArray(_), BindValue::Text(search)] => assert_eq!(search, "flaming"),
+            other => panic!("expected the filter binds then the search one, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn total_count_is_the_same_on_every_keyset_page() {
+        let filters = CreatureFieldFilters {
+            name_filter: Some("goblin".to_string()),
+            ..Default::default()
+        };
+        let sort = [SortKeySpec::new(
+            CreatureSortEnum::Level,
+            OrderEnum::Ascending,
+        )];
+        let listing = |cursor| {
+            prepare_paginated_get_creatures_listing(
+                GameSystem::Pathfinder,
+                &filters,
+                &sort,
+                &cursor,
+                20,
+            )
+            .unwrap()
+        };
+        let first_page = listing(ListingCursor::Offset(0));
+        let second_page = listing(ListingCursor::Keyset(KeysetCursor {
+            sort: "Level:Ascending".to_string(),
+            keys: vec![serde_json::json!(3), serde_json::json!(120)],
+        }));
+        let (count, count_binds) = &first_page.count;
+        assert_eq!(count, &second_page.count.0);
+        assert!(count.contains("SELECT COUNT(*) FROM pf_creature_core WHERE"));
+        assert!(!count.contains("level >"));
+        assert_eq!(
+            format!("{count_binds:?}"),
+            format!("{:?}", first_page.page.1)
+        );
+        assert!(
+            second_page
+                .page
+                .0
+                .contains("ESCAPE '\\') AND (((level > CAST($2 AS bigint)")
+        );
+        assert!(!second_page.page.0.contains("OVER()"));
+        assert_eq!(
+            format!("{count_binds:?}"),
+            format!("{:?}", &second_page.page.1[..count_binds.len()])
+        );
+    }
+
+    #[test]
+    fn keyset_cursor_resumes_after_the_last_row() {
+        let cursor = ListingCursor::Keyset(KeysetCursor {
+            sort: "Fortitude:Descending".to_string(),
+            keys: vec![serde_json::json!(null), serde_json::json!(42)],
+        });
+        let (query, binds) = prepare_paginated_get_hazards_listing(
+            GameSystem::Pathfinder,
+            &HazardFieldFilters::default(),
+            &[SortKeySpec::new(
+                HazardSortEnum::Fortitude,
+                OrderEnum::Descending,
+            )],
+            &cursor,
+            20,
+        )
+        .unwrap()
+        .page;
+        // NULLs are last, so after a NULL only the other NULLs with a lower id are left
+        assert!(query.contains(
+            "WHERE (TRUE) AND ((FALSE) OR (fortitude IS NULL AND id < CAST($1 AS bigint)))"
+        ));
+        assert!(query.contains("ORDER BY fortitude DESC NULLS LAST, id DESC LIMIT 20 OFFSET 0"));
+        assert!(query.contains("CAST(json_build_array(fortitude, id) AS TEXT) AS keyset_keys"));
+        assert!(matches!(&binds[..], [BindValue::Text(id)] if id == "42"));
+
+        let mismatched = ListingCursor::Keyset(KeysetCursor {
+            sort: "Name:Ascending".to_string(),
+            keys: vec![serde_json::json!(42)],
+        });
+        assert!(
+            prepare_paginated_get_items_listing(
+                GameSystem::Pathfinder,
+                &ItemFieldFilters::default(),
+                &[SortKeySpec::new(ItemSortEnum::Name, OrderEnum::Ascending)],
+                &mismatched,
+                20,
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn multi_key_sort_orders_and_resumes_on_every_key() {
+        let sort = [
+            SortKeySpec::new(CreatureSortEnum::Level, OrderEnum::Descending),
+            SortKeySpec::new(
+                CreatureSortEnum::RolePercentage(CreatureRoleEnum::Brute),
+                OrderEnum::Descending,
+            ),
+            SortKeySpec::new(CreatureSortEnum::Name, OrderEnum::Ascending),
+        ];
+        let (query, _) = prepare_paginated_get_creatures_listing(
+            GameSystem::Pathfinder,
+            &CreatureFieldFilters::default(),
+            &sort,
+            &ListingCursor::Offset(0),
+            20,
+        )
+        .unwrap()
+        .page;
+        assert!(query.contains("ORDER BY level DESC, brute_percentage DESC, name ASC, id ASC"));
+
+        let cursor = ListingCursor::Keyset(KeysetCursor {
+            sort: String::new(),
+            keys: ["5", "80", "\"Ogre\"", "7"]
+                .iter()
+                .map(|v| serde_json::from_str(v).unwrap())
+                .collect(),
+        });
+        let (query, binds) = prepare_paginated_get_creatures_listing(
+            GameSystem::Pathfinder,
+            &CreatureFieldFilters::default(),
+            &sort,
+            &cursor,
+            20,
+        )
+        .unwrap()
+        .page;
+        assert!(query.contains("(level < CAST($1 AS bigint))"));
+        assert!(query.contains(
+            "(level = CAST($7 AS bigint) AND brute_percentage = CAST($8 AS bigint) \
+             AND name = CAST($9 AS text) AND id > CAST($10 AS bigint))"
+        ));
+        assert!(query.contains("(name > CAST($6 AS text) OR name IS NULL)"));
+        assert_eq!(binds.len(), 10);
+    }
 }
@@ -1,10 +1,12 @@
 use crate::db::data_providers::generic_fetcher::{
     enrich_with_traits, fetch_all_with_binds, fetch_all_with_binds_and_count, fetch_armor_runes,
-    fetch_col_range, fetch_col_range_f64, fetch_item_traits, fetch_weapon_damage_data,
-    fetch_weapon_runes,
+    fetch_armor_runes_batch, fetch_col_range, fetch_col_range_f64, fetch_facet_values,
+    fetch_item_traits, fetch_range_with_binds, fetch_weapon_damage_data,
+    fetch_weapon_damage_data_batch, fetch_weapon_runes, fetch_weapon_runes_batch, without_filter,
 };
 use crate::db::data_providers::raw_query_builder::{
-    format_pagination_clause, prepare_filtered_get_items, prepare_paginated_get_items_listing,
+    format_pagination_clause, prepare_filtered_get_items, prepare_item_facet_query,
+    prepare_item_range_query, prepare_item_trait_facet_query, prepare_paginated_get_items_listing,
 };
 use crate::models::item::armor_struct::{Armor, ArmorData};
 use crate::models::item::item_field_filter::ItemFieldFilters;
@@ -14,12 +16,18 @@ use crate::models::item::shield_struct::{Shield, ShieldData};
 use crate::models::item::shop_structs::{ItemSortEnum, ShopFilterQuery, ShopRanges};
 use crate::models::item::weapon_struct::{Weapon, WeaponData};
 use crate::models::response_data::ResponseItem;
-use crate::models::routers_validator_structs::OrderEnum;
+use crate::models::shared::facets::ShopFacets;
 use crate::models::shared::game_system_enum::GameSystem;
+use crate::models::shared::listing_cursor::ListingCursor;
+use crate::models::shared::sort_spec::SortKeySpec;
+use crate::models::shared::text_search::TextSearchMatch;
 use anyhow::Result;
 use futures::future::try_join_all;
+use futures::try_join;
+use itertools::Itertools;
 use nanorand::{Rng, WyRand};
 use sqlx::PgPool;
+use std::collections::HashMap;
 use tracing::debug;
 
 pub async fn fetch_item_by_id(pool: &PgPool, gs: GameSystem, item_id: i64) -> Result<ResponseItem> {
@@ -56,6 +64,150 @@ pub async fn fetch_item_by_id(pool: &PgPool, gs: GameSystem, item_id: i64) -> Re
     })
 }
 
+/// Gets every valid item of `ids`, in the same order (repeated ids are repeated), with their
+/// traits and weapon, armor or shield data loaded by `= ANY($1)` queries.
+pub async fn fetch_items_by_ids(
+    pool: &PgPool,
+    gs: GameSystem,
+    ids: &[i64],
+) -> Result<Vec<ResponseItem>> {
+    let unique_ids: Vec<i64> = ids.iter().copied().unique().collect();
+    let items: Vec<Item> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "SELECT * FROM {gs}_item_table WHERE status = 'valid' AND id = ANY($1)"
+    )))
+    .bind(&unique_ids)
+    .fetch_all(pool)
+    .await?;
+    let items = enrich_with_traits(pool, gs, items, false).await;
+    let ids_of_type = |item_type: ItemTypeEnum| -> Vec<i64> {
+        items
+            .iter()
+            .filter(|x| x.item_type == item_type)
+            .map(|x| x.id)
+            .collect()
+    };
+    let mut weapon_data =
+        fetch_weapon_data_by_item_ids(pool, gs, &ids_of_type(ItemTypeEnum::Weapon)).await?;
+    let mut armor_data =
+        fetch_armor_data_by_item_ids(pool, gs, &ids_of_type(ItemTypeEnum::Armor)).await?;
+    let mut shield_data =
+        fetch_shield_data_by_item_ids(pool, gs, &ids_of_type(ItemTypeEnum::Shield)).await?;
+    let items: HashMap<i64, ResponseItem> = items
+        .into_iter()
+        .map(|item| {
+            let id = item.id;
+            let response = ResponseItem {
+                weapon_data: weapon_data.remove(&id),
+                armor_data: armor_data.remove(&id),
+                shield_data: shield_data.remove(&id),
+                ..ResponseItem::from((item, gs))
+            };
+            (id, response)
+        })
+        .collect();
+    Ok(ids.iter().filter_map(|id| items.get(id).cloned()).collect())
+}
+
+/// Weapon data of the given base items, keyed by item id.
+async fn fetch_weapon_data_by_item_ids(
+    pool: &PgPool,
+    gs: GameSystem,
+    item_ids: &[i64],
+) -> Result<HashMap<i64, WeaponData>> {
+    if item_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let weapons: Vec<Weapon> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "
+        SELECT
+            wt.id AS weapon_id, wt.to_hit_bonus, wt.splash_dmg, wt.n_of_potency_runes,
+            wt.n_of_striking_runes, wt.reload, wt.weapon_type, wt.base_item_id,
+            it.*,
+            rt.id AS range_id, rt.value AS range_value,
+            rt.increment AS range_increment, rt.max AS range_max
+        FROM {gs}_weapon_table wt
+        LEFT JOIN {gs}_item_table it                      ON wt.base_item_id = it.id
+        LEFT JOIN {gs}_weapon_range_association_table wr  ON wr.weapon_id = wt.id
+        LEFT JOIN {gs}_range_table rt                     ON rt.id = wr.range_id
+        WHERE wt.base_item_id = ANY($1) AND it.status = 'valid'
+        "
+    )))
+    .bind(item_ids)
+    .fetch_all(pool)
+    .await?;
+    let weapon_ids: Vec<i64> = weapons.iter().map(|x| x.weapon_data.id).collect();
+    let mut runes = fetch_weapon_runes_batch(pool, gs, &weapon_ids).await?;
+    let mut damage_data = fetch_weapon_damage_data_batch(pool, gs, &weapon_ids).await?;
+    Ok(weapons
+        .into_iter()
+        .map(|mut el| {
+            el.weapon_data.property_runes = runes.remove(&el.weapon_data.id).unwrap_or_default();
+            el.weapon_data.damage_data = damage_data.remove(&el.weapon_data.id).unwrap_or_default();
+            (el.item_core.id, el.weapon_data)
+        })
+        .collect())
+}
+
+/// Armor data of the given base items, keyed by item id.
+async fn fetch_armor_data_by_item_ids(
+    pool: &PgPool,
+    gs: GameSystem,
+    item_ids: &[i64],
+) -> Result<HashMap<i64, ArmorData>> {
+    if item_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let armors: Vec<Armor> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "
+        SELECT at.id AS armor_id, at.bonus_ac, at.check_penalty, at.dex_cap, at.n_of_potency_runes,
+        at.n_of_resilient_runes, at.speed_penalty, at.strength_required, at.base_item_id,
+        it.*
+        FROM {gs}_armor_table at
+        LEFT JOIN {gs}_item_table it ON at.base_item_id = it.id
+        WHERE at.base_item_id = ANY($1) AND it.status = 'valid'
+        "
+    )))
+    .bind(item_ids)
+    .fetch_all(pool)
+    .await?;
+    let armor_ids: Vec<i64> = armors.iter().map(|x| x.armor_data.id).collect();
+    let mut runes = fetch_armor_runes_batch(pool, gs, &armor_ids).await?;
+    Ok(armors
+        .into_iter()
+        .map(|mut el| {
+            el.armor_data.property_runes = runes.remove(&el.armor_data.id).unwrap_or_default();
+            (el.item_core.id, el.armor_data)
+        })
+        .collect())
+}
+
+/// Shield data of the given base items, keyed by item id.
+async fn fetch_shield_data_by_item_ids(
+    pool: &PgPool,
+    gs: GameSystem,
+    item_ids: &[i64],
+) -> Result<HashMap<i64, ShieldData>> {
+    if item_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let shields: Vec<Shield> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "
+        SELECT st.id AS shield_id, st.bonus_ac, st.n_of_reinforcing_runes, st.speed_penalty,
+        it.*
+        FROM {gs}_shield_table st
+        LEFT JOIN {gs}_item_table it ON st.base_item_id = it.id
+        WHERE st.base_item_id = ANY($1) AND it.status = 'valid'
+        "
+    )))
+    .bind(item_ids)
+    .fetch_all(pool)
+    .await?;
+    Ok(shields
+        .into_iter()
+        .map(|el| (el.item_core.id, el.shield_data))
+        .collect())
+}
+
 async fn fetch_weapon_by_item_id(pool: &PgPool, gs: GameSystem, item_id: i64) -> Result<Weapon> {
     let mut weapon: Weapon = sqlx::query_as(sqlx::AssertSqlSafe(format!(
         "
@@ -308,23 +460,21 @@ fn fill_item_vec_to_len(item_vec: &[&Item], desired_len: i64) -> Vec<Item> {
     og_vec
 }
 
-/// Returns the requested page of items alongside their total count, in a single round trip.
+/// Returns the requested page of items alongside their total count, fetched concurrently.
 ///
-/// Uses `COUNT(*) OVER()` for the total and batches the page's traits into one query instead of
+/// Batches the page's traits into one query instead of
 /// one per item. Per-item weapon/armor/shield sub-data is fetched one row at a time
 pub async fn fetch_paginated_items(
     pool: &PgPool,
     gs: GameSystem,
     filters: &ItemFieldFilters,
-    sort_by: ItemSortEnum,
-    order_by: OrderEnum,
-    cursor: u32,
+    sort: &[SortKeySpec<ItemSortEnum>],
+    cursor: &ListingCursor,
     page_size: i16,
-) -> Result<(Vec<ResponseItem>, i64)> {
-    let (query, binds) =
-        prepare_paginated_get_items_listing(gs, filters, sort_by, order_by, cursor, page_size);
-    let (items, total_count): (Vec<Item>, i64) =
-        fetch_all_with_binds_and_count(pool, query, binds).await?;
+) -> Result<(Vec<ResponseItem>, i64, Vec<TextSearchMatch>, Option<String>)> {
+    let queries = prepare_paginated_get_items_listing(gs, filters, sort, cursor, page_size)?;
+    let (items, total_count, search_matches, last_keys) =
+        fetch_all_with_binds_and_count::<Item>(pool, queries).await?;
     let items = enrich_with_traits(pool, gs, items, false).await;
     let mut result = Vec::with_capacity(items.len());
     for item in items {
@@ -361,7 +511,7 @@ pub async fn fetch_paginated_items(
         };
         result.push(response_item);
     }
-    Ok((result, total_count))
+    Ok((result, total_count, search_matches, last_keys))
 }
 
 pub async fn fetch_shop_ranges(pool: &PgPool, gs: GameSystem) -> Result<ShopRanges> {
@@ -388,6 +538,181 @@ pub async fn fetch_shop_ranges(pool: &PgPool, gs: GameSystem) -> Result<ShopRang
     })
 }
 
+/// Returns, for every filterable dimension, the values available under the other active filters
+/// and the number of items they would lead to.
+pub async fn fetch_shop_facets(
+    pool: &PgPool,
+    gs: GameSystem,
+    filters: &ItemFieldFilters,
+) -> Result<ShopFacets> {
+    let (source, category, rarity, size, item_type, traits, level, ranges) = try_join!(
+        fetch_facet_values(
+            pool,
+            prepare_item_facet_query(
+                gs,
+                &without_filter(filters, |f| f.source_filter = None),
+                "source",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_item_facet_query(
+                gs,
+                &without_filter(filters, |f| f.category_filter = None),
+                "category",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_item_facet_query(
+                gs,
+                &without_filter(filters, |f| f.rarity_filter = None),
+                "rarity",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_item_facet_query(
+                gs,
+                &without_filter(filters, |f| f.size_filter = None),
+                "size",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_item_facet_query(
+                gs,
+                &without_filter(filters, |f| f.type_filter = None),
+                "item_type",
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_item_trait_facet_query(
+                gs,
+                &without_filter(filters, |f| {
+                    f.trait_whitelist_filter = None;
+                    f.trait_blacklist_filter = None;
+                    f.trait_expression_filter = None;
+                }),
+            ),
+        ),
+        fetch_facet_values(
+            pool,
+            prepare_item_facet_query(
+                gs,
+                &without_filter(filters, |f| {
+                    f.min_level_filter = None;
+                    f.max_level_filter = None;
+                }),
+                "level",
+            ),
+        ),
+        fetch_filtered_shop_ranges(pool, gs, filters),
+    )?;
+    Ok(ShopFacets {
+        source,
+        category,
+        rarity,
+        size,
+        item_type,
+        traits,
+        level,
+        ranges,
+    })
+}
+
+/// Like `fetch_shop_ranges`, but only over the items passing the filters.
+/// The range of each stat ignores the bounds on that same stat.
+pub async fn fetch_filtered_shop_ranges(
+    pool: &PgPool,
+    gs: GameSystem,
+    filters: &ItemFieldFilters,
+) -> Result<ShopRanges> {
+    let (
+        (min_hp, max_hp),
+        (min_level, max_level),
+        (min_price, max_price),
+        (min_bulk, max_bulk),
+        (min_number_of_uses, max_number_of_uses),
+    ) = try_join!(
+        fetch_range_with_binds::<i64>(
+            pool,
+            prepare_item_range_query(
+                gs,
+                &without_filter(filters, |f| {
+                    f.min_hp_filter = None;
+                    f.max_hp_filter = None;
+                }),
+                "hp",
+                "bigint",
+            ),
+        ),
+        fetch_range_with_binds::<i64>(
+            pool,
+            prepare_item_range_query(
+                gs,
+                &without_filter(filters, |f| {
+                    f.min_level_filter = None;
+                    f.max_level_filter = None;
+                }),
+                "level",
+                "bigint",
+            ),
+        ),
+        fetch_range_with_binds::<i64>(
+            pool,
+            prepare_item_range_query(
+                gs,
+                &without_filter(filters, |f| {
+                    f.min_price_filter = None;
+                    f.max_price_filter = None;
+                }),
+                "price",
+                "bigint",
+            ),
+        ),
+        fetch_range_with_binds::<f64>(
+            pool,
+            prepare_item_range_query(
+                gs,
+                &without_filter(filters, |f| {
+                    f.min_bulk_filter = None;
+                    f.max_bulk_filter = None;
+                }),
+                "bulk",
+                "float8",
+            ),
+        ),
+        fetch_range_with_binds::<i64>(
+            pool,
+            prepare_item_range_query(
+                gs,
+                &without_filter(filters, |f| {
+                    f.min_n_of_uses_filter = None;
+                    f.max_n_of_uses_filter = None;
+                }),
+                "number_of_uses",
+                "bigint",
+            ),
+        ),
+    )?;
+    Ok(ShopRanges {
+        min_bulk,
+        max_bulk,
+        min_quantity: 1,
+        max_quantity: 1,
+        min_hp,
+        max_hp,
+        min_level,
+        max_level,
+        min_price,
+        max_price,
+        min_number_of_uses,
+        max_number_of_uses,
+    })
+}
+
 pub async fn fetch_shop_all_sources(pool: &PgPool, gs: GameSystem) -> Result<Vec<String>> {
     Ok(sqlx::query_scalar(sqlx::AssertSqlSafe(format!(
         "SELECT DISTINCT source FROM {gs}_item_table
@@ -7,7 +7,11 @@ use crate::models::hazard::hazard_listing_struct::{
 };
 use crate::models::hazard::hazard_struct::{Hazard, HazardRanges};
 use crate::models::response_data::ResponseHazard;
+use crate::models::shared::facets::HazardFacets;
 use crate::models::shared::game_system_enum::GameSystem;
+use crate::models::shared::text_search::TextSearchMatch;
+use crate::traits::url::has_sort_fields::HasSortFields;
+use crate::traits::url::paginated_request_ext::PaginatedRequestExt;
 use anyhow::Result;
 #[cfg(feature = "cache")]
 use cached::cached;
@@ -22,6 +26,15 @@ pub async fn get_hazard_by_id(
         .ok()
 }
 
+/// Gets the hazards of `ids` that exist, in the same order, with a fixed number of queries.
+pub async fn get_hazards_by_ids(
+    app_state: &AppState,
+    gs: GameSystem,
+    ids: &[i64],
+) -> Result<Vec<ResponseHazard>> {
+    hazard_fetcher::fetch_hazards_by_ids(&app_state.pool, gs, ids).await
+}
+
 pub async fn get_hazards_passing_all_filters(
     app_state: &AppState,
     gs: GameSystem,
@@ -35,18 +48,18 @@ pub async fn get_paginated_hazards(
     gs: GameSystem,
     filters: &HazardFieldFilters,
     pagination: &HazardListingPaginatedRequest,
-) -> Result<(u32, Vec<ResponseHazard>)> {
-    let (hazards, count) = hazard_fetcher::fetch_paginated_hazards(
+) -> Result<(
+    u32,
+    Vec<ResponseHazard>,
+    Vec<TextSearchMatch>,
+    Option<String>,
+)> {
+    let (hazards, count, search_matches, last_keys) = hazard_fetcher::fetch_paginated_hazards(
         &app_state.pool,
         gs,
         filters,
-        pagination
-            .hazard_sort_data
-            .sort_by
-            .clone()
-            .unwrap_or_default(),
-        pagination.hazard_sort_data.order_by.unwrap_or_default(),
-        pagination.paginated_request.cursor,
+        &pagination.hazard_sort_data.sort_keys()?,
+        &pagination.listing_cursor()?,
         pagination.paginated_request.page_size,
     )
     .await?;
@@ -58,7 +71,7 @@ pub async fn get_paginated_hazards(
             game: gs,
         })
         .collect();
-    Ok((count, response_hazards))
+    Ok((count, response_hazards, search_matches, last_keys))
 }
 
 #[cfg_attr(feature = "cache", cached(key = "i64", convert = r##"{ gs.into() }"##))]
@@ -89,3 +102,13 @@ pub async fn get_hazard_ranges(app_state: &AppState, gs: GameSystem) -> Option<H
         .await
         .ok()
 }
+
+pub async fn get_hazard_facets(
+    app_state: &AppState,
+    gs: GameSystem,
+    filters: &HazardFieldFilters,
+) -> Option<HazardFacets> {
+    hazard_fetcher::fetch_hazard_facets(&app_state.pool, gs, filters)
+        .await
+        .ok()
+}
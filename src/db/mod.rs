@@ -1,6 +1,8 @@
+pub mod autocomplete_proxy;
 pub mod bestiary_proxy;
 pub mod cr_core_initializer;
 pub mod data_providers;
 pub mod hazard_proxy;
 pub mod json_fetcher;
 pub mod shop_proxy;
+pub mod text_search_initializer;
@@ -4,7 +4,11 @@ use crate::models::item::item_field_filter::ItemFieldFilters;
 use crate::models::item::item_struct::Item;
 use crate::models::item::shop_structs::{ShopFilterQuery, ShopPaginatedRequest, ShopRanges};
 use crate::models::response_data::ResponseItem;
+use crate::models::shared::facets::ShopFacets;
 use crate::models::shared::game_system_enum::GameSystem;
+use crate::models::shared::text_search::TextSearchMatch;
+use crate::traits::url::has_sort_fields::HasSortFields;
+use crate::traits::url::paginated_request_ext::PaginatedRequestExt;
 use anyhow::Result;
 #[cfg(feature = "cache")]
 use cached::cached;
@@ -15,6 +19,15 @@ pub async fn get_item_by_id(app_state: &AppState, gs: GameSystem, id: i64) -> Op
         .ok()
 }
 
+/// Gets the valid items of `ids`, in the same order, with a fixed number of queries.
+pub async fn get_items_by_ids(
+    app_state: &AppState,
+    gs: GameSystem,
+    ids: &[i64],
+) -> Result<Vec<ResponseItem>> {
+    shop_fetcher::fetch_items_by_ids(&app_state.pool, gs, ids).await
+}
+
 pub async fn get_filtered_items(
     app_state: &AppState,
     gs: GameSystem,
@@ -28,18 +41,17 @@ pub async fn get_paginated_items(
     gs: GameSystem,
     filters: &ItemFieldFilters,
     pagination: &ShopPaginatedRequest,
-) -> Result<(u32, Vec<ResponseItem>)> {
-    let (items, count) = shop_fetcher::fetch_paginated_items(
+) -> Result<(u32, Vec<ResponseItem>, Vec<TextSearchMatch>, Option<String>)> {
+    let (items, count, search_matches, last_keys) = shop_fetcher::fetch_paginated_items(
         &app_state.pool,
         gs,
         filters,
-        pagination.shop_sort_data.sort_by.unwrap_or_default(),
-        pagination.shop_sort_data.order_by.unwrap_or_default(),
-        pagination.paginated_request.cursor,
+        &pagination.shop_sort_data.sort_keys()?,
+        &pagination.listing_cursor()?,
         pagination.paginated_request.page_size,
     )
     .await?;
-    Ok((count as u32, items))
+    Ok((count as u32, items, search_matches, last_keys))
 }
 
 #[cfg_attr(feature = "cache", cached(key = "i64", convert = r##"{ gs.into() }"##))]
@@ -50,6 +62,16 @@ pub async fn get_shop_ranges(app_state: &AppState, gs: GameSystem) -> Option<Sho
 }
 
 #[cfg_attr(feature = "cache", cached(key = "i64", convert = r##"{ gs.into() }"##))]
+pub async fn get_shop_facets(
+    app_state: &AppState,
+    gs: GameSystem,
+    filters: &ItemFieldFilters,
+) -> Option<ShopFacets> {
+    shop_fetcher::fetch_shop_facets(&app_state.pool, gs, filters)
+        .await
+        .ok()
+}
+
 pub async fn get_all_sources(app_state: &AppState, gs: GameSystem) -> Vec<String> {
     shop_fetcher::fetch_shop_all_sources(&app_state.pool, gs)
         .await
@@ -0,0 +1,169 @@
+use crate::models::shared::game_system_enum::GameSystem;
+use anyhow::Result;
+use sqlx::{AssertSqlSafe, PgPool};
+use tracing::warn;
+
+/// Text search configuration used both when building the documents and when parsing queries.
+/// They must match, otherwise stemmed terms would not line up.
+pub const TEXT_SEARCH_CONFIG: &str = "english";
+
+pub async fn update_text_search_tables(pool: &PgPool, gs: GameSystem) -> Result<()> {
+    warn!("Handler for startup, Should only be used once for each gamesystem");
+    create_and_populate_creature_search_table(pool, gs).await?;
+    create_and_populate_hazard_search_table(pool, gs).await?;
+    create_and_populate_item_search_table(pool, gs).await?;
+    create_name_trigram_indexes(pool, gs).await
+}
+
+/// Creates the trigram indexes backing the fuzzy name search and autocomplete.
+/// `{gs}_creature_core` must already exist, rebuilding it drops its index.
+async fn create_name_trigram_indexes(pool: &PgPool, gs: GameSystem) -> Result<()> {
+    let mut conn = pool.acquire().await?;
+
+    sqlx::query("CREATE EXTENSION IF NOT EXISTS pg_trgm")
+        .execute(&mut *conn)
+        .await?;
+
+    for table in ["creature_core", "hazard_table", "item_table", "trait_table"] {
+        sqlx::query(AssertSqlSafe(format!(
+            "CREATE INDEX IF NOT EXISTS {gs}_{table}_name_trgm_idx ON {gs}_{table} USING GIN (name gin_trgm_ops)"
+        )))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn create_and_populate_creature_search_table(pool: &PgPool, gs: GameSystem) -> Result<()> {
+    // name > spell names > action names and descriptions
+    create_search_table(
+        pool,
+        gs,
+        "creature",
+        &format!(
+            "
+WITH
+action_text AS (
+    SELECT ca.creature_id, STRING_AGG(a.name || ': ' || a.description, ' ' ORDER BY a.id) AS text
+    FROM {gs}_creature_action_association_table ca
+    JOIN {gs}_action_table a ON a.id = ca.action_id
+    GROUP BY ca.creature_id
+),
+spell_text AS (
+    SELECT creature_id, STRING_AGG(DISTINCT name, ', ') AS text
+    FROM {gs}_spell_table
+    GROUP BY creature_id
+)
+SELECT
+    t.id,
+    setweight(to_tsvector('{TEXT_SEARCH_CONFIG}', t.name), 'A')
+        || setweight(to_tsvector('{TEXT_SEARCH_CONFIG}', COALESCE(st.text, '')), 'B')
+        || setweight(to_tsvector('{TEXT_SEARCH_CONFIG}', COALESCE(at.text, '')), 'C')
+        AS document,
+    {content} AS content
+FROM {gs}_creature_table t
+LEFT JOIN spell_text  st ON st.creature_id = t.id
+LEFT JOIN action_text at ON at.creature_id = t.id
+",
+            content = plain_text("CONCAT_WS(' ', st.text, at.text)")
+        ),
+    )
+    .await
+}
+
+async fn create_and_populate_hazard_search_table(pool: &PgPool, gs: GameSystem) -> Result<()> {
+    // name > description > disable and routine descriptions
+    create_search_table(
+        pool,
+        gs,
+        "hazard",
+        &format!(
+            "
+SELECT
+    id,
+    setweight(to_tsvector('{TEXT_SEARCH_CONFIG}', name), 'A')
+        || setweight(to_tsvector('{TEXT_SEARCH_CONFIG}', COALESCE(description, '')), 'B')
+        || setweight(to_tsvector('{TEXT_SEARCH_CONFIG}', COALESCE(disable_description, '')), 'C')
+        || setweight(to_tsvector('{TEXT_SEARCH_CONFIG}', COALESCE(routine_description, '')), 'C')
+        AS document,
+    {content} AS content
+FROM {gs}_hazard_table
+",
+            content =
+                plain_text("CONCAT_WS(' ', description, disable_description, routine_description)")
+        ),
+    )
+    .await
+}
+
+async fn create_and_populate_item_search_table(pool: &PgPool, gs: GameSystem) -> Result<()> {
+    // name > description
+    create_search_table(
+        pool,
+        gs,
+        "item",
+        &format!(
+            "
+SELECT
+    id,
+    setweight(to_tsvector('{TEXT_SEARCH_CONFIG}', name), 'A')
+        || setweight(to_tsvector('{TEXT_SEARCH_CONFIG}', COALESCE(description, '')), 'B')
+        AS document,
+    {content} AS content
+FROM {gs}_item_table
+",
+            content = plain_text("COALESCE(description, '')")
+        ),
+    )
+    .await
+}
+
+/// SQL expression turning the Foundry HTML of `expression` into the plain text of the snippets:
+/// enrichers with a label, ex `@UUID[Compendium.pf2e.conditionitems.Grabbed]{Grabbed}`,
+/// are replaced by it and tags are dropped, so that the only markup left is the highlighting.
+fn plain_text(expression: &str) -> String {
+    format!(
+        r"regexp_replace(
+        regexp_replace({expression}, '@\w+\[(?:[^\[\]]|\[[^\]]*\])*\]\{{([^}}]*)\}}', '\1', 'g'),
+        '<[^>]*>', ' ', 'g')"
+    )
+}
+
+/// Creates `{gs}_{entity}_search_table (id, document, content)` from the given select.
+/// `document` is the weighted tsvector that gets matched and ranked, `content` the plain text
+/// used to build highlighted snippets.
+async fn create_search_table(
+    pool: &PgPool,
+    gs: GameSystem,
+    entity: &str,
+    select_query: &str,
+) -> Result<()> {
+    let mut conn = pool.acquire().await?;
+
+    sqlx::query(AssertSqlSafe(format!(
+        "DROP TABLE IF EXISTS {gs}_{entity}_search_table"
+    )))
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(AssertSqlSafe(format!(
+        "CREATE TABLE {gs}_{entity}_search_table AS {select_query}"
+    )))
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(AssertSqlSafe(format!(
+        "ALTER TABLE {gs}_{entity}_search_table ADD PRIMARY KEY (id)"
+    )))
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(AssertSqlSafe(format!(
+        "CREATE INDEX {gs}_{entity}_search_document_idx ON {gs}_{entity}_search_table USING GIN (document)"
+    )))
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
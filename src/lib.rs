@@ -6,6 +6,8 @@ pub mod models;
 pub mod services;
 pub mod traits;
 
+use crate::models::share_limits::ShareDecodeLimits;
+use crate::models::share_signing::ShareSigningKeys;
 use sqlx::PgPool;
 
 #[derive(Clone)]
@@ -13,4 +15,8 @@ pub struct AppState {
     pub pool: PgPool,
     pub name_json_path: String,
     pub nick_json_path: String,
+    /// Keys signing and verifying share codes, empty to leave them unsigned
+    pub share_signing_keys: ShareSigningKeys,
+    /// Caps applied while decoding share codes, see `ShareDecodeLimits::from_str` for their format
+    pub share_decode_limits: ShareDecodeLimits,
 }
@@ -1,14 +1,22 @@
 use crate::models::creature::creature_metadata::creature_role::CreatureRoleEnum;
+use crate::models::creature::creature_metadata::spellcasting_enum::{
+    SpellTraditionEnum, SpellcasterTypeEnum,
+};
 use crate::models::creature::creature_metadata::type_enum::CreatureTypeEnum;
 use crate::models::routers_validator_structs::{OrderEnum, PaginatedRequest};
 use crate::models::shared::alignment_enum::AlignmentEnum;
 use crate::models::shared::rarity_enum::RarityEnum;
 use crate::models::shared::size_enum::SizeEnum;
+use crate::models::shared::trait_filter_expression::TraitFilterExpression;
 use crate::traits::url::has_sort_fields::HasSortFields;
 use crate::traits::url::paginated_request_ext::PaginatedRequestExt;
-use serde::{Deserialize, Serialize};
-use strum::Display;
-use utoipa::{IntoParams, ToSchema};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use std::fmt;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+use utoipa::openapi::RefOr;
+use utoipa::openapi::schema::{ObjectBuilder, Schema, Type};
+use utoipa::{IntoParams, PartialSchema, ToSchema};
 
 #[derive(Serialize, Deserialize, ToSchema, Eq, PartialEq, Hash, Clone, Copy)]
 pub struct BestiaryRanges {
@@ -18,6 +26,18 @@ pub struct BestiaryRanges {
     pub max_level: i64,
     pub min_focus_points: i64,
     pub max_focus_points: i64,
+    pub min_ac: i64,
+    pub max_ac: i64,
+    pub min_fortitude: i64,
+    pub max_fortitude: i64,
+    pub min_reflex: i64,
+    pub max_reflex: i64,
+    pub min_will: i64,
+    pub max_will: i64,
+    pub min_perception: i64,
+    pub max_perception: i64,
+    pub min_skill_modifier: i64,
+    pub max_skill_modifier: i64,
 }
 
 impl Default for BestiaryRanges {
@@ -29,45 +49,134 @@ impl Default for BestiaryRanges {
             max_level: i64::MIN,
             min_focus_points: i64::MAX,
             max_focus_points: i64::MIN,
+            min_ac: i64::MAX,
+            max_ac: i64::MIN,
+            min_fortitude: i64::MAX,
+            max_fortitude: i64::MIN,
+            min_reflex: i64::MAX,
+            max_reflex: i64::MIN,
+            min_will: i64::MAX,
+            max_will: i64::MIN,
+            min_perception: i64::MAX,
+            max_perception: i64::MIN,
+            min_skill_modifier: i64::MAX,
+            max_skill_modifier: i64::MIN,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, ToSchema, Default, Eq, PartialEq, Hash, Clone, Display, Copy)]
+/// (De)serialized as its `Display` and `FromStr` string, ex `Level` or `Brute`,
+/// so that roles can be query params too
+#[derive(Default, Eq, PartialEq, Hash, Clone, Copy, Debug)]
 pub enum CreatureSortEnum {
-    #[serde(alias = "id", alias = "ID")]
     Id,
     #[default]
-    #[serde(alias = "name", alias = "NAME")]
     Name,
-    #[serde(alias = "level", alias = "LEVEL")]
     Level,
-    #[serde(alias = "trait", alias = "TRAIT")]
     Trait,
-    #[serde(alias = "size", alias = "SIZE")]
     Size,
-    #[serde(alias = "type", alias = "TYPE")]
     Type,
-    #[serde(alias = "hp", alias = "HP")]
     Hp,
-    #[serde(alias = "rarity", alias = "RARITY")]
     Rarity,
-    #[serde(alias = "family", alias = "FAMILY")]
     Family,
-    #[serde(alias = "alignment", alias = "ALIGNMENT")]
     Alignment,
-    #[serde(alias = "attack", alias = "ATTACK")]
     Attack,
-    #[serde(alias = "role", alias = "ROLE")]
+    /// Highest of the role percentages
     Role,
+    /// Percentage of the given role
+    RolePercentage(CreatureRoleEnum),
 }
 
-#[derive(
-    Serialize, Deserialize, IntoParams, ToSchema, Eq, PartialEq, Hash, Default, Copy, Clone,
-)]
+impl fmt::Display for CreatureSortEnum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Id => write!(f, "Id"),
+            Self::Name => write!(f, "Name"),
+            Self::Level => write!(f, "Level"),
+            Self::Trait => write!(f, "Trait"),
+            Self::Size => write!(f, "Size"),
+            Self::Type => write!(f, "Type"),
+            Self::Hp => write!(f, "Hp"),
+            Self::Rarity => write!(f, "Rarity"),
+            Self::Family => write!(f, "Family"),
+            Self::Alignment => write!(f, "Alignment"),
+            Self::Attack => write!(f, "Attack"),
+            Self::Role => write!(f, "Role"),
+            // Without spaces, to be usable as is in urls
+            Self::RolePercentage(role) => write!(f, "{}", role.to_string().replace(' ', "")),
+        }
+    }
+}
+
+impl FromStr for CreatureSortEnum {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "ID" => Ok(Self::Id),
+            "NAME" => Ok(Self::Name),
+            "LEVEL" => Ok(Self::Level),
+            "TRAIT" => Ok(Self::Trait),
+            "SIZE" => Ok(Self::Size),
+            "TYPE" => Ok(Self::Type),
+            "HP" => Ok(Self::Hp),
+            "RARITY" => Ok(Self::Rarity),
+            "FAMILY" => Ok(Self::Family),
+            "ALIGNMENT" => Ok(Self::Alignment),
+            "ATTACK" => Ok(Self::Attack),
+            "ROLE" => Ok(Self::Role),
+            role => CreatureRoleEnum::from_str(role.replace('_', " ").as_str())
+                .map(Self::RolePercentage),
+        }
+    }
+}
+
+impl Serialize for CreatureSortEnum {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CreatureSortEnum {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(s.as_str()).map_err(|()| de::Error::custom(format!("Unknown sort {s}")))
+    }
+}
+
+impl PartialSchema for CreatureSortEnum {
+    fn schema() -> RefOr<Schema> {
+        let sorts = [
+            Self::Id,
+            Self::Name,
+            Self::Level,
+            Self::Trait,
+            Self::Size,
+            Self::Type,
+            Self::Hp,
+            Self::Rarity,
+            Self::Family,
+            Self::Alignment,
+            Self::Attack,
+            Self::Role,
+        ]
+        .into_iter()
+        .chain(CreatureRoleEnum::iter().map(Self::RolePercentage));
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .enum_values(Some(sorts.map(|x| x.to_string())))
+            .into()
+    }
+}
+
+impl ToSchema for CreatureSortEnum {}
+
+#[derive(Serialize, Deserialize, IntoParams, ToSchema, Eq, PartialEq, Hash, Default, Clone)]
 pub struct BestiarySortData {
     pub sort_by: Option<CreatureSortEnum>,
     pub order_by: Option<OrderEnum>,
+    /// Multi-key sort, ex `level:desc,brute:desc,name:asc`. Roles sort by their percentage.
+    /// When given, `sort_by` and `order_by` are ignored.
+    pub sort: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, IntoParams, Eq, PartialEq, Hash)]
@@ -84,6 +193,9 @@ impl HasSortFields for BestiarySortData {
     fn order_by_field(&self) -> &Option<OrderEnum> {
         &self.order_by
     }
+    fn sort_field(&self) -> &Option<String> {
+        &self.sort
+    }
 }
 
 impl PaginatedRequestExt for BestiaryPaginatedRequest {
@@ -113,6 +225,21 @@ pub struct CreatureTableFieldsFilter {
     pub is_melee_filter: Vec<bool>,
     pub is_ranged_filter: Vec<bool>,
     pub is_spellcaster_filter: Vec<bool>,
+    pub immunity_whitelist_filter: Vec<String>,
+    pub immunity_blacklist_filter: Vec<String>,
+    pub resistance_whitelist_filter: Vec<String>,
+    pub resistance_blacklist_filter: Vec<String>,
+    pub weakness_whitelist_filter: Vec<String>,
+    pub weakness_blacklist_filter: Vec<String>,
+    pub speed_type_filter: Vec<String>,
+    pub min_speed_filter: Option<i64>,
+    pub sense_filter: Vec<String>,
+    pub language_filter: Vec<String>,
+    pub spell_tradition_filter: Vec<SpellTraditionEnum>,
+    pub spellcaster_type_filter: Vec<SpellcasterTypeEnum>,
+    pub spell_name_filter: Vec<String>,
+    pub min_spell_dc_filter: Option<i64>,
+    pub max_spell_dc_filter: Option<i64>,
     pub supported_version: Vec<String>,
 
     pub level_filter: Vec<i64>,
@@ -133,4 +260,43 @@ pub struct BestiaryFilterQuery {
     pub creature_table_fields_filter: CreatureTableFieldsFilter,
     pub trait_whitelist_filter: Vec<String>,
     pub trait_blacklist_filter: Vec<String>,
+    pub trait_expression_filter: Option<TraitFilterExpression>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("\"level\"", CreatureSortEnum::Level)]
+    #[case("\"HP\"", CreatureSortEnum::Hp)]
+    #[case(
+        "\"magical_striker\"",
+        CreatureSortEnum::RolePercentage(CreatureRoleEnum::MagicalStriker)
+    )]
+    fn sort_is_deserialized_from_its_string(
+        #[case] json: &str,
+        #[case] expected: CreatureSortEnum,
+    ) {
+        let sort: CreatureSortEnum = serde_json::from_str(json).unwrap();
+        assert_eq!(sort, expected);
+        assert_eq!(
+            serde_json::from_str::<CreatureSortEnum>(&serde_json::to_string(&sort).unwrap())
+                .unwrap(),
+            sort
+        );
+    }
+
+    #[rstest]
+    fn sort_schema_is_a_string() {
+        let schema = serde_json::to_value(CreatureSortEnum::schema()).unwrap();
+        assert_eq!(schema["type"], "string");
+        assert!(
+            schema["enum"]
+                .as_array()
+                .unwrap()
+                .contains(&serde_json::json!("MagicalStriker"))
+        );
+    }
 }
@@ -0,0 +1,601 @@
+use crate::models::creature::creature_metadata::variant_enum::CreatureVariant;
+use crate::models::creature::creature_struct::Creature;
+use crate::models::scales_struct::creature_scales::CreatureScales;
+use crate::traits::proficiency_without_level::ProficiencyWithoutLevel;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use strum::Display;
+use utoipa::ToSchema;
+
+pub const MIN_CREATURES_TO_COMPARE: usize = 2;
+pub const MAX_CREATURES_TO_COMPARE: usize = 5;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct CreatureComparisonEntry {
+    #[schema(example = 1)]
+    pub id: i64,
+    pub variant: Option<CreatureVariant>,
+    pub is_pwl_on: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct CreatureComparisonRequest {
+    #[schema(min_items = 2, max_items = 5)]
+    pub creatures: Vec<CreatureComparisonEntry>,
+}
+
+impl CreatureComparisonRequest {
+    pub fn is_valid(&self) -> bool {
+        (MIN_CREATURES_TO_COMPARE..=MAX_CREATURES_TO_COMPARE).contains(&self.creatures.len())
+    }
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    ToSchema,
+    Display,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Ord,
+    PartialOrd,
+    Debug,
+)]
+pub enum BenchmarkTierEnum {
+    Extreme,
+    High,
+    Moderate,
+    Low,
+    Terrible,
+}
+
+impl BenchmarkTierEnum {
+    /// Classify the value given the thresholds of the scale row, from the highest to the lowest.
+    /// If the scale does not have a lower bound for low (or an extreme one) the tier is
+    /// capped accordingly.
+    const fn from_thresholds(
+        value: i64,
+        extreme: Option<i64>,
+        high: i64,
+        moderate: i64,
+        low: Option<i64>,
+    ) -> Self {
+        if let Some(extreme) = extreme
+            && value >= extreme
+        {
+            Self::Extreme
+        } else if value >= high {
+            Self::High
+        } else if value >= moderate {
+            Self::Moderate
+        } else if let Some(low) = low
+            && value < low
+        {
+            Self::Terrible
+        } else {
+            Self::Low
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Display, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum ComparedStatSection {
+    Core,
+    Combat,
+    Extra,
+    Spellcaster,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ComparedCreature {
+    pub id: i64,
+    pub name: String,
+    pub variant: CreatureVariant,
+    #[schema(example = 1)]
+    pub level: i64,
+    pub is_pwl_on: bool,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ComparedValue {
+    #[schema(example = 20)]
+    pub value: i64,
+    /// Difference with the value of the first creature, if it has that statistic.
+    #[schema(example = 2)]
+    pub delta: Option<i64>,
+    pub tier: Option<BenchmarkTierEnum>,
+}
+
+/// A single statistic, with one value for each compared creature (in request order).
+/// A None value means that the creature does not have that statistic.
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ComparedStat {
+    pub section: ComparedStatSection,
+    #[schema(example = "ac")]
+    pub name: String,
+    pub values: Vec<Option<ComparedValue>>,
+}
+
+/// Traits of a creature compared with the first creature of the comparison.
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct TraitsDifference {
+    pub id: i64,
+    pub gained: Vec<String>,
+    pub lost: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ComparedImmunity {
+    #[schema(example = "fire")]
+    pub name: String,
+    pub values: Vec<bool>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct CreatureComparisonResponse {
+    pub creatures: Vec<ComparedCreature>,
+    pub stats: Vec<ComparedStat>,
+    pub traits: Vec<TraitsDifference>,
+    pub resistances: Vec<ComparedStat>,
+    pub weaknesses: Vec<ComparedStat>,
+    pub immunities: Vec<ComparedImmunity>,
+}
+
+struct RawStat {
+    section: ComparedStatSection,
+    name: String,
+    value: i64,
+    tier: Option<BenchmarkTierEnum>,
+}
+
+impl CreatureComparisonResponse {
+    /// Builds the aligned comparison of the given creatures. Each creature comes with
+    /// a flag stating if proficiency without level was applied to it: benchmark tiers
+    /// are always computed on level-inclusive values, at the creature's variant level.
+    pub fn from_creatures(creatures: &[(Creature, bool)], scales: &CreatureScales) -> Self {
+        let raw_stats: Vec<Vec<RawStat>> = creatures
+            .iter()
+            .map(|(cr, is_pwl_on)| get_raw_stats(cr, *is_pwl_on, scales))
+            .collect();
+        Self {
+            creatures: creatures
+                .iter()
+                .map(|(cr, is_pwl_on)| ComparedCreature {
+                    id: cr.core_data.essential.id,
+                    name: cr.core_data.essential.name.clone(),
+                    variant: cr.variant_data.variant,
+                    level: cr.variant_data.level,
+                    is_pwl_on: *is_pwl_on,
+                })
+                .collect(),
+            stats: align_stats(&raw_stats, |x| !x.name.contains(':')),
+            traits: get_traits_differences(creatures),
+            resistances: align_stats(&raw_stats, |x| x.name.starts_with("resistance:")),
+            weaknesses: align_stats(&raw_stats, |x| x.name.starts_with("weakness:")),
+            immunities: get_immunities(creatures),
+        }
+    }
+}
+
+fn align_stats(
+    raw_stats: &[Vec<RawStat>],
+    predicate: impl Fn(&RawStat) -> bool,
+) -> Vec<ComparedStat> {
+    let mut aligned: Vec<ComparedStat> = Vec::new();
+    for (i, cr_stats) in raw_stats.iter().enumerate() {
+        for stat in cr_stats.iter().filter(|x| predicate(x)) {
+            let pos = aligned
+                .iter()
+                .position(|x| x.section == stat.section && x.name == stat.name)
+                .unwrap_or_else(|| {
+                    aligned.push(ComparedStat {
+                        section: stat.section,
+                        name: stat.name.clone(),
+                        values: vec![None; raw_stats.len()],
+                    });
+                    aligned.len() - 1
+                });
+            aligned[pos].values[i] = Some(ComparedValue {
+                value: stat.value,
+                delta: None,
+                tier: stat.tier,
+            });
+        }
+    }
+    for stat in &mut aligned {
+        let first = stat.values.first().cloned().flatten().map(|x| x.value);
+        for value in stat.values.iter_mut().flatten() {
+            value.delta = first.map(|f| value.value - f);
+        }
+    }
+    aligned
+}
+
+fn get_raw_stats(cr: &Creature, is_pwl_on: bool, scales: &CreatureScales) -> Vec<RawStat> {
+    let lvl = cr.variant_data.level;
+    // Scales are level-inclusive, the level removed by PWL must be added back before tiering
+    // or, for the spell DCs and attacks, removed from their scales
+    let pwl_mod = if is_pwl_on { cr.pwl_mod() } else { 0 };
+    let pwl_bonus = i64::try_from(pwl_mod).unwrap_or(i64::MAX);
+    let mut stats = vec![];
+    let mut push = |section, name: &str, value: i64, tier: Option<BenchmarkTierEnum>| {
+        stats.push(RawStat {
+            section,
+            name: name.to_string(),
+            value,
+            tier,
+        });
+    };
+
+    let hp = cr.core_data.essential.hp;
+    push(ComparedStatSection::Core, "level", lvl, None);
+    push(
+        ComparedStatSection::Core,
+        "hp",
+        hp,
+        scales.hp_scales.get(&lvl).map(|s| {
+            if hp > s.high_ub {
+                BenchmarkTierEnum::Extreme
+            } else {
+                BenchmarkTierEnum::from_thresholds(
+                    hp,
+                    None,
+                    s.high_lb,
+                    s.moderate_lb,
+                    Some(s.low_lb),
+                )
+            }
+        }),
+    );
+    push(
+        ComparedStatSection::Core,
+        "focus_points",
+        cr.core_data.essential.focus_points,
+        None,
+    );
+
+    if let Some(combat) = &cr.combat_data {
+        let ac = i64::from(combat.ac);
+        push(
+            ComparedStatSection::Combat,
+            "ac",
+            ac,
+            scales.ac_scales.get(&lvl).map(|s| {
+                BenchmarkTierEnum::from_thresholds(
+                    ac + pwl_bonus,
+                    Some(s.extreme),
+                    s.high,
+                    s.moderate,
+                    Some(s.low),
+                )
+            }),
+        );
+        let st = &combat.saving_throws;
+        for (name, value) in [
+            ("fortitude", st.fortitude),
+            ("reflex", st.reflex),
+            ("will", st.will),
+        ] {
+            push(
+                ComparedStatSection::Combat,
+                name,
+                value,
+                scales.saving_throw_scales.get(&lvl).map(|s| {
+                    BenchmarkTierEnum::from_thresholds(
+                        value + pwl_bonus,
+                        Some(s.extreme),
+                        s.high,
+                        s.moderate,
+                        Some(s.terrible),
+                    )
+                }),
+            );
+        }
+        if let Some(to_hit) = combat
+            .weapons
+            .iter()
+            .filter_map(|wp| wp.weapon_data.to_hit_bonus)
+            .max()
+        {
+            push(
+                ComparedStatSection::Combat,
+                "best_strike_bonus",
+                to_hit,
+                scales.strike_bonus_scales.get(&lvl).map(|s| {
+                    BenchmarkTierEnum::from_thresholds(
+                        to_hit + pwl_bonus,
+                        Some(s.extreme),
+                        s.high,
+                        s.moderate,
+                        None,
+                    )
+                }),
+            );
+        }
+        if let Some(dmg) = combat.weapons.iter().map(|wp| wp.get_avg_dmg()).max() {
+            push(
+                ComparedStatSection::Combat,
+                "best_strike_avg_dmg",
+                dmg,
+                None,
+            );
+        }
+        for res in &combat.resistances {
+            push_res_weak(
+                &mut push,
+                format!("resistance:{}", res.core.name).as_str(),
+                res.core.value,
+                lvl,
+                scales,
+            );
+        }
+        for (name, value) in &combat.weaknesses {
+            push_res_weak(
+                &mut push,
+                format!("weakness:{name}").as_str(),
+                i64::from(*value),
+                lvl,
+                scales,
+            );
+        }
+    }
+
+    if let Some(extra) = &cr.extra_data {
+        let perception = i64::from(extra.perception);
+        push(
+            ComparedStatSection::Extra,
+            "perception",
+            perception,
+            scales.perception_scales.get(&lvl).map(|s| {
+                BenchmarkTierEnum::from_thresholds(
+                    perception + pwl_bonus,
+                    Some(s.extreme),
+                    s.high,
+                    s.moderate,
+                    Some(s.terrible),
+                )
+            }),
+        );
+        let ab = &extra.ability_scores;
+        for (name, value) in [
+            ("strength", ab.strength),
+            ("dexterity", ab.dexterity),
+            ("constitution", ab.constitution),
+            ("intelligence", ab.intelligence),
+            ("wisdom", ab.wisdom),
+            ("charisma", ab.charisma),
+        ] {
+            push(
+                ComparedStatSection::Extra,
+                name,
+                value,
+                scales.ability_scales.get(&lvl).map(|s| {
+                    BenchmarkTierEnum::from_thresholds(
+                        value,
+                        s.extreme.map(i64::from),
+                        s.high,
+                        s.moderate,
+                        None,
+                    )
+                }),
+            );
+        }
+        for skill in &extra.skills {
+            push(
+                ComparedStatSection::Extra,
+                format!("skill_{}", skill.name.to_lowercase()).as_str(),
+                skill.modifier,
+                scales.skill_scales.get(&lvl).map(|s| {
+                    BenchmarkTierEnum::from_thresholds(
+                        skill.modifier + pwl_bonus,
+                        Some(s.extreme),
+                        s.high,
+                        s.moderate,
+                        Some(s.low_lb),
+                    )
+                }),
+            );
+        }
+        for (name, value) in &extra.speeds {
+            push(
+                ComparedStatSection::Extra,
+                format!("speed_{}", name.to_lowercase()).as_str(),
+                i64::from(*value),
+                None,
+            );
+        }
+    }
+
+    if let Some(spells) = &cr.spellcaster_data {
+        push(
+            ComparedStatSection::Spellcaster,
+            "n_of_spells",
+            spells.get_total_n_of_spells() as i64,
+            None,
+        );
+        if let Some(dc) = spells.get_highest_spell_dc_mod() {
+            push(
+                ComparedStatSection::Spellcaster,
+                "highest_spell_dc",
+                dc,
+                scales.spell_dc_and_atk_scales.get(&lvl).map(|s| {
+                    let s = s.clone().remove_pwl_mod(pwl_mod);
+                    BenchmarkTierEnum::from_thresholds(
+                        dc,
+                        Some(s.extreme_dc),
+                        s.high_dc,
+                        s.moderate_dc,
+                        None,
+                    )
+                }),
+            );
+        }
+        if let Some(atk) = spells
+            .spellcaster_entries
+            .iter()
+            .map(|x| x.spellcaster_data.spellcasting_atk_mod)
+            .max()
+        {
+            push(
+                ComparedStatSection::Spellcaster,
+                "highest_spell_atk",
+                atk,
+                scales.spell_dc_and_atk_scales.get(&lvl).map(|s| {
+                    let s = s.clone().remove_pwl_mod(pwl_mod);
+                    BenchmarkTierEnum::from_thresholds(
+                        atk,
+                        Some(s.extreme_atk_bonus),
+                        s.high_atk_bonus,
+                        s.moderate_atk_bonus,
+                        None,
+                    )
+                }),
+            );
+        }
+        if let Some(max_rank) = spells
+            .spellcaster_entries
+            .iter()
+            .flat_map(|x| x.spells.iter().map(|s| s.level))
+            .max()
+        {
+            push(
+                ComparedStatSection::Spellcaster,
+                "highest_spell_rank",
+                max_rank,
+                None,
+            );
+        }
+    }
+    stats
+}
+
+/// Resistances and weaknesses are not proficiency based, they are not affected by PWL.
+fn push_res_weak(
+    push: &mut impl FnMut(ComparedStatSection, &str, i64, Option<BenchmarkTierEnum>),
+    name: &str,
+    value: i64,
+    lvl: i64,
+    scales: &CreatureScales,
+) {
+    push(
+        ComparedStatSection::Combat,
+        name,
+        value,
+        scales.res_weak_scales.get(&lvl).map(|s| {
+            if value > s.max {
+                BenchmarkTierEnum::Extreme
+            } else {
+                BenchmarkTierEnum::from_thresholds(value, None, s.max, s.min, None)
+            }
+        }),
+    );
+}
+
+fn get_traits_differences(creatures: &[(Creature, bool)]) -> Vec<TraitsDifference> {
+    let traits_of = |cr: &Creature| -> BTreeSet<String> {
+        cr.core_data.traits.iter().map(|t| t.name.clone()).collect()
+    };
+    let first_traits = creatures
+        .first()
+        .map(|(cr, _)| traits_of(cr))
+        .unwrap_or_default();
+    creatures
+        .iter()
+        .map(|(cr, _)| {
+            let curr_traits = traits_of(cr);
+            TraitsDifference {
+                id: cr.core_data.essential.id,
+                gained: curr_traits.difference(&first_traits).cloned().collect(),
+                lost: first_traits.difference(&curr_traits).cloned().collect(),
+            }
+        })
+        .collect()
+}
+
+fn get_immunities(creatures: &[(Creature, bool)]) -> Vec<ComparedImmunity> {
+    let immunities_of = |cr: &Creature| -> Vec<String> {
+        cr.combat_data
+            .as_ref()
+            .map(|x| x.immunities.clone())
+            .unwrap_or_default()
+    };
+    creatures
+        .iter()
+        .flat_map(|(cr, _)| immunities_of(cr))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|name| ComparedImmunity {
+            values: creatures
+                .iter()
+                .map(|(cr, _)| immunities_of(cr).contains(&name))
+                .collect(),
+            name,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(25, BenchmarkTierEnum::Extreme)]
+    #[case(22, BenchmarkTierEnum::High)]
+    #[case(19, BenchmarkTierEnum::Moderate)]
+    #[case(16, BenchmarkTierEnum::Low)]
+    #[case(12, BenchmarkTierEnum::Terrible)]
+    fn tier_from_thresholds(#[case] value: i64, #[case] expected: BenchmarkTierEnum) {
+        assert_eq!(
+            expected,
+            BenchmarkTierEnum::from_thresholds(value, Some(24), 21, 18, Some(15))
+        );
+    }
+
+    #[rstest]
+    fn tier_without_extreme_and_low_is_capped() {
+        assert_eq!(
+            BenchmarkTierEnum::High,
+            BenchmarkTierEnum::from_thresholds(100, None, 21, 18, None)
+        );
+        assert_eq!(
+            BenchmarkTierEnum::Low,
+            BenchmarkTierEnum::from_thresholds(-100, None, 21, 18, None)
+        );
+    }
+
+    #[rstest]
+    fn stats_are_aligned_with_first_creature_delta() {
+        let raw_stats = vec![
+            vec![RawStat {
+                section: ComparedStatSection::Combat,
+                name: "ac".to_string(),
+                value: 18,
+                tier: None,
+            }],
+            vec![
+                RawStat {
+                    section: ComparedStatSection::Combat,
+                    name: "ac".to_string(),
+                    value: 21,
+                    tier: None,
+                },
+                RawStat {
+                    section: ComparedStatSection::Extra,
+                    name: "speed_fly".to_string(),
+                    value: 30,
+                    tier: None,
+                },
+            ],
+        ];
+        let aligned = align_stats(&raw_stats, |_| true);
+        assert_eq!(2, aligned.len());
+        assert_eq!(Some(3), aligned[0].values[1].as_ref().unwrap().delta);
+        assert_eq!(Some(0), aligned[0].values[0].as_ref().unwrap().delta);
+        assert!(aligned[1].values[0].is_none());
+        assert_eq!(None, aligned[1].values[1].as_ref().unwrap().delta);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::models::creature::creature_metadata::variant_enum::CreatureVariant;
 use crate::models::creature::items::skill::Skill;
 use crate::models::db::sense::Sense;
+use crate::models::foundry::inline_markup::adjust_description_rolls;
 use crate::models::item::item_struct::Item;
 use crate::models::shared::action::Action;
 use serde::{Deserialize, Serialize};
@@ -68,13 +69,37 @@ impl CreatureExtraData {
 
         ex_data
     }
-    /// Lowers skill and perception by the given `pwl_mod`
+    /// Lowers skill, perception and the DCs written in actions by the given `pwl_mod`
     pub fn convert_from_base_to_pwl(self, pwl_mod: u64) -> Self {
-        self.add_mod_to_perception_and_skill_mods(-i64::try_from(pwl_mod).unwrap_or(i64::MAX))
+        let modifier = -i64::try_from(pwl_mod).unwrap_or(i64::MAX);
+        self.add_mod_to_perception_and_skill_mods(modifier)
+            .add_mod_to_action_rolls(modifier, 0)
+    }
+
+    /// Adds the modifiers to the DCs and damage written in the descriptions of actions,
+    /// the damage of limited-use abilities is changed twice as much.
+    fn add_mod_to_action_rolls(self, dc_modifier: i64, dmg_modifier: i64) -> Self {
+        let mut ex_data = self;
+        for action in &mut ex_data.actions {
+            let dmg_modifier = if action.is_limited_use() {
+                dmg_modifier * 2
+            } else {
+                dmg_modifier
+            };
+            action.core_action.description = adjust_description_rolls(
+                &action.core_action.description,
+                dc_modifier,
+                dmg_modifier,
+            );
+        }
+        ex_data
     }
 
     /// Increase/Decrease Perception, and skill modifiers by 2.
+    /// DCs and damage written in actions are adjusted as well, see `add_mod_to_action_rolls`.
     pub fn convert_from_base_to_variant(self, variant: CreatureVariant) -> Self {
-        self.add_mod_to_perception_and_skill_mods(variant.to_adjustment_modifier())
+        let modifier = variant.to_adjustment_modifier();
+        self.add_mod_to_perception_and_skill_mods(modifier)
+            .add_mod_to_action_rolls(modifier, modifier)
     }
 }
@@ -53,9 +53,8 @@ impl CreatureSpellcasterData {
         self.add_mod_to_spellcaster_atk_and_dc(-i64::try_from(pwl_mod).unwrap_or(i64::MAX))
     }
 
-    /// Increase/Decrease the damage of its Strikes and other offensive abilities by 2.
-    /// If the creature has limits on how many times or how often it can use an ability
-    /// (such as a spellcaster’s spells or a dragon’s breath), decrease the damage by 4 instead.
+    /// Increase/Decrease spell attack modifiers and spell DCs by 2.
+    /// Spells are stored without their damage, which is left to the spell itself.
     pub fn convert_from_base_to_variant(self, variant: CreatureVariant) -> Self {
         self.add_mod_to_spellcaster_atk_and_dc(variant.to_adjustment_modifier())
     }
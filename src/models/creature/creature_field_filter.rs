@@ -1,39 +1,114 @@
-use crate::models::creature::creature_metadata::creature_role::CreatureRoleEnum;
-use crate::models::creature::creature_metadata::type_enum::CreatureTypeEnum;
-use crate::models::shared::alignment_enum::AlignmentEnum;
-use crate::models::shared::pf_version_enum::GameSystemVersionEnum;
-use crate::models::shared::rarity_enum::RarityEnum;
-use crate::models::shared::size_enum::SizeEnum;
-use serde::{Deserialize, Serialize};
-#[allow(unused_imports)] // it's actually used in the example schema
-use serde_json::json;
-use std::collections::BTreeMap;
-use utoipa::{IntoParams, ToSchema};
+pub use params::CreatureFieldFilters;
 
-#[derive(Serialize, Deserialize, IntoParams, ToSchema, Default)]
-pub struct CreatureFieldFilters {
-    pub name_filter: Option<String>,
-    pub source_filter: Option<Vec<String>>,
-    pub family_filter: Option<Vec<String>>,
-    pub rarity_filter: Option<Vec<RarityEnum>>,
-    pub size_filter: Option<Vec<SizeEnum>>,
-    pub alignment_filter: Option<Vec<AlignmentEnum>>,
-    pub trait_whitelist_filter: Option<Vec<String>>,
-    pub trait_blacklist_filter: Option<Vec<String>>,
-    pub role_filter: Option<Vec<CreatureRoleEnum>>,
-    pub type_filter: Option<Vec<CreatureTypeEnum>>,
-    #[schema(minimum = 0, maximum = 100, example = 50)]
-    pub role_threshold: Option<i64>,
-    #[schema(minimum = 0, example = 0)]
-    pub min_hp_filter: Option<i64>,
-    #[schema(minimum = 0, example = 100)]
-    pub max_hp_filter: Option<i64>,
-    #[schema(minimum = -1, example = -1)]
-    pub min_level_filter: Option<i64>,
-    #[schema(minimum = -1, example = 5)]
-    pub max_level_filter: Option<i64>,
+// The impl IntoParams generates for this many filters has a large stack frame, but it is only
+// run to build the docs. Lints can't be set on derived impls, so the struct gets its own module.
+#[allow(clippy::large_stack_frames)]
+mod params {
+    use crate::models::creature::creature_metadata::creature_role::CreatureRoleEnum;
+    use crate::models::creature::creature_metadata::spellcasting_enum::{
+        SpellTraditionEnum, SpellcasterTypeEnum,
+    };
+    use crate::models::creature::creature_metadata::type_enum::CreatureTypeEnum;
+    use crate::models::shared::alignment_enum::AlignmentEnum;
+    use crate::models::shared::name_match_mode_enum::NameMatchModeEnum;
+    use crate::models::shared::pf_version_enum::GameSystemVersionEnum;
+    use crate::models::shared::rarity_enum::RarityEnum;
+    use crate::models::shared::size_enum::SizeEnum;
+    use crate::models::shared::trait_filter_expression::TraitFilterExpression;
+    use serde::{Deserialize, Serialize};
+    #[allow(unused_imports)] // it's actually used in the example schema
+    use serde_json::json;
+    use std::collections::BTreeMap;
+    use utoipa::{IntoParams, ToSchema};
 
-    #[schema(example = json!({"melee": true, "ranged": false, "spellcaster": true}))]
-    pub attack_data_filter: Option<BTreeMap<String, Option<bool>>>,
-    pub game_system_version: Option<GameSystemVersionEnum>,
+    #[derive(Serialize, Deserialize, IntoParams, ToSchema, Default, Clone)]
+    pub struct CreatureFieldFilters {
+        pub name_filter: Option<String>,
+        pub name_match_mode: Option<NameMatchModeEnum>,
+        #[schema(example = "swallow whole")]
+        pub text_search_filter: Option<String>,
+        pub source_filter: Option<Vec<String>>,
+        pub family_filter: Option<Vec<String>>,
+        pub rarity_filter: Option<Vec<RarityEnum>>,
+        pub size_filter: Option<Vec<SizeEnum>>,
+        pub alignment_filter: Option<Vec<AlignmentEnum>>,
+        pub trait_whitelist_filter: Option<Vec<String>>,
+        pub trait_blacklist_filter: Option<Vec<String>>,
+        pub trait_expression_filter: Option<TraitFilterExpression>,
+        pub role_filter: Option<Vec<CreatureRoleEnum>>,
+        pub type_filter: Option<Vec<CreatureTypeEnum>>,
+        #[schema(minimum = 0, maximum = 100, example = 50)]
+        pub role_threshold: Option<i64>,
+        #[schema(minimum = 0, example = 0)]
+        pub min_hp_filter: Option<i64>,
+        #[schema(minimum = 0, example = 100)]
+        pub max_hp_filter: Option<i64>,
+        #[schema(minimum = -1, example = -1)]
+        pub min_level_filter: Option<i64>,
+        #[schema(minimum = -1, example = 5)]
+        pub max_level_filter: Option<i64>,
+        #[schema(example = 10)]
+        pub min_ac_filter: Option<i64>,
+        #[schema(example = 30)]
+        pub max_ac_filter: Option<i64>,
+        #[schema(example = 0)]
+        pub min_fortitude_filter: Option<i64>,
+        #[schema(example = 20)]
+        pub max_fortitude_filter: Option<i64>,
+        #[schema(example = 0)]
+        pub min_reflex_filter: Option<i64>,
+        #[schema(example = 20)]
+        pub max_reflex_filter: Option<i64>,
+        #[schema(example = 0)]
+        pub min_will_filter: Option<i64>,
+        #[schema(example = 20)]
+        pub max_will_filter: Option<i64>,
+        #[schema(example = 0)]
+        pub min_perception_filter: Option<i64>,
+        #[schema(example = 20)]
+        pub max_perception_filter: Option<i64>,
+        #[schema(minimum = 0, example = 0)]
+        pub min_focus_points_filter: Option<i64>,
+        #[schema(minimum = 0, example = 3)]
+        pub max_focus_points_filter: Option<i64>,
+        /// Skill the skill modifier bounds refer to. If missing, any skill can satisfy them.
+        #[schema(example = "stealth")]
+        pub skill_filter: Option<String>,
+        #[schema(example = 20)]
+        pub min_skill_modifier_filter: Option<i64>,
+        #[schema(example = 30)]
+        pub max_skill_modifier_filter: Option<i64>,
+
+        #[schema(example = json!(["fire", "poison"]))]
+        pub immunity_whitelist_filter: Option<Vec<String>>,
+        #[schema(example = json!(["fire"]))]
+        pub immunity_blacklist_filter: Option<Vec<String>>,
+        #[schema(example = json!(["physical"]))]
+        pub resistance_whitelist_filter: Option<Vec<String>>,
+        #[schema(example = json!(["fire"]))]
+        pub resistance_blacklist_filter: Option<Vec<String>>,
+        #[schema(example = json!(["cold-iron"]))]
+        pub weakness_whitelist_filter: Option<Vec<String>>,
+        pub weakness_blacklist_filter: Option<Vec<String>>,
+        #[schema(example = json!(["fly", "swim"]))]
+        pub speed_type_filter: Option<Vec<String>>,
+        #[schema(minimum = 0, example = 30)]
+        pub min_speed_filter: Option<i64>,
+        #[schema(example = json!(["darkvision", "tremorsense"]))]
+        pub sense_filter: Option<Vec<String>>,
+        #[schema(example = json!(["draconic"]))]
+        pub language_filter: Option<Vec<String>>,
+        pub spell_tradition_filter: Option<Vec<SpellTraditionEnum>>,
+        pub spellcaster_type_filter: Option<Vec<SpellcasterTypeEnum>>,
+        #[schema(example = json!(["fireball"]))]
+        pub spell_name_filter: Option<Vec<String>>,
+        #[schema(minimum = 0, example = 15)]
+        pub min_spell_dc_filter: Option<i64>,
+        #[schema(minimum = 0, example = 30)]
+        pub max_spell_dc_filter: Option<i64>,
+
+        #[schema(example = json!({"melee": true, "ranged": false, "spellcaster": true}))]
+        pub attack_data_filter: Option<BTreeMap<String, Option<bool>>>,
+        pub game_system_version: Option<GameSystemVersionEnum>,
+    }
 }
@@ -17,7 +17,18 @@ use utoipa::ToSchema;
 const MISSING_FIELD_DISTANCE: u16 = 20;
 
 #[derive(
-    Serialize, Deserialize, EnumIter, Clone, ToSchema, Eq, Hash, PartialEq, Ord, PartialOrd,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    Clone,
+    Copy,
+    Debug,
+    ToSchema,
+    Eq,
+    Hash,
+    PartialEq,
+    Ord,
+    PartialOrd,
 )]
 pub enum CreatureRoleEnum {
     Brute,
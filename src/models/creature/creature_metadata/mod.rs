@@ -1,3 +1,4 @@
 pub mod creature_role;
+pub mod spellcasting_enum;
 pub mod type_enum;
 pub mod variant_enum;
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+use utoipa::ToSchema;
+
+#[derive(
+    Serialize,
+    Deserialize,
+    ToSchema,
+    Display,
+    EnumIter,
+    Eq,
+    Hash,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Clone,
+    Copy,
+    Debug,
+)]
+pub enum SpellTraditionEnum {
+    #[serde(alias = "arcane", alias = "ARCANE")]
+    Arcane,
+    #[serde(alias = "divine", alias = "DIVINE")]
+    Divine,
+    #[serde(alias = "occult", alias = "OCCULT")]
+    Occult,
+    #[serde(alias = "primal", alias = "PRIMAL")]
+    Primal,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    ToSchema,
+    Display,
+    EnumIter,
+    Eq,
+    Hash,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Clone,
+    Copy,
+    Debug,
+)]
+pub enum SpellcasterTypeEnum {
+    #[serde(alias = "prepared", alias = "PREPARED")]
+    Prepared,
+    #[serde(alias = "spontaneous", alias = "SPONTANEOUS")]
+    Spontaneous,
+    #[serde(alias = "innate", alias = "INNATE")]
+    Innate,
+    #[serde(alias = "focus", alias = "FOCUS")]
+    Focus,
+}
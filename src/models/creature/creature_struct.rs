@@ -9,6 +9,7 @@ use crate::models::shared::game_system_enum::GameSystem;
 use crate::models::shared::pf_version_enum::GameSystemVersionEnum;
 use crate::traits::filterable::Filterable;
 use crate::traits::has_level::HasLevel;
+use crate::traits::proficiency_without_level::{ProficiencyWithoutLevel, pwl_mod_from_level};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Eq, Hash, PartialEq)]
@@ -27,6 +28,27 @@ impl HasLevel for Creature {
     }
 }
 
+impl ProficiencyWithoutLevel for Creature {
+    fn pwl_mod(&self) -> u64 {
+        pwl_mod_from_level(self.core_data.essential.base_level)
+    }
+
+    fn remove_pwl_mod(self, pwl_mod: u64) -> Self {
+        Self {
+            core_data: self.core_data,
+            variant_data: self.variant_data,
+            extra_data: self.extra_data.map(|x| x.convert_from_base_to_pwl(pwl_mod)),
+            combat_data: self
+                .combat_data
+                .map(|x| x.convert_from_base_to_pwl(pwl_mod)),
+            spellcaster_data: self
+                .spellcaster_data
+                .map(|x| x.convert_from_base_to_pwl(pwl_mod)),
+            game_system: self.game_system,
+        }
+    }
+}
+
 impl Creature {
     /// Decrease the creature’s level by 1; if the creature is level 1, instead decrease its level by 2.
     /// Decrease the creature’s HP based on its starting level.
@@ -46,26 +68,6 @@ impl Creature {
         cr
     }
 
-    pub fn convert_creature_to_pwl(self) -> Self {
-        let pwl_mod = if self.core_data.essential.base_level >= 0 {
-            self.core_data.essential.base_level.unsigned_abs()
-        } else {
-            0
-        };
-
-        Self {
-            core_data: self.core_data,
-            variant_data: self.variant_data,
-            extra_data: self.extra_data.map(|x| x.convert_from_base_to_pwl(pwl_mod)),
-            combat_data: self
-                .combat_data
-                .map(|x| x.convert_from_base_to_pwl(pwl_mod)),
-            spellcaster_data: self
-                .spellcaster_data
-                .map(|x| x.convert_from_base_to_pwl(pwl_mod)),
-            game_system: self.game_system,
-        }
-    }
     pub fn from_core(core: CreatureCoreData, game_system: GameSystem) -> Self {
         let level = core.essential.base_level;
         let archive_link = core.derived.archive_link.clone();
@@ -119,6 +121,34 @@ impl Filterable for Creature {
             && filters
                 .max_level_filter
                 .is_none_or(|max_lvl| self.variant_data.level <= max_lvl)
+            && filters
+                .max_focus_points_filter
+                .is_none_or(|max_fp| self.core_data.essential.focus_points <= max_fp)
+            && filters.max_ac_filter.is_none_or(|max_ac| {
+                self.combat_data
+                    .as_ref()
+                    .is_some_and(|combat| i64::from(combat.ac) <= max_ac)
+            })
+            && filters.max_fortitude_filter.is_none_or(|max_fort| {
+                self.combat_data
+                    .as_ref()
+                    .is_some_and(|combat| combat.saving_throws.fortitude <= max_fort)
+            })
+            && filters.max_reflex_filter.is_none_or(|max_ref| {
+                self.combat_data
+                    .as_ref()
+                    .is_some_and(|combat| combat.saving_throws.reflex <= max_ref)
+            })
+            && filters.max_will_filter.is_none_or(|max_will| {
+                self.combat_data
+                    .as_ref()
+                    .is_some_and(|combat| combat.saving_throws.will <= max_will)
+            })
+            && filters.max_perception_filter.is_none_or(|max_per| {
+                self.extra_data
+                    .as_ref()
+                    .is_some_and(|extra| i64::from(extra.perception) <= max_per)
+            })
     }
 
     fn does_it_pass_lb_filters(&self, filters: &Self::FilterImpl) -> bool {
@@ -128,15 +158,42 @@ impl Filterable for Creature {
             && filters
                 .min_level_filter
                 .is_none_or(|min_lvl| self.variant_data.level >= min_lvl)
+            && filters
+                .min_focus_points_filter
+                .is_none_or(|min_fp| self.core_data.essential.focus_points >= min_fp)
+            && filters.min_ac_filter.is_none_or(|min_ac| {
+                self.combat_data
+                    .as_ref()
+                    .is_some_and(|combat| i64::from(combat.ac) >= min_ac)
+            })
+            && filters.min_fortitude_filter.is_none_or(|min_fort| {
+                self.combat_data
+                    .as_ref()
+                    .is_some_and(|combat| combat.saving_throws.fortitude >= min_fort)
+            })
+            && filters.min_reflex_filter.is_none_or(|min_ref| {
+                self.combat_data
+                    .as_ref()
+                    .is_some_and(|combat| combat.saving_throws.reflex >= min_ref)
+            })
+            && filters.min_will_filter.is_none_or(|min_will| {
+                self.combat_data
+                    .as_ref()
+                    .is_some_and(|combat| combat.saving_throws.will >= min_will)
+            })
+            && filters.min_perception_filter.is_none_or(|min_per| {
+                self.extra_data
+                    .as_ref()
+                    .is_some_and(|extra| i64::from(extra.perception) >= min_per)
+            })
     }
 
     fn does_it_pass_string_filters(&self, filters: &Self::FilterImpl) -> bool {
         filters.name_filter.as_ref().is_none_or(|name| {
-            self.core_data
-                .essential
-                .name
-                .to_lowercase()
-                .contains(name.to_lowercase().as_str())
+            filters
+                .name_match_mode
+                .unwrap_or_default()
+                .is_matching(name, self.core_data.essential.name.as_str())
         }) && filters.family_filter.as_ref().is_none_or(|x| {
             x.iter().any(|fam| {
                 self.core_data
@@ -163,6 +220,15 @@ impl Filterable for Creature {
                         .eq(filter_trait.to_lowercase().as_str())
                 })
             })
+        }) && filters.trait_expression_filter.as_ref().is_none_or(|expr| {
+            expr.is_satisfied_by(
+                &self
+                    .core_data
+                    .traits
+                    .iter()
+                    .map(|cr_trait| cr_trait.name.as_str())
+                    .collect::<Vec<_>>(),
+            )
         })
     }
 
@@ -211,5 +277,309 @@ impl Filterable for Creature {
                 GameSystemVersionEnum::Remaster => self.core_data.essential.remaster,
                 GameSystemVersionEnum::Any => true,
             }
+            && self.does_it_pass_defense_filters(filters)
+            && self.does_it_pass_environment_filters(filters)
+            && self.does_it_pass_spellcasting_filters(filters)
+            && self.does_it_pass_skill_filters(filters)
+    }
+}
+
+/// The filters below mirror the statements of the bestiary query, a creature missing
+/// the data a set filter checks is rejected.
+impl Creature {
+    fn does_it_pass_defense_filters(&self, filters: &CreatureFieldFilters) -> bool {
+        let defenses = self.combat_data.as_ref().map(|combat| {
+            [
+                combat.immunities.iter().map(String::as_str).collect(),
+                combat
+                    .resistances
+                    .iter()
+                    .map(|x| x.core.name.as_str())
+                    .collect(),
+                combat
+                    .weaknesses
+                    .keys()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>(),
+            ]
+        });
+        [
+            (
+                &filters.immunity_whitelist_filter,
+                &filters.immunity_blacklist_filter,
+            ),
+            (
+                &filters.resistance_whitelist_filter,
+                &filters.resistance_blacklist_filter,
+            ),
+            (
+                &filters.weakness_whitelist_filter,
+                &filters.weakness_blacklist_filter,
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        .all(|(i, (whitelist, blacklist))| {
+            upper_values(whitelist.as_ref()).is_none_or(|wl| {
+                defenses
+                    .as_ref()
+                    .is_some_and(|x| has_any(&wl, x[i].iter().copied()))
+            }) && upper_values(blacklist.as_ref()).is_none_or(|bl| {
+                defenses
+                    .as_ref()
+                    .is_some_and(|x| !has_any(&bl, x[i].iter().copied()))
+            })
+        })
+    }
+
+    fn does_it_pass_environment_filters(&self, filters: &CreatureFieldFilters) -> bool {
+        let extra = self.extra_data.as_ref();
+        let speed_types = upper_values(filters.speed_type_filter.as_ref());
+        ((speed_types.is_none() && filters.min_speed_filter.is_none())
+            || extra.is_some_and(|extra| {
+                extra.speeds.iter().any(|(name, value)| {
+                    speed_types
+                        .as_ref()
+                        .is_none_or(|x| has_any(x, [name.as_str()]))
+                        && filters
+                            .min_speed_filter
+                            .is_none_or(|min| i64::from(*value) >= min)
+                })
+            }))
+            && upper_values(filters.sense_filter.as_ref()).is_none_or(|senses| {
+                extra.is_some_and(|extra| {
+                    has_any(&senses, extra.senses.iter().map(|x| x.name.as_str()))
+                })
+            })
+            && upper_values(filters.language_filter.as_ref()).is_none_or(|languages| {
+                extra.is_some_and(|extra| {
+                    has_any(&languages, extra.languages.iter().map(String::as_str))
+                })
+            })
+    }
+
+    /// Tradition, type of spellcaster and spell DC bounds must be satisfied by the same entry
+    fn does_it_pass_spellcasting_filters(&self, filters: &CreatureFieldFilters) -> bool {
+        let entries = self
+            .spellcaster_data
+            .as_ref()
+            .map(|x| &x.spellcaster_entries);
+        let traditions = upper_values(filters.spell_tradition_filter.as_ref());
+        let spellcaster_types = upper_values(filters.spellcaster_type_filter.as_ref());
+        let (min_dc, max_dc) = (filters.min_spell_dc_filter, filters.max_spell_dc_filter);
+        let is_entry_filtered = traditions.is_some()
+            || spellcaster_types.is_some()
+            || min_dc.is_some()
+            || max_dc.is_some();
+        (!is_entry_filtered
+            || entries.is_some_and(|entries| {
+                entries.iter().map(|x| &x.spellcaster_data).any(|entry| {
+                    traditions
+                        .as_ref()
+                        .is_none_or(|x| has_any(x, [entry.spellcasting_tradition.as_str()]))
+                        && spellcaster_types
+                            .as_ref()
+                            .is_none_or(|x| has_any(x, [entry.type_of_spellcaster.as_str()]))
+                        && min_dc.is_none_or(|min| entry.spellcasting_dc_mod >= min)
+                        && max_dc.is_none_or(|max| entry.spellcasting_dc_mod <= max)
+                })
+            }))
+            && upper_values(filters.spell_name_filter.as_ref()).is_none_or(|names| {
+                entries.is_some_and(|entries| {
+                    has_any(
+                        &names,
+                        entries
+                            .iter()
+                            .flat_map(|x| &x.spells)
+                            .map(|x| x.name.as_str()),
+                    )
+                })
+            })
+    }
+
+    /// Without a skill name the modifier bounds can be satisfied by any skill
+    fn does_it_pass_skill_filters(&self, filters: &CreatureFieldFilters) -> bool {
+        let (min, max) = (
+            filters.min_skill_modifier_filter,
+            filters.max_skill_modifier_filter,
+        );
+        (filters.skill_filter.is_none() && min.is_none() && max.is_none())
+            || self.extra_data.as_ref().is_some_and(|extra| {
+                extra.skills.iter().any(|skill| {
+                    filters
+                        .skill_filter
+                        .as_ref()
+                        .is_none_or(|name| skill.name.to_uppercase() == name.to_uppercase())
+                        && min.is_none_or(|min| skill.modifier >= min)
+                        && max.is_none_or(|max| skill.modifier <= max)
+                })
+            })
+    }
+}
+
+/// Values of a list filter in upper case, None if the filter does not constrain anything
+fn upper_values<T: ToString>(filter: Option<&Vec<T>>) -> Option<Vec<String>> {
+    filter
+        .filter(|x| !x.is_empty())
+        .map(|x| x.iter().map(|v| v.to_string().to_uppercase()).collect())
+}
+
+/// Case insensitive, like the `UPPER(column) = ANY(values)` statements of the query
+fn has_any<'a>(upper_values: &[String], names: impl IntoIterator<Item = &'a str>) -> bool {
+    names
+        .into_iter()
+        .any(|name| upper_values.contains(&name.to_uppercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    // Ogre of Monster Core, with its Ogre Hook Strike
+    fn ogre() -> Creature {
+        serde_json::from_value(json!({
+            "core_data": {
+                "essential": {
+                    "id": 1, "aon_id": null, "name": "Ogre", "hp": 50, "base_level": 3,
+                    "size": "Large", "family": "Ogre", "rarity": "Common", "license": "ORC",
+                    "remaster": true, "source": "Monster Core", "cr_type": "Creature",
+                    "alignment": "No Alignment", "focus_points": 0, "status": "Valid"
+                },
+                "derived": {
+                    "archive_link": null,
+                    "attack_data": {"melee": true, "ranged": false, "spellcaster": false},
+                    "role_data": {}
+                },
+                "traits": [{"name": "Giant", "description": null, "display_name": null}]
+            },
+            "variant_data": {"variant": "Base", "level": 3, "archive_link": null},
+            "extra_data": {
+                "actions": [],
+                "skills": [
+                    {"name": "Athletics", "description": null, "modifier": 11, "proficiency": 2},
+                    {"name": "Intimidation", "description": null, "modifier": 4, "proficiency": 2}
+                ],
+                "items": [],
+                "languages": ["Common", "Jotun"],
+                "senses": [{"id": 1, "name": "Darkvision", "range": null, "acuity": null}],
+                "speeds": {"Base": 25},
+                "ability_scores": {
+                    "charisma": -2, "constitution": 4, "dexterity": -1,
+                    "intelligence": -2, "strength": 5, "wisdom": 0
+                },
+                "hp_detail": null,
+                "ac_detail": null,
+                "language_detail": null,
+                "perception": 5,
+                "perception_detail": null,
+                "has_vision": true
+            },
+            "combat_data": {
+                "weapons": [{
+                    "item_core": {
+                        "id": 1, "name": "Ogre Hook", "bulk": 1.0, "quantity": 1,
+                        "base_item": "Ogre Hook", "category": "Martial", "description": "",
+                        "hardness": 0, "hp": 0, "level": 0, "price": 100,
+                        "usage": "held-in-two-hands", "group": "Flail", "item_type": "Weapon",
+                        "material_grade": null, "material_type": null, "number_of_uses": null,
+                        "license": "ORC", "remaster": true, "source": "Monster Core",
+                        "rarity": "Common", "size": "Large", "traits": [], "status": "Valid"
+                    },
+                    "weapon_data": {
+                        "id": 1, "to_hit_bonus": 12,
+                        "damage_data": [{
+                            "id": 1, "bonus_dmg": 7, "dmg_type": "Piercing",
+                            "dice": {"n_of_dices": 1, "dice_size": 10}
+                        }],
+                        "n_of_potency_runes": 0, "n_of_striking_runes": 0, "property_runes": [],
+                        "range": null, "reload": null, "weapon_type": "Melee",
+                        "splash_dmg": null, "attack_effects": []
+                    }
+                }],
+                "armors": [],
+                "shields": [],
+                "resistances": [],
+                "immunities": [],
+                "weaknesses": {},
+                "saving_throws": {
+                    "fortitude": 13, "reflex": 6, "will": 4,
+                    "fortitude_detail": null, "reflex_detail": null, "will_detail": null
+                },
+                "ac": 17,
+                "conditions": []
+            },
+            "spellcaster_data": null,
+            "game_system": "pf"
+        }))
+        .unwrap()
+    }
+
+    // Published elite and weak Ogres, ex the weak one is a level 2 creature with 35 HP
+    #[rstest]
+    #[case(CreatureVariant::Elite, [4, 65], [19, 15, 8, 6], 7, [14, 9], 13)]
+    #[case(CreatureVariant::Weak, [2, 35], [15, 11, 4, 2], 3, [10, 5], 9)]
+    fn variants_match_the_adjusted_stat_blocks(
+        #[case] variant: CreatureVariant,
+        #[case] level_and_hp: [i64; 2],
+        #[case] ac_and_saves: [i64; 4],
+        #[case] perception: i32,
+        #[case] ogre_hook_to_hit_and_dmg: [i64; 2],
+        #[case] athletics: i64,
+    ) {
+        let cr = ogre().convert_creature_to_variant(variant);
+        assert_eq!(
+            [cr.variant_data.level, cr.core_data.essential.hp],
+            level_and_hp
+        );
+        let combat = cr.combat_data.unwrap();
+        let st = &combat.saving_throws;
+        assert_eq!(
+            [i64::from(combat.ac), st.fortitude, st.reflex, st.will],
+            ac_and_saves
+        );
+        let ogre_hook = &combat.weapons[0].weapon_data;
+        assert_eq!(
+            [
+                ogre_hook.to_hit_bonus,
+                Some(ogre_hook.damage_data[0].bonus_dmg)
+            ],
+            ogre_hook_to_hit_and_dmg.map(Some)
+        );
+        let extra = cr.extra_data.unwrap();
+        assert_eq!(extra.perception, perception);
+        assert_eq!(extra.skills[0].modifier, athletics);
+    }
+
+    #[rstest]
+    #[case(CreatureFieldFilters { language_filter: Some(vec![String::from("jotun")]), ..Default::default() }, true)]
+    #[case(CreatureFieldFilters { sense_filter: Some(vec![String::from("tremorsense")]), ..Default::default() }, false)]
+    #[case(CreatureFieldFilters { min_speed_filter: Some(30), ..Default::default() }, false)]
+    #[case(CreatureFieldFilters { immunity_blacklist_filter: Some(vec![String::from("fire")]), ..Default::default() }, true)]
+    #[case(CreatureFieldFilters { weakness_whitelist_filter: Some(vec![String::from("fire")]), ..Default::default() }, false)]
+    #[case(CreatureFieldFilters { skill_filter: Some(String::from("athletics")), min_skill_modifier_filter: Some(11), ..Default::default() }, true)]
+    #[case(CreatureFieldFilters { skill_filter: Some(String::from("intimidation")), min_skill_modifier_filter: Some(11), ..Default::default() }, false)]
+    #[case(CreatureFieldFilters { spell_name_filter: Some(vec![String::from("fear")]), ..Default::default() }, false)]
+    fn stat_block_filters_match_the_query_ones(
+        #[case] filters: CreatureFieldFilters,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(ogre().is_passing_filters(&filters), expected);
+    }
+
+    #[rstest]
+    #[case(CreatureFieldFilters { min_ac_filter: Some(0), ..Default::default() })]
+    #[case(CreatureFieldFilters { max_will_filter: Some(50), ..Default::default() })]
+    #[case(CreatureFieldFilters { min_perception_filter: Some(0), ..Default::default() })]
+    fn bounds_reject_creatures_without_the_data_they_check(#[case] filters: CreatureFieldFilters) {
+        let creature = ogre();
+        assert!(creature.is_passing_filters(&filters));
+
+        let mut core_only = creature;
+        core_only.combat_data = None;
+        core_only.extra_data = None;
+        assert!(!core_only.is_passing_filters(&filters));
+        assert!(core_only.is_passing_filters(&CreatureFieldFilters::default()));
     }
 }
@@ -1,5 +1,6 @@
 use crate::models::db::pg_type_helper::get_i32_as_i64;
 use crate::models::shared::range_data::RangeData;
+use crate::models::shared::trait_data::TraitData;
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgRow;
 use sqlx::{Error, FromRow, Row};
@@ -33,6 +34,8 @@ pub struct Spell {
     pub slot: i64,
     pub creature_id: i64,
     pub spellcasting_entry_id: i64,
+    #[serde(default)]
+    pub traits: Vec<TraitData>,
 }
 
 impl<'r> FromRow<'r, PgRow> for Spell {
@@ -58,6 +61,7 @@ impl<'r> FromRow<'r, PgRow> for Spell {
             creature_id: row.try_get("creature_id")?,
             spellcasting_entry_id: row.try_get("spellcasting_entry_id")?,
             range: RangeData::from_row(row).ok(),
+            traits: vec![],
         })
     }
 }
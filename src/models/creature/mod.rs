@@ -1,3 +1,4 @@
+pub mod creature_comparison;
 pub mod creature_component;
 pub mod creature_field_filter;
 pub mod creature_filter_enum;
@@ -1,10 +1,14 @@
 use crate::models::creature::creature_metadata::creature_role::CreatureRoleEnum;
+use crate::models::creature::creature_metadata::spellcasting_enum::{
+    SpellTraditionEnum, SpellcasterTypeEnum,
+};
 use crate::models::creature::creature_metadata::type_enum::CreatureTypeEnum;
 use crate::models::hazard::hazard_field_filter::HazardComplexityEnum;
 use crate::models::shared::alignment_enum::AlignmentEnum;
 use crate::models::shared::pf_version_enum::GameSystemVersionEnum;
 use crate::models::shared::rarity_enum::RarityEnum;
 use crate::models::shared::size_enum::SizeEnum;
+use crate::models::shared::trait_filter_expression::TraitFilterExpression;
 use nanorand::Rng;
 use nanorand::WyRand;
 use serde::{Deserialize, Serialize};
@@ -41,7 +45,7 @@ pub struct EncounterParams {
     pub hazards_params: Option<HazardEncounterParams>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
 pub struct RandomEncounterData {
     #[schema(min_items = 1)]
     pub party_levels: Vec<i64>,
@@ -55,11 +59,20 @@ pub struct RandomEncounterData {
     pub adventure_group: Option<AdventureGroupEnum>,
 }
 
+impl RandomEncounterData {
+    pub fn is_valid(&self) -> bool {
+        !self.party_levels.is_empty()
+            && self.creature_percentage.is_none_or(|x| x <= 100)
+            && self.hazard_percentage.is_none_or(|x| x <= 100)
+    }
+}
+
 #[derive(Serialize, Deserialize, ToSchema, Clone, Default)]
 pub struct RandomCreatureData {
     pub source_filter: Option<Vec<String>>,
     pub trait_whitelist_filter: Option<Vec<String>>,
     pub trait_blacklist_filter: Option<Vec<String>>,
+    pub trait_expression_filter: Option<TraitFilterExpression>,
     pub family_filter: Option<Vec<String>>,
     pub rarity_filter: Option<Vec<RarityEnum>>,
     pub size_filter: Option<Vec<SizeEnum>>,
@@ -67,6 +80,24 @@ pub struct RandomCreatureData {
     pub type_filter: Option<Vec<CreatureTypeEnum>>,
     pub role_filter: Option<Vec<CreatureRoleEnum>>,
     pub attack_list: Option<HashMap<String, bool>>,
+    pub immunity_whitelist_filter: Option<Vec<String>>,
+    pub immunity_blacklist_filter: Option<Vec<String>>,
+    pub resistance_whitelist_filter: Option<Vec<String>>,
+    pub resistance_blacklist_filter: Option<Vec<String>>,
+    pub weakness_whitelist_filter: Option<Vec<String>>,
+    pub weakness_blacklist_filter: Option<Vec<String>>,
+    pub speed_type_filter: Option<Vec<String>>,
+    #[schema(minimum = 0, example = 30)]
+    pub min_speed_filter: Option<i64>,
+    pub sense_filter: Option<Vec<String>>,
+    pub language_filter: Option<Vec<String>>,
+    pub spell_tradition_filter: Option<Vec<SpellTraditionEnum>>,
+    pub spellcaster_type_filter: Option<Vec<SpellcasterTypeEnum>>,
+    pub spell_name_filter: Option<Vec<String>>,
+    #[schema(minimum = 0, example = 15)]
+    pub min_spell_dc_filter: Option<i64>,
+    #[schema(minimum = 0, example = 30)]
+    pub max_spell_dc_filter: Option<i64>,
     pub role_lower_threshold: Option<u8>,
     pub role_upper_threshold: Option<u8>,
     #[schema(minimum = 1, maximum = 30, example = 1)]
@@ -85,6 +116,7 @@ pub struct RandomHazardData {
     pub source_filter: Option<Vec<String>>,
     pub trait_whitelist_filter: Option<Vec<String>>,
     pub trait_blacklist_filter: Option<Vec<String>>,
+    pub trait_expression_filter: Option<TraitFilterExpression>,
     pub rarity_filter: Option<Vec<RarityEnum>>,
     pub size_filter: Option<Vec<SizeEnum>>,
 
@@ -0,0 +1,198 @@
+use crate::models::foundry::foundry_actor::{FoundryIdKind, foundry_name_id, slugify};
+use crate::models::foundry::loot_actor::shop_loot_actor;
+use crate::models::response_data::{EncounterContent, ShopListingResponse};
+use crate::models::shearable_data::HydratedEncounterContent;
+use crate::traits::foundry_exportable::FoundryExportable;
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Actors grouped in a folder, in the LevelDB-free format of the Foundry CLI:
+/// one JSON file per document, each one holding its database key.
+pub struct FoundryPack {
+    pub name: String,
+    pub folder: Value,
+    pub actors: Vec<Value>,
+}
+
+impl FoundryPack {
+    /// Actors are given with their quantity. Actors exported more than once, such as
+    /// the same creature listed twice, are kept once with their total quantity in the BYBE flags.
+    pub fn new(name: &str, actors: impl IntoIterator<Item = (Value, u64)>) -> Self {
+        let folder_id = foundry_name_id(FoundryIdKind::Folder, name);
+        let mut unique_actors: Vec<Value> = vec![];
+        for (mut actor, quantity) in actors {
+            if let Some(existing) = unique_actors.iter_mut().find(|x| x["_id"] == actor["_id"]) {
+                let total = existing["flags"]["bybe"]["quantity"]
+                    .as_u64()
+                    .unwrap_or(1)
+                    .saturating_add(quantity);
+                existing["flags"]["bybe"]["quantity"] = json!(total);
+                continue;
+            }
+            let actor_id = actor["_id"].as_str().unwrap_or_default().to_string();
+            actor["folder"] = json!(folder_id);
+            actor["_key"] = json!(format!("!actors!{actor_id}"));
+            actor["flags"]["bybe"]["quantity"] = json!(quantity);
+            for item in actor["items"].as_array_mut().into_iter().flatten() {
+                let item_id = item["_id"].as_str().unwrap_or_default().to_string();
+                item["_key"] = json!(format!("!actors.items!{actor_id}.{item_id}"));
+            }
+            unique_actors.push(actor);
+        }
+        Self {
+            name: name.to_string(),
+            folder: json!({
+                "_id": folder_id,
+                "_key": format!("!folders!{folder_id}"),
+                "name": name,
+                "type": "Actor",
+                "folder": null,
+                "sorting": "a",
+                "sort": 0,
+                "color": null,
+                "flags": {},
+            }),
+            actors: unique_actors,
+        }
+    }
+
+    pub fn from_encounter(name: &str, encounter: &EncounterContent) -> Self {
+        let creatures = encounter
+            .creatures
+            .iter()
+            .flatten()
+            .map(|x| (x.to_foundry_actor(), 1));
+        let hazards = encounter
+            .hazards
+            .iter()
+            .flatten()
+            .map(|x| (x.to_foundry_actor(), 1));
+        Self::new(name, creatures.chain(hazards))
+    }
+
+    pub fn from_shared_encounter(name: &str, encounter: &HydratedEncounterContent) -> Self {
+        let creatures = encounter
+            .creatures
+            .iter()
+            .map(|x| (x.entity.to_foundry_actor(), x.quantity));
+        let hazards = encounter
+            .hazards
+            .iter()
+            .map(|x| (x.entity.to_foundry_actor(), x.quantity));
+        Self::new(name, creatures.chain(hazards))
+    }
+
+    /// The shop is a single merchant actor, its folder is named after it
+    pub fn from_shop(name: &str, shop: &ShopListingResponse) -> Self {
+        Self::new(name, [(shop_loot_actor(name, shop), 1)])
+    }
+
+    /// Name of the directory holding the pack files, both in the zip and on disk
+    pub fn directory_name(&self) -> String {
+        file_stem(&self.name, "bybe-pack")
+    }
+
+    /// File name and content of every document, the folder first
+    pub fn files(&self) -> Result<Vec<(String, String)>> {
+        std::iter::once(&self.folder)
+            .chain(&self.actors)
+            .map(|document| {
+                let name = file_stem(document["name"].as_str().unwrap_or_default(), "document");
+                let id = document["_id"].as_str().unwrap_or_default();
+                Ok((
+                    format!("{name}_{id}.json"),
+                    serde_json::to_string_pretty(document)?,
+                ))
+            })
+            .collect()
+    }
+
+    pub fn to_zip(&self) -> Result<Vec<u8>> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let directory = self.directory_name();
+        for (file_name, content) in self.files()? {
+            zip.start_file(format!("{directory}/{file_name}"), options)?;
+            zip.write_all(content.as_bytes())?;
+        }
+        Ok(zip.finish()?.into_inner())
+    }
+
+    /// Writes the pack files in a new directory inside `parent`, and returns its path
+    pub fn write_to_directory(&self, parent: &Path) -> Result<PathBuf> {
+        let directory = parent.join(self.directory_name());
+        fs::create_dir_all(&directory)?;
+        for (file_name, content) in self.files()? {
+            fs::write(directory.join(file_name), content)?;
+        }
+        Ok(directory)
+    }
+}
+
+fn file_stem(name: &str, fallback: &str) -> String {
+    let stem = slugify(name);
+    if stem.is_empty() {
+        fallback.to_string()
+    } else {
+        stem
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::creature::creature_struct::Creature;
+    use crate::models::hazard::hazard_struct::Hazard;
+    use crate::models::response_data::{ResponseCreature, ResponseHazard};
+    use crate::models::shared::game_system_enum::GameSystem;
+    use std::io::Read;
+
+    fn goblin_ambush() -> FoundryPack {
+        let creature: Creature =
+            serde_json::from_str(include_str!("fixtures/creature.json")).unwrap();
+        let hazard: Hazard = serde_json::from_str(include_str!("fixtures/hazard.json")).unwrap();
+        let creature = ResponseCreature::from(creature);
+        FoundryPack::from_encounter(
+            "Goblin ambush",
+            &EncounterContent {
+                creatures: Some(vec![creature.clone(), creature]),
+                hazards: Some(vec![ResponseHazard::from((hazard, GameSystem::Pathfinder))]),
+            },
+        )
+    }
+
+    #[test]
+    fn repeated_actors_are_exported_once_in_the_folder() {
+        let pack = goblin_ambush();
+        assert_eq!(pack.actors.len(), 2);
+        assert_eq!(pack.actors[0]["flags"]["bybe"]["quantity"], 2);
+        assert_eq!(pack.actors[1]["flags"]["bybe"]["quantity"], 1);
+        for actor in &pack.actors {
+            assert_eq!(actor["folder"], pack.folder["_id"]);
+            let key = format!("!actors!{}", actor["_id"].as_str().unwrap());
+            assert_eq!(actor["_key"], key);
+        }
+    }
+
+    #[test]
+    fn zip_holds_one_file_per_document() {
+        let pack = goblin_ambush();
+        let mut archive = zip::ZipArchive::new(Cursor::new(pack.to_zip().unwrap())).unwrap();
+        assert_eq!(archive.len(), 3);
+        let mut folder = String::new();
+        archive
+            .by_name(&format!(
+                "goblin-ambush/goblin-ambush_{}.json",
+                pack.folder["_id"].as_str().unwrap()
+            ))
+            .unwrap()
+            .read_to_string(&mut folder)
+            .unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&folder).unwrap(), pack.folder);
+    }
+}
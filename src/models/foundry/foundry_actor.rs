@@ -0,0 +1,141 @@
+use crate::models::routers_validator_structs::Dice;
+use crate::models::shared::action::Action;
+use crate::models::shared::dice_expression::DiceExpression;
+use crate::models::shared::rarity_enum::RarityEnum;
+use crate::models::shared::size_enum::SizeEnum;
+use crate::models::shared::trait_data::TraitData;
+use serde_json::{Value, json};
+
+/// Version of the PF2e system data model the actors are written for
+pub const FOUNDRY_PF2E_SCHEMA_VERSION: f64 = 0.935;
+
+/// Kind of document a Foundry id is generated for, so that ids never collide between kinds
+#[derive(Clone, Copy)]
+pub enum FoundryIdKind {
+    Actor = b'A' as isize,
+    Action = b'C' as isize,
+    EliteActor = b'X' as isize,
+    Folder = b'F' as isize,
+    Hazard = b'H' as isize,
+    Item = b'I' as isize,
+    Loot = b'O' as isize,
+    Lore = b'L' as isize,
+    Spell = b'P' as isize,
+    SpellcastingEntry = b'E' as isize,
+    Strike = b'S' as isize,
+    WeakActor = b'W' as isize,
+}
+
+/// Foundry ids are 16 alphanumeric characters. They are derived from the BYBE id,
+/// so that exporting the same entity twice updates it instead of duplicating it.
+pub fn foundry_id(kind: FoundryIdKind, id: i64) -> String {
+    format!(
+        "bybe{}{:011}",
+        char::from(kind as u8),
+        id.unsigned_abs() % 100_000_000_000
+    )
+}
+
+/// Id of documents that have no BYBE id, such as folders and shops, derived from their name.
+/// FNV-1a is used because, unlike the std hasher, its output never changes between releases.
+pub fn foundry_name_id(kind: FoundryIdKind, name: &str) -> String {
+    let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    foundry_id(kind, hash.cast_signed())
+}
+
+/// Foundry identifies traits, damage types, senses and the like by slug, ex `cold-iron`
+pub fn slugify(value: &str) -> String {
+    value
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+pub fn trait_slugs(traits: &[TraitData]) -> Vec<String> {
+    traits.iter().map(|x| slugify(&x.name)).collect()
+}
+
+pub const fn size_slug(size: &SizeEnum) -> &'static str {
+    match size {
+        SizeEnum::Tiny => "tiny",
+        SizeEnum::Small => "sm",
+        SizeEnum::Medium => "med",
+        SizeEnum::Large => "lg",
+        SizeEnum::Huge => "huge",
+        SizeEnum::Gargantuan => "grg",
+    }
+}
+
+pub fn rarity_slug(rarity: &RarityEnum) -> String {
+    rarity.to_string().to_lowercase()
+}
+
+pub fn publication(source: &str, license: &str, remaster: bool) -> Value {
+    json!({
+        "title": source,
+        "license": license,
+        "remaster": remaster,
+    })
+}
+
+/// Leading number of values such as `30 feet`
+pub fn parse_feet(value: &str) -> Option<i64> {
+    value.split_whitespace().next().and_then(|x| x.parse().ok())
+}
+
+/// Roll formula of dice plus a flat bonus, ex `2d6+4`
+pub fn damage_formula(dice: Option<&Dice>, bonus: i64) -> String {
+    DiceExpression::from_dice_and_bonus(dice, bonus).to_string()
+}
+
+pub fn action_item(action: &Action) -> Value {
+    let core = &action.core_action;
+    json!({
+        "_id": foundry_id(FoundryIdKind::Action, core.id),
+        "name": core.name,
+        "type": "action",
+        "img": "systems/pf2e/icons/actions/Empty.webp",
+        "system": {
+            "actionType": {"value": slugify(&core.action_type)},
+            "actions": {"value": core.n_of_actions},
+            "category": core.category.as_deref().map(slugify),
+            "description": {"value": core.description},
+            "slug": core.slug,
+            "traits": {
+                "rarity": rarity_slug(&core.rarity),
+                "value": trait_slugs(&action.traits),
+            },
+            "publication": publication(&core.source, &core.license, core.remaster),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("Cold Iron", "cold-iron")]
+    #[case("Lore: Sailing", "lore-sailing")]
+    #[case("  fire  ", "fire")]
+    fn slugify_values(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(expected, slugify(input));
+    }
+
+    #[rstest]
+    fn ids_are_sixteen_alphanumeric_characters() {
+        let id = foundry_id(FoundryIdKind::Strike, 42);
+        assert_eq!(id, "bybeS00000000042");
+        assert_ne!(id, foundry_id(FoundryIdKind::Action, 42));
+        assert_eq!(
+            foundry_name_id(FoundryIdKind::Folder, "Goblin ambush"),
+            foundry_name_id(FoundryIdKind::Folder, "Goblin ambush")
+        );
+        assert_eq!(foundry_name_id(FoundryIdKind::Loot, "").len(), 16);
+    }
+}
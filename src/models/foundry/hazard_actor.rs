@@ -0,0 +1,94 @@
+use crate::models::foundry::foundry_actor::{
+    FOUNDRY_PF2E_SCHEMA_VERSION, FoundryIdKind, action_item, foundry_id, publication, rarity_slug,
+    size_slug, trait_slugs,
+};
+use crate::models::hazard::hazard_field_filter::HazardComplexityEnum;
+use crate::models::hazard::hazard_struct::Hazard;
+use crate::models::response_data::ResponseHazard;
+use crate::traits::foundry_exportable::FoundryExportable;
+use serde_json::{Value, json};
+
+impl FoundryExportable for Hazard {
+    fn to_foundry_actor(&self) -> Value {
+        let essential = &self.essential;
+        json!({
+            "_id": foundry_id(FoundryIdKind::Hazard, essential.id),
+            "name": essential.name,
+            "type": "hazard",
+            "img": "systems/pf2e/icons/default-icons/hazard.svg",
+            "system": {
+                "_migration": {"version": FOUNDRY_PF2E_SCHEMA_VERSION},
+                "attributes": {
+                    "ac": {"value": essential.ac},
+                    "hardness": essential.hardness,
+                    "hasHealth": essential.has_health,
+                    "hp": {
+                        "value": essential.hp,
+                        "max": essential.hp,
+                        "temp": 0,
+                        "details": "",
+                    },
+                    "stealth": {
+                        "value": essential.stealth,
+                        "details": essential.stealth_detail,
+                    },
+                    "immunities": [],
+                    "resistances": [],
+                    "weaknesses": [],
+                },
+                "details": {
+                    "level": {"value": essential.level},
+                    "isComplex": essential.complexity == HazardComplexityEnum::Complex,
+                    "description": essential.description,
+                    "disable": essential.disable_description,
+                    "reset": essential.reset_description,
+                    "routine": essential.routine_description,
+                    "publication": publication(&essential.source, &essential.license, essential.remaster),
+                },
+                "saves": {
+                    "fortitude": {"value": essential.fortitude},
+                    "reflex": {"value": essential.reflex},
+                    "will": {"value": essential.will},
+                },
+                "traits": {
+                    "rarity": rarity_slug(&essential.rarity),
+                    "size": {"value": size_slug(&essential.size)},
+                    "value": trait_slugs(&self.traits),
+                },
+            },
+            "items": self.actions.iter().map(action_item).collect::<Vec<_>>(),
+            "flags": {
+                "bybe": {
+                    "id": essential.id,
+                    "game": self.game_system.to_string(),
+                },
+            },
+        })
+    }
+}
+
+impl FoundryExportable for ResponseHazard {
+    fn to_foundry_actor(&self) -> Value {
+        self.core_hazard.to_foundry_actor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actor_matches_the_pinned_schema() {
+        let schema =
+            serde_json::from_str(include_str!("fixtures/pf2e_hazard_actor.schema.json")).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let hazard: Hazard = serde_json::from_str(include_str!("fixtures/hazard.json")).unwrap();
+        let actor = hazard.to_foundry_actor();
+        let errors: Vec<_> = validator
+            .iter_errors(&actor)
+            .map(|x| x.to_string())
+            .collect();
+        assert!(errors.is_empty(), "{errors:#?}");
+        assert_eq!(actor["system"]["details"]["isComplex"], false);
+    }
+}
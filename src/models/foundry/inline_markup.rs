@@ -0,0 +1,973 @@
+use crate::models::shared::dice_expression::DiceExpression;
+use crate::models::stat_block::stat_block_struct::escape_html;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Output of descriptions, which are stored as Foundry HTML with inline macros
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum DescriptionFormat {
+    Text,
+    Markdown,
+    Html,
+    /// The spans of the description, as a JSON array
+    Ast,
+}
+
+#[derive(Serialize, Deserialize, IntoParams, Default, Clone, Copy, Eq, PartialEq, Debug)]
+pub struct DescriptionFormatQuery {
+    /// Descriptions are returned as stored when it is not given
+    pub description_format: Option<DescriptionFormat>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Default, Eq, PartialEq, Hash, Debug)]
+pub struct TextSpan {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bold: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub italic: bool,
+}
+
+/// `@UUID[Compendium.pf2e.conditionitems.Item.Frightened]{Frightened 1}`
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct LinkMacro {
+    pub uuid: String,
+    pub label: String,
+}
+
+/// `@Damage[(2d6+4)[fire],1d6[persistent,bleed]]`
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct DamageMacro {
+    pub parts: Vec<DamagePart>,
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct DamagePart {
+    pub formula: String,
+    pub damage_type: Option<String>,
+    /// Persistent, splash or precision
+    pub category: Option<String>,
+}
+
+/// `@Check[fortitude|dc:20|basic]`
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct CheckMacro {
+    pub statistic: String,
+    /// Missing when it is computed by Foundry, ex `dc:resolve(@actor.attributes.classDC.value)`
+    pub dc: Option<i64>,
+    pub basic: bool,
+    pub label: Option<String>,
+}
+
+/// `[[/r 1d4 #Healing]]`
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct RollMacro {
+    pub formula: String,
+    pub flavor: Option<String>,
+    pub label: Option<String>,
+}
+
+/// `@Template[emanation|distance:10]`
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct TemplateMacro {
+    pub shape: String,
+    pub distance: Option<i64>,
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+#[serde(tag = "type")]
+pub enum MarkupSpan {
+    Text(TextSpan),
+    Link(LinkMacro),
+    Damage(DamageMacro),
+    Check(CheckMacro),
+    Roll(RollMacro),
+    Template(TemplateMacro),
+    LineBreak,
+    ParagraphBreak,
+    ListItem,
+    Rule,
+}
+
+/// Checks and damage found in descriptions, so that they can be used without parsing text
+#[derive(Serialize, Deserialize, ToSchema, Clone, Default, Eq, PartialEq, Hash, Debug)]
+pub struct DescriptionRolls {
+    pub checks: Vec<CheckMacro>,
+    pub damage: Vec<DamageMacro>,
+}
+
+impl DescriptionRolls {
+    pub fn from_descriptions<'a>(descriptions: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut rolls = Self::default();
+        for description in descriptions {
+            for span in ParsedDescription::parse(description).spans {
+                match span {
+                    MarkupSpan::Check(x) => rolls.checks.push(x),
+                    MarkupSpan::Damage(x) => rolls.damage.push(x),
+                    _ => {}
+                }
+            }
+        }
+        rolls
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Default, Eq, PartialEq, Hash, Debug)]
+pub struct ParsedDescription {
+    pub spans: Vec<MarkupSpan>,
+}
+
+impl ParsedDescription {
+    pub fn parse(input: &str) -> Self {
+        let mut parser = Parser {
+            input,
+            pos: 0,
+            spans: vec![],
+            bold: 0,
+            italic: 0,
+        };
+        parser.parse();
+        Self {
+            spans: parser.spans,
+        }
+    }
+
+    pub fn render(&self, format: DescriptionFormat) -> String {
+        if format == DescriptionFormat::Ast {
+            return serde_json::to_string(&self.spans).unwrap_or_default();
+        }
+        let mut blocks: Vec<Option<String>> = vec![];
+        let mut current = String::new();
+        let flush = |blocks: &mut Vec<Option<String>>, current: &mut String| {
+            let block = current.trim();
+            if !block.is_empty() {
+                blocks.push(Some(block.to_string()));
+            }
+            current.clear();
+        };
+        for span in &self.spans {
+            match span {
+                MarkupSpan::Text(x) => current.push_str(&render_text(x, format)),
+                MarkupSpan::LineBreak => current.push_str(match format {
+                    DescriptionFormat::Markdown => "  \n",
+                    DescriptionFormat::Html => "<br>",
+                    DescriptionFormat::Text | DescriptionFormat::Ast => "\n",
+                }),
+                MarkupSpan::ParagraphBreak => flush(&mut blocks, &mut current),
+                MarkupSpan::ListItem => {
+                    flush(&mut blocks, &mut current);
+                    current.push_str(match format {
+                        DescriptionFormat::Markdown => "- ",
+                        _ => "• ",
+                    });
+                }
+                MarkupSpan::Rule => {
+                    flush(&mut blocks, &mut current);
+                    // Rules are dropped from plain text, paragraphs already separate its content
+                    if format != DescriptionFormat::Text {
+                        blocks.push(None);
+                    }
+                }
+                x => current.push_str(&render_macro(x, format)),
+            }
+        }
+        flush(&mut blocks, &mut current);
+        match format {
+            DescriptionFormat::Html => blocks
+                .into_iter()
+                .map(|x| x.map_or_else(|| String::from("<hr>"), |x| format!("<p>{x}</p>")))
+                .join(""),
+            _ => blocks
+                .into_iter()
+                .map(|x| x.unwrap_or_else(|| String::from("---")))
+                .join("\n\n"),
+        }
+    }
+}
+
+/// Renders a stored description in the given format
+pub fn render_description(description: &str, format: DescriptionFormat) -> String {
+    ParsedDescription::parse(description).render(format)
+}
+
+/// Shifts the numeric DCs of `@Check` and the main damage of `@Damage` macros,
+/// as done by the elite and weak adjustments.
+///
+/// The rest of the description is kept as stored,
+/// labels included, only the values that changed are replaced in them.
+/// Persistent, splash and precision damage, and formulas computed by Foundry, are left as is.
+pub fn adjust_description_rolls(
+    description: &str,
+    dc_modifier: i64,
+    damage_modifier: i64,
+) -> String {
+    if dc_modifier == 0 && damage_modifier == 0 {
+        return description.to_string();
+    }
+    let mut adjusted = String::with_capacity(description.len());
+    let mut pos = 0;
+    while let Some(offset) = description[pos..].find('@') {
+        let start = pos + offset;
+        adjusted.push_str(&description[pos..start]);
+        let rest = &description[start + 1..];
+        let name_len = if rest.starts_with("Check[") {
+            "Check".len()
+        } else if rest.starts_with("Damage[") {
+            "Damage".len()
+        } else {
+            0
+        };
+        let open = start + 1 + name_len;
+        let close = (name_len > 0)
+            .then(|| matching(description, open, b'[', b']'))
+            .flatten();
+        let Some(close) = close else {
+            adjusted.push('@');
+            pos = start + 1;
+            continue;
+        };
+        let content = &description[open + 1..close];
+        let (content, changes) = if name_len == "Check".len() {
+            adjust_check(content, dc_modifier)
+        } else {
+            adjust_damage(content, damage_modifier)
+        };
+        adjusted.push_str(&description[start..=open]);
+        adjusted.push_str(&content);
+        adjusted.push(']');
+        pos = close + 1;
+        if description[pos..].starts_with('{')
+            && let Some(label_close) = matching(description, pos, b'{', b'}')
+        {
+            let label = changes.iter().fold(
+                description[pos..=label_close].to_string(),
+                |label, (old, new)| replace_value(&label, old, new),
+            );
+            adjusted.push_str(&label);
+            pos = label_close + 1;
+        }
+    }
+    adjusted.push_str(&description[pos..]);
+    adjusted
+}
+
+/// Adjusted content, along with the values that changed
+fn adjust_check(content: &str, modifier: i64) -> (String, Vec<(String, String)>) {
+    let mut changes = vec![];
+    let content = content
+        .split('|')
+        .map(|param| match param.trim().split_once(':') {
+            Some(("dc", x)) if modifier != 0 => x.trim().parse::<i64>().map_or_else(
+                |_| param.to_string(),
+                |dc| {
+                    changes.push((dc.to_string(), (dc + modifier).to_string()));
+                    format!("dc:{}", dc + modifier)
+                },
+            ),
+            _ => param.to_string(),
+        })
+        .join("|");
+    (content, changes)
+}
+
+/// Only the first part that is not persistent, splash or precision damage is adjusted
+fn adjust_damage(content: &str, modifier: i64) -> (String, Vec<(String, String)>) {
+    let rolls = split_top_level(content, '|')[0];
+    let mut changes = vec![];
+    let parts = split_top_level(rolls, ',')
+        .into_iter()
+        .map(|part| {
+            let (formula, tags) = split_damage_tags(part.trim());
+            let is_main_damage = damage_category(&damage_tags(tags.unwrap_or_default())).is_none();
+            let formula = strip_wrapping(formula.trim());
+            let expression = formula
+                .parse::<DiceExpression>()
+                .ok()
+                .filter(|_| modifier != 0 && changes.is_empty() && is_main_damage);
+            let Some(expression) = expression else {
+                return part.to_string();
+            };
+            let adjusted = expression.with_bonus(modifier);
+            changes.push((formula.to_string(), adjusted.to_string()));
+            match tags {
+                Some(tags) if adjusted.terms().len() > 1 => format!("({adjusted})[{tags}]"),
+                Some(tags) => format!("{adjusted}[{tags}]"),
+                None => adjusted.to_string(),
+            }
+        })
+        .join(",");
+    (format!("{parts}{}", &content[rolls.len()..]), changes)
+}
+
+/// Replaces `old` where it is not part of a longer word or number
+fn replace_value(text: &str, old: &str, new: &str) -> String {
+    let is_word = |c: Option<char>| c.is_some_and(|x| x.is_ascii_alphanumeric());
+    let mut replaced = String::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(offset) = text[pos..].find(old) {
+        let start = pos + offset;
+        let end = start + old.len();
+        replaced.push_str(&text[pos..start]);
+        if is_word(text[..start].chars().next_back()) || is_word(text[end..].chars().next()) {
+            replaced.push_str(old);
+        } else {
+            replaced.push_str(new);
+        }
+        pos = end;
+    }
+    replaced.push_str(&text[pos..]);
+    replaced
+}
+
+fn render_text(span: &TextSpan, format: DescriptionFormat) -> String {
+    match format {
+        DescriptionFormat::Markdown => {
+            let escaped = escape_markdown(&span.text);
+            // Markers must touch the text, so the spaces around it are kept outside of them
+            let text = escaped.trim();
+            if text.is_empty() || !(span.bold || span.italic) {
+                return escaped;
+            }
+            let marker = match (span.bold, span.italic) {
+                (true, true) => "***",
+                (true, false) => "**",
+                _ => "*",
+            };
+            let leading = &escaped[..escaped.len() - escaped.trim_start().len()];
+            let trailing = &escaped[escaped.trim_end().len()..];
+            format!("{leading}{marker}{text}{marker}{trailing}")
+        }
+        DescriptionFormat::Html => {
+            let mut text = escape_html(&span.text);
+            if span.italic {
+                text = format!("<em>{text}</em>");
+            }
+            if span.bold {
+                text = format!("<strong>{text}</strong>");
+            }
+            text
+        }
+        _ => span.text.clone(),
+    }
+}
+
+/// Escapes the characters that would otherwise start an emphasis or a code span
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn render_macro(span: &MarkupSpan, format: DescriptionFormat) -> String {
+    let label = macro_label(span);
+    if format != DescriptionFormat::Html {
+        return label;
+    }
+    let label = escape_html(&label);
+    match span {
+        MarkupSpan::Link(x) => format!(
+            "<span class=\"link\" data-uuid=\"{}\">{label}</span>",
+            escape_html(&x.uuid)
+        ),
+        MarkupSpan::Damage(x) => format!(
+            "<span class=\"damage\" data-formula=\"{}\">{label}</span>",
+            escape_html(&x.parts.iter().map(|p| &p.formula).join(" + "))
+        ),
+        MarkupSpan::Check(x) => format!(
+            "<span class=\"check\" data-statistic=\"{}\"{}>{label}</span>",
+            escape_html(&x.statistic),
+            x.dc.map(|dc| format!(" data-dc=\"{dc}\""))
+                .unwrap_or_default()
+        ),
+        MarkupSpan::Roll(x) => format!(
+            "<span class=\"roll\" data-formula=\"{}\">{label}</span>",
+            escape_html(&x.formula)
+        ),
+        MarkupSpan::Template(x) => format!(
+            "<span class=\"template\" data-shape=\"{}\">{label}</span>",
+            escape_html(&x.shape)
+        ),
+        _ => label,
+    }
+}
+
+/// Text of a macro as it is displayed by Foundry, ex `DC 20 basic Fortitude save`
+fn macro_label(span: &MarkupSpan) -> String {
+    match span {
+        MarkupSpan::Link(x) => x.label.clone(),
+        MarkupSpan::Damage(x) => x.label.clone().unwrap_or_else(|| {
+            x.parts
+                .iter()
+                .map(|part| {
+                    [
+                        Some(part.formula.as_str()),
+                        part.category.as_deref(),
+                        part.damage_type.as_deref(),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .join(" ")
+                })
+                .join(" plus ")
+        }),
+        MarkupSpan::Check(x) => x.label.clone().unwrap_or_else(|| {
+            let is_save = ["fortitude", "reflex", "will"].contains(&x.statistic.as_str());
+            let statistic = capitalize(&x.statistic);
+            let check = match (is_save, x.basic) {
+                (true, true) => format!("basic {statistic} save"),
+                (true, false) => format!("{statistic} save"),
+                _ if x.statistic == "flat" => String::from("flat check"),
+                _ => format!("{statistic} check"),
+            };
+            match x.dc {
+                Some(dc) => format!("DC {dc} {check}"),
+                None => check,
+            }
+        }),
+        MarkupSpan::Roll(x) => x.label.clone().unwrap_or_else(|| x.formula.clone()),
+        MarkupSpan::Template(x) => x.label.clone().unwrap_or_else(|| {
+            x.distance.map_or_else(
+                || x.shape.clone(),
+                |distance| format!("{distance}-foot {}", x.shape),
+            )
+        }),
+        _ => String::new(),
+    }
+}
+
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    spans: Vec<MarkupSpan>,
+    bold: u32,
+    italic: u32,
+}
+
+impl Parser<'_> {
+    fn parse(&mut self) {
+        while let Some(c) = self.input[self.pos..].chars().next() {
+            let parsed = match c {
+                '[' => self.inline_roll(),
+                '@' => self.inline_macro(),
+                '<' => self.tag(),
+                '&' => self.entity(),
+                _ => false,
+            };
+            if !parsed {
+                self.push_text(&self.input[self.pos..self.pos + c.len_utf8()]);
+                self.pos += c.len_utf8();
+            }
+        }
+        while matches!(
+            self.spans.last(),
+            Some(MarkupSpan::ParagraphBreak | MarkupSpan::LineBreak)
+        ) {
+            self.spans.pop();
+        }
+    }
+
+    /// HTML whitespace is collapsed, and dropped at the start of a paragraph
+    fn push_text(&mut self, text: &str) {
+        let collapsed = collapse_whitespace(text);
+        let mut text = collapsed.as_str();
+        if matches!(
+            self.spans.last(),
+            None | Some(
+                MarkupSpan::ParagraphBreak
+                    | MarkupSpan::LineBreak
+                    | MarkupSpan::ListItem
+                    | MarkupSpan::Rule
+            )
+        ) {
+            text = text.trim_start();
+        }
+        let (bold, italic) = (self.bold > 0, self.italic > 0);
+        if let Some(MarkupSpan::Text(last)) = self.spans.last_mut() {
+            if last.text.ends_with(' ') {
+                text = text.trim_start();
+            }
+            if last.bold == bold && last.italic == italic {
+                last.text.push_str(text);
+                return;
+            }
+        }
+        if !text.is_empty() {
+            self.spans.push(MarkupSpan::Text(TextSpan {
+                text: text.to_string(),
+                bold,
+                italic,
+            }));
+        }
+    }
+
+    fn push_break(&mut self, span: MarkupSpan) {
+        if let Some(MarkupSpan::Text(last)) = self.spans.last_mut() {
+            let trimmed = last.text.trim_end().len();
+            last.text.truncate(trimmed);
+        }
+        match (self.spans.last(), &span) {
+            (None, MarkupSpan::ParagraphBreak | MarkupSpan::LineBreak)
+            | (Some(MarkupSpan::ParagraphBreak), MarkupSpan::ParagraphBreak) => {}
+            _ => self.spans.push(span),
+        }
+    }
+
+    /// `[[/r 1d4 #Healing]]{Label}`
+    fn inline_roll(&mut self) -> bool {
+        if !self.input[self.pos..].starts_with("[[") {
+            return false;
+        }
+        let Some(end) = matching(self.input, self.pos, b'[', b']') else {
+            return false;
+        };
+        if self.input.as_bytes()[end - 1] != b']' {
+            return false;
+        }
+        let content = self.input[self.pos + 2..end - 1].trim();
+        self.pos = end + 1;
+        let label = self.label();
+        let content = if content.starts_with('/') {
+            content
+                .split_once(char::is_whitespace)
+                .map_or("", |(_, x)| x)
+        } else {
+            content
+        };
+        let (formula, flavor) = content
+            .split_once('#')
+            .map_or((content, None), |(formula, flavor)| {
+                (formula, Some(flavor.trim().to_string()))
+            });
+        self.spans.push(MarkupSpan::Roll(RollMacro {
+            formula: formula.trim().to_string(),
+            flavor,
+            label,
+        }));
+        true
+    }
+
+    /// `@Name[content]{Label}`
+    fn inline_macro(&mut self) -> bool {
+        let rest = &self.input[self.pos + 1..];
+        let name_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        if name_len == 0 || !rest[name_len..].starts_with('[') {
+            return false;
+        }
+        let name = &rest[..name_len];
+        let open = self.pos + 1 + name_len;
+        let Some(close) = matching(self.input, open, b'[', b']') else {
+            return false;
+        };
+        let content = &self.input[open + 1..close];
+        self.pos = close + 1;
+        let label = self.label();
+        let span = match name {
+            "UUID" | "Compendium" | "Actor" | "Item" | "JournalEntry" | "Scene" => {
+                MarkupSpan::Link(LinkMacro {
+                    uuid: content.to_string(),
+                    label: label.unwrap_or_else(|| {
+                        content.rsplit('.').next().unwrap_or(content).to_string()
+                    }),
+                })
+            }
+            "Damage" => MarkupSpan::Damage(parse_damage(content, label)),
+            "Check" => MarkupSpan::Check(parse_check(content, label)),
+            "Template" => MarkupSpan::Template(parse_template(content, label)),
+            // Localization keys are resolved by Foundry, there is nothing to print
+            "Localize" => return true,
+            _ => {
+                self.push_text(label.as_deref().unwrap_or(content));
+                return true;
+            }
+        };
+        self.spans.push(span);
+        true
+    }
+
+    /// Label following a macro, ex `{Frightened 1}`
+    fn label(&mut self) -> Option<String> {
+        if !self.input[self.pos..].starts_with('{') {
+            return None;
+        }
+        let close = matching(self.input, self.pos, b'{', b'}')?;
+        let label = self.input[self.pos + 1..close].to_string();
+        self.pos = close + 1;
+        Some(label)
+    }
+
+    fn tag(&mut self) -> bool {
+        let rest = &self.input[self.pos..];
+        if rest.starts_with("<!--") {
+            self.pos += rest.find("-->").map_or(rest.len(), |x| x + 3);
+            return true;
+        }
+        let is_tag = rest[1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/');
+        let Some(end) = rest.find('>').filter(|_| is_tag) else {
+            return false;
+        };
+        let tag = &rest[1..end];
+        self.pos += end + 1;
+        let is_closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match name.as_str() {
+            "p" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "ul" | "ol" | "blockquote"
+            | "table" | "tr" | "section" => self.push_break(MarkupSpan::ParagraphBreak),
+            "br" => self.push_break(MarkupSpan::LineBreak),
+            "hr" => self.push_break(MarkupSpan::Rule),
+            "li" if !is_closing => self.push_break(MarkupSpan::ListItem),
+            "td" | "th" if is_closing => self.push_text(" "),
+            "strong" | "b" => self.bold = toggle(self.bold, is_closing),
+            "em" | "i" => self.italic = toggle(self.italic, is_closing),
+            _ => {}
+        }
+        true
+    }
+
+    fn entity(&mut self) -> bool {
+        let rest = &self.input[self.pos..];
+        let Some(end) = rest.find(';').filter(|x| *x <= 10) else {
+            return false;
+        };
+        let decoded = match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            "ndash" => Some('–'),
+            "mdash" => Some('—'),
+            x if x.starts_with("#x") || x.starts_with("#X") => u32::from_str_radix(&x[2..], 16)
+                .ok()
+                .and_then(char::from_u32),
+            x if x.starts_with('#') => x[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        let Some(decoded) = decoded else {
+            return false;
+        };
+        self.pos += end + 1;
+        self.push_text(decoded.encode_utf8(&mut [0; 4]));
+        true
+    }
+}
+
+const fn toggle(depth: u32, is_closing: bool) -> u32 {
+    if is_closing {
+        depth.saturating_sub(1)
+    } else {
+        depth + 1
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    for c in text.chars() {
+        if !c.is_whitespace() {
+            collapsed.push(c);
+        } else if !collapsed.ends_with(' ') {
+            collapsed.push(' ');
+        }
+    }
+    collapsed
+}
+
+/// Index of the bracket closing the one at `open`, nested brackets included.
+/// None when a bracket is closed before being opened.
+fn matching(input: &str, open: usize, open_bracket: u8, close_bracket: u8) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, byte) in input.bytes().enumerate().skip(open) {
+        if byte == open_bracket {
+            depth += 1;
+        } else if byte == close_bracket {
+            depth = depth.checked_sub(1)?;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// Splits on `separator`, ignoring the ones inside brackets
+fn split_top_level(input: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            x if x == separator && depth == 0 => {
+                parts.push(&input[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+fn parse_damage(content: &str, label: Option<String>) -> DamageMacro {
+    let rolls = split_top_level(content, '|')[0];
+    let parts = split_top_level(rolls, ',')
+        .into_iter()
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .map(|part| {
+            let (formula, tags) = split_damage_tags(part);
+            let tags = damage_tags(tags.unwrap_or_default());
+            let category = damage_category(&tags);
+            DamagePart {
+                formula: strip_wrapping(formula.trim()).to_string(),
+                damage_type: tags.iter().find(|x| Some(*x) != category.as_ref()).cloned(),
+                category,
+            }
+        })
+        .collect();
+    DamageMacro { parts, label }
+}
+
+/// `(2d6+4)[fire]` is split in `(2d6+4)` and `fire`
+fn split_damage_tags(part: &str) -> (&str, Option<&str>) {
+    part.strip_suffix(']')
+        .and_then(|x| {
+            let open = x
+                .char_indices()
+                .rev()
+                .scan(0i32, |depth, (i, c)| {
+                    match c {
+                        ']' => *depth += 1,
+                        '[' if *depth == 0 => return Some(Some(i)),
+                        '[' => *depth -= 1,
+                        _ => {}
+                    }
+                    Some(None)
+                })
+                .flatten()
+                .next()?;
+            Some((&x[..open], Some(&x[open + 1..])))
+        })
+        .unwrap_or((part, None))
+}
+
+fn damage_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(|x| x.trim().to_lowercase())
+        .filter(|x| !x.is_empty())
+        .collect()
+}
+
+fn damage_category(tags: &[String]) -> Option<String> {
+    tags.iter()
+        .find(|x| ["persistent", "splash", "precision"].contains(&x.as_str()))
+        .cloned()
+}
+
+/// `(2d6+4)` and `{2d6}` are printed without their brackets
+fn strip_wrapping(formula: &str) -> &str {
+    for (open, close) in [(b'(', b')'), (b'{', b'}')] {
+        if formula.as_bytes().first() == Some(&open)
+            && matching(formula, 0, open, close) == Some(formula.len() - 1)
+        {
+            return &formula[1..formula.len() - 1];
+        }
+    }
+    formula
+}
+
+/// Both `fortitude|dc:20|basic` and the older `type:fortitude|dc:20|basic:true` syntaxes
+fn parse_check(content: &str, label: Option<String>) -> CheckMacro {
+    let mut check = CheckMacro {
+        statistic: String::new(),
+        dc: None,
+        basic: false,
+        label,
+    };
+    for (i, param) in content.split('|').map(str::trim).enumerate() {
+        match param.split_once(':') {
+            Some(("type", x)) => check.statistic = x.trim().to_lowercase(),
+            Some(("dc", x)) => check.dc = x.trim().parse().ok(),
+            Some(("basic", x)) => check.basic = x.trim() == "true",
+            None if param == "basic" => check.basic = true,
+            None if i == 0 => check.statistic = param.to_lowercase(),
+            _ => {}
+        }
+    }
+    check
+}
+
+fn parse_template(content: &str, label: Option<String>) -> TemplateMacro {
+    let mut template = TemplateMacro {
+        shape: String::new(),
+        distance: None,
+        label,
+    };
+    for (i, param) in content.split('|').map(str::trim).enumerate() {
+        match param.split_once(':') {
+            Some(("type", x)) => template.shape = x.trim().to_lowercase(),
+            Some(("distance", x)) => template.distance = x.trim().parse().ok(),
+            None if i == 0 => template.shape = param.to_lowercase(),
+            _ => {}
+        }
+    }
+    template
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(
+        "@UUID[Compendium.pf2e.conditionitems.Item.Frightened]{Frightened 1}",
+        "Frightened 1"
+    )]
+    #[case("@UUID[Compendium.pf2e.spells-srd.Item.Fireball]", "Fireball")]
+    #[case("@Damage[2d6[fire]]", "2d6 fire")]
+    #[case(
+        "@Damage[(2d6+4)[slashing],1d6[persistent,bleed]]",
+        "2d6+4 slashing plus 1d6 persistent bleed"
+    )]
+    #[case("@Check[fortitude|dc:20|basic]", "DC 20 basic Fortitude save")]
+    #[case("@Check[type:reflex|dc:18]", "DC 18 Reflex save")]
+    #[case("@Check[athletics|dc:15]{Athletics}", "Athletics")]
+    #[case("@Check[flat|dc:5]", "DC 5 flat check")]
+    #[case("@Template[emanation|distance:10]", "10-foot emanation")]
+    #[case("[[/r 1d4 #Healing]]", "1d4")]
+    #[case("[[/br {2d6}[fire]]]{2d6 fire damage}", "2d6 fire damage")]
+    #[case("@Localize[PF2E.NPC.Abilities.Glossary.Grab]", "")]
+    #[case("Fish &amp; chips&nbsp;&#8212; cheap", "Fish & chips — cheap")]
+    #[case("a @ b [c] <- d", "a @ b [c] <- d")]
+    fn macros_render_as_plain_text(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            ParsedDescription::parse(input).render(DescriptionFormat::Text),
+            expected
+        );
+    }
+
+    #[test]
+    fn html_structure_is_kept_in_every_format() {
+        let parsed = ParsedDescription::parse(
+            "<p><strong>Trigger</strong> A creature  enters;</p>\n<hr />\n<ul><li>one</li><li>two<br>lines</li></ul>",
+        );
+        assert_eq!(
+            parsed.render(DescriptionFormat::Text),
+            "Trigger A creature enters;\n\n• one\n\n• two\nlines"
+        );
+        assert_eq!(
+            parsed.render(DescriptionFormat::Markdown),
+            "**Trigger** A creature enters;\n\n---\n\n- one\n\n- two  \nlines"
+        );
+        assert_eq!(
+            parsed.render(DescriptionFormat::Html),
+            "<p><strong>Trigger</strong> A creature enters;</p><hr><p>• one</p><p>• two<br>lines</p>"
+        );
+    }
+
+    #[test]
+    fn markdown_escapes_the_text_it_did_not_write() {
+        let parsed = ParsedDescription::parse("<p><em>snake_case</em> deals 2*3 `code`</p>");
+        assert_eq!(
+            parsed.render(DescriptionFormat::Markdown),
+            r"*snake\_case* deals 2\*3 \`code\`"
+        );
+    }
+
+    #[test]
+    fn macros_keep_their_data_in_html_and_ast() {
+        let parsed =
+            ParsedDescription::parse("Take @Damage[2d6[fire]] (@Check[reflex|dc:20|basic])");
+        assert_eq!(
+            parsed.render(DescriptionFormat::Html),
+            "<p>Take <span class=\"damage\" data-formula=\"2d6\">2d6 fire</span> \
+             (<span class=\"check\" data-statistic=\"reflex\" data-dc=\"20\">DC 20 basic Reflex save</span>)</p>"
+        );
+        let ast: Vec<MarkupSpan> =
+            serde_json::from_str(&parsed.render(DescriptionFormat::Ast)).unwrap();
+        assert_eq!(ast, parsed.spans);
+    }
+
+    #[test]
+    fn checks_and_damage_are_extracted() {
+        let rolls = DescriptionRolls::from_descriptions([
+            "@Damage[1d6[persistent,acid]]",
+            "<p>@Check[will|dc:resolve(@actor.system.details.level.value)]</p>",
+        ]);
+        assert_eq!(
+            rolls.damage[0].parts,
+            vec![DamagePart {
+                formula: String::from("1d6"),
+                damage_type: Some(String::from("acid")),
+                category: Some(String::from("persistent")),
+            }]
+        );
+        assert_eq!(rolls.checks[0].statistic, "will");
+        assert_eq!(rolls.checks[0].dc, None);
+    }
+
+    #[rstest]
+    #[case(
+        "(@Check[reflex|dc:29|basic]) and @Damage[11d6[fire]]",
+        2,
+        4,
+        "(@Check[reflex|dc:31|basic]) and @Damage[(11d6+4)[fire]]"
+    )]
+    #[case(
+        "@Damage[(2d6+4)[slashing],1d6[persistent,bleed]]{2d6+4 slashing}",
+        -2,
+        -2,
+        "@Damage[(2d6+2)[slashing],1d6[persistent,bleed]]{2d6+2 slashing}"
+    )]
+    #[case(
+        "@Check[type:fortitude|dc:20]{DC 20 Fortitude} @Check[will|dc:resolve(@actor.level)]",
+        -2,
+        -4,
+        "@Check[type:fortitude|dc:18]{DC 18 Fortitude} @Check[will|dc:resolve(@actor.level)]"
+    )]
+    #[case(
+        "@Damage[(@actor.level)d4[fire]] at 120 feet",
+        2,
+        2,
+        "@Damage[(@actor.level)d4[fire]] at 120 feet"
+    )]
+    #[case("[[/r (@item.level)d6]]", 2, 2, "[[/r (@item.level)d6]]")]
+    fn elite_and_weak_adjust_rolls_inside_descriptions(
+        #[case] input: &str,
+        #[case] dc_modifier: i64,
+        #[case] damage_modifier: i64,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            adjust_description_rolls(input, dc_modifier, damage_modifier),
+            expected
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use crate::models::foundry::foundry_actor::{
+    FOUNDRY_PF2E_SCHEMA_VERSION, FoundryIdKind, foundry_id, foundry_name_id, parse_feet,
+    publication, rarity_slug, size_slug, slugify, trait_slugs,
+};
+use crate::models::item::item_metadata::type_enum::ItemTypeEnum;
+use crate::models::response_data::{ResponseItem, ShopListingResponse};
+use serde_json::{Map, Value, json};
+
+/// Exports a shop as a merchant loot actor, with every listed item in its inventory.
+/// Items listed more than once are merged, adding up their quantity.
+pub fn shop_loot_actor(name: &str, shop: &ShopListingResponse) -> Value {
+    let mut items: Vec<Value> = vec![];
+    for item in shop.results.iter().flatten() {
+        let exported = physical_item(item);
+        match items.iter_mut().find(|x| x["_id"] == exported["_id"]) {
+            Some(existing) => {
+                let quantity =
+                    existing["system"]["quantity"].as_i64().unwrap_or(0) + item.core_item.quantity;
+                existing["system"]["quantity"] = json!(quantity);
+            }
+            None => items.push(exported),
+        }
+    }
+    json!({
+        "_id": foundry_name_id(FoundryIdKind::Loot, name),
+        "name": name,
+        "type": "loot",
+        "img": "systems/pf2e/icons/default-icons/loot.svg",
+        "system": {
+            "_migration": {"version": FOUNDRY_PF2E_SCHEMA_VERSION},
+            "details": {
+                "description": "",
+                "level": {"value": shop.results.iter().flatten().map(|x| x.core_item.level).max().unwrap_or(0)},
+            },
+            "lootSheetType": "Merchant",
+            "hiddenWhenEmpty": false,
+            "traits": {"rarity": "common", "size": {"value": "med"}, "value": []},
+        },
+        "items": items,
+        "flags": {
+            "bybe": {
+                "game": shop.game.to_string(),
+            },
+        },
+    })
+}
+
+/// Inventory item, with the data of its type when it is a weapon, armor or shield
+pub fn physical_item(item: &ResponseItem) -> Value {
+    let core = &item.core_item;
+    let mut system = json!({
+        "baseItem": core.base_item.as_deref().map(slugify),
+        "bulk": {"value": core.bulk.into_inner()},
+        "description": {"value": core.description},
+        "hardness": core.hardness,
+        "hp": {"value": core.hp, "max": core.hp},
+        "level": {"value": core.level},
+        "material": {
+            "grade": core.material_grade.as_deref().map(slugify),
+            "type": core.material_type.as_deref().map(slugify),
+        },
+        "price": {"value": price(core.price)},
+        "publication": publication(&core.source, &core.license, core.remaster),
+        "quantity": core.quantity,
+        "size": size_slug(&core.size),
+        "traits": {"rarity": rarity_slug(&core.rarity), "value": trait_slugs(&core.traits)},
+        "usage": {"value": core.usage.clone().unwrap_or_default()},
+    });
+    let mut type_data = Map::new();
+    if let Some(uses) = core.number_of_uses {
+        type_data.insert("uses".into(), json!({"value": uses, "max": uses}));
+    }
+    if let Some(weapon) = &item.weapon_data {
+        let damage = weapon.damage_data.first();
+        type_data.extend([
+            ("category".into(), json!(core.category.as_deref().map(slugify))),
+            ("group".into(), json!(core.group.as_deref().map(slugify))),
+            (
+                "damage".into(),
+                json!({
+                    "dice": damage.and_then(|x| x.dice.as_ref()).map_or(0, |x| x.n_of_dices),
+                    "die": damage.and_then(|x| x.dice.as_ref()).map(|x| format!("d{}", x.dice_size)),
+                    "damageType": damage.and_then(|x| x.dmg_type.as_deref()).map_or_else(|| "untyped".to_string(), slugify),
+                }),
+            ),
+            (
+                "range".into(),
+                json!(weapon.range.as_ref().and_then(|x| parse_feet(x.increment.as_deref().unwrap_or(&x.value)))),
+            ),
+            (
+                "runes".into(),
+                json!({
+                    "potency": weapon.n_of_potency_runes,
+                    "striking": weapon.n_of_striking_runes,
+                    "property": weapon.property_runes.iter().map(|x| slugify(x)).collect::<Vec<_>>(),
+                }),
+            ),
+        ]);
+    }
+    if let Some(armor) = &item.armor_data {
+        type_data.extend([
+            (
+                "category".into(),
+                json!(core.category.as_deref().map(slugify)),
+            ),
+            ("acBonus".into(), json!(armor.ac_bonus)),
+            ("checkPenalty".into(), json!(armor.check_penalty)),
+            ("dexCap".into(), json!(armor.dex_cap)),
+            ("speedPenalty".into(), json!(armor.speed_penalty)),
+            (
+                "runes".into(),
+                json!({
+                    "potency": armor.n_of_potency_runes,
+                    "resilient": armor.n_of_resilient_runes,
+                    "property": armor.property_runes.iter().map(|x| slugify(x)).collect::<Vec<_>>(),
+                }),
+            ),
+        ]);
+    }
+    if let Some(shield) = &item.shield_data {
+        type_data.extend([
+            ("acBonus".into(), json!(shield.bonus_ac)),
+            ("speedPenalty".into(), json!(shield.speed_penalty)),
+            (
+                "runes".into(),
+                json!({"reinforcing": shield.n_of_reinforcing_runes}),
+            ),
+        ]);
+    }
+    if let Value::Object(system) = &mut system {
+        system.extend(type_data);
+    }
+    json!({
+        "_id": foundry_id(FoundryIdKind::Item, core.id),
+        "name": core.name,
+        "type": item_type(&core.item_type),
+        "system": system,
+    })
+}
+
+const fn item_type(item_type: &ItemTypeEnum) -> &'static str {
+    match item_type {
+        ItemTypeEnum::Consumable => "consumable",
+        ItemTypeEnum::Equipment => "equipment",
+        ItemTypeEnum::Weapon => "weapon",
+        ItemTypeEnum::Armor => "armor",
+        ItemTypeEnum::Shield => "shield",
+    }
+}
+
+/// Prices are stored in cp, Foundry splits them by coin
+fn price(cp: i64) -> Value {
+    json!({"gp": cp / 100, "sp": cp % 100 / 10, "cp": cp % 10})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::creature::creature_struct::Creature;
+    use crate::models::shared::game_system_enum::GameSystem;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(10, json!({"gp": 0, "sp": 1, "cp": 0}))]
+    #[case(1234, json!({"gp": 12, "sp": 3, "cp": 4}))]
+    fn prices_are_split_by_coin(#[case] cp: i64, #[case] expected: Value) {
+        assert_eq!(expected, price(cp));
+    }
+
+    #[rstest]
+    fn repeated_items_are_merged() {
+        let creature: Creature =
+            serde_json::from_str(include_str!("fixtures/creature.json")).unwrap();
+        let weapon = &creature.combat_data.unwrap().weapons[0];
+        let item = ResponseItem {
+            core_item: weapon.item_core.clone(),
+            weapon_data: Some(weapon.weapon_data.clone()),
+            armor_data: None,
+            shield_data: None,
+            game: GameSystem::Pathfinder,
+        };
+        let shop: ShopListingResponse = serde_json::from_value(json!({
+            "results": [item, item],
+            "count": 2,
+            "total": 2,
+            "game": "pf",
+            "next": null,
+            "search_matches": null,
+        }))
+        .unwrap();
+        let actor = shop_loot_actor("Dogslicers and more", &shop);
+        let items = actor["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["type"], "weapon");
+        assert_eq!(items[0]["system"]["quantity"], 2);
+        assert_eq!(items[0]["system"]["damage"]["die"], "d6");
+    }
+}
@@ -0,0 +1,206 @@
+use crate::models::creature::items::skill::Skill;
+use crate::models::foundry::inline_markup::{DescriptionFormat, render_description};
+use crate::models::hazard::hazard_struct::Hazard;
+use crate::models::item::item_struct::Item;
+use crate::models::response_data::{
+    BestiaryResponse, EncounterContent, HazardListingResponse, RandomEncounterGeneratorResponse,
+    ResponseCreature, ResponseHazard, ResponseItem, ShopListingResponse,
+};
+use crate::models::shared::action::Action;
+use crate::models::shearable_data::{
+    HydratedEncounterContent, HydratedEncounterResponse, HydratedShopResponse,
+};
+use crate::traits::description_renderable::DescriptionRenderable;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+impl<T: DescriptionRenderable> DescriptionRenderable for Option<T> {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        if let Some(x) = self {
+            x.render_descriptions(format);
+        }
+    }
+}
+
+impl<T: DescriptionRenderable> DescriptionRenderable for Vec<T> {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        for x in self {
+            x.render_descriptions(format);
+        }
+    }
+}
+
+impl<K: Eq + Hash, T: DescriptionRenderable> DescriptionRenderable for HashMap<K, T> {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        for x in self.values_mut() {
+            x.render_descriptions(format);
+        }
+    }
+}
+
+impl DescriptionRenderable for Action {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        self.core_action.description = render_description(&self.core_action.description, format);
+    }
+}
+
+impl DescriptionRenderable for Item {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        self.description = render_description(&self.description, format);
+    }
+}
+
+impl DescriptionRenderable for Skill {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        if let Some(description) = &self.description {
+            self.description = Some(render_description(description, format));
+        }
+    }
+}
+
+impl DescriptionRenderable for Hazard {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        let essential = &mut self.essential;
+        for description in [
+            &mut essential.description,
+            &mut essential.disable_description,
+            &mut essential.reset_description,
+            &mut essential.routine_description,
+        ] {
+            *description = render_description(description, format);
+        }
+        self.actions.render_descriptions(format);
+    }
+}
+
+impl DescriptionRenderable for ResponseCreature {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        if let Some(extra) = &mut self.extra_data {
+            extra.actions.render_descriptions(format);
+            extra.skills.render_descriptions(format);
+            extra.items.render_descriptions(format);
+        }
+        if let Some(combat) = &mut self.combat_data {
+            for weapon in &mut combat.weapons {
+                weapon.item_core.render_descriptions(format);
+                weapon
+                    .weapon_data
+                    .attack_effects
+                    .render_descriptions(format);
+            }
+            for armor in &mut combat.armors {
+                armor.item_core.render_descriptions(format);
+            }
+            for shield in &mut combat.shields {
+                shield.item_core.render_descriptions(format);
+            }
+        }
+    }
+}
+
+impl DescriptionRenderable for ResponseHazard {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        self.core_hazard.render_descriptions(format);
+    }
+}
+
+impl DescriptionRenderable for ResponseItem {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        self.core_item.render_descriptions(format);
+    }
+}
+
+impl DescriptionRenderable for BestiaryResponse {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        self.results.render_descriptions(format);
+    }
+}
+
+impl DescriptionRenderable for HazardListingResponse {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        self.results.render_descriptions(format);
+    }
+}
+
+impl DescriptionRenderable for ShopListingResponse {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        self.results.render_descriptions(format);
+    }
+}
+
+impl DescriptionRenderable for EncounterContent {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        self.creatures.render_descriptions(format);
+        self.hazards.render_descriptions(format);
+    }
+}
+
+impl DescriptionRenderable for RandomEncounterGeneratorResponse {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        self.results.render_descriptions(format);
+    }
+}
+
+impl DescriptionRenderable for HydratedEncounterContent {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        for x in &mut self.creatures {
+            x.entity.render_descriptions(format);
+        }
+        for x in &mut self.hazards {
+            x.entity.render_descriptions(format);
+        }
+    }
+}
+
+impl DescriptionRenderable for HydratedEncounterResponse {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        self.results.render_descriptions(format);
+    }
+}
+
+impl DescriptionRenderable for HydratedShopResponse {
+    fn render_descriptions(&mut self, format: DescriptionFormat) {
+        self.listing.render_descriptions(format);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::creature::creature_struct::Creature;
+    use crate::models::shared::game_system_enum::GameSystem;
+
+    #[test]
+    fn hazard_descriptions_are_rendered_only_when_a_format_is_requested() {
+        let mut hazard: Hazard =
+            serde_json::from_str(include_str!("fixtures/hazard.json")).unwrap();
+        hazard.essential.disable_description =
+            String::from("<p>@Check[thievery|dc:22]{Thievery} (expert) to jam the spring</p>");
+        let stored = ResponseHazard::from((hazard, GameSystem::Pathfinder));
+        assert_eq!(stored.clone().with_description_format(None), stored);
+        let rendered = stored.with_description_format(Some(DescriptionFormat::Text));
+        assert_eq!(
+            rendered.core_hazard.essential.disable_description,
+            "Thievery (expert) to jam the spring"
+        );
+    }
+
+    #[test]
+    fn attack_effects_of_creature_weapons_are_rendered() {
+        let creature: Creature =
+            serde_json::from_str(include_str!("fixtures/creature.json")).unwrap();
+        let mut creature = ResponseCreature::from(creature);
+        let mut effect = creature.extra_data.as_ref().unwrap().actions[0].clone();
+        effect.core_action.description = String::from("<p>Takes @Damage[1d6[fire]]</p>");
+        let combat = creature.combat_data.as_mut().unwrap();
+        combat.weapons[0].weapon_data.attack_effects = vec![effect];
+        creature.render_descriptions(DescriptionFormat::Markdown);
+        let combat = creature.combat_data.unwrap();
+        assert_eq!(
+            combat.weapons[0].weapon_data.attack_effects[0]
+                .core_action
+                .description,
+            "Takes 1d6 fire"
+        );
+    }
+}
@@ -0,0 +1,7 @@
+pub mod compendium;
+pub mod foundry_actor;
+pub mod hazard_actor;
+pub mod inline_markup;
+pub mod loot_actor;
+pub mod markup_responses;
+pub mod npc_actor;
@@ -0,0 +1,444 @@
+use crate::models::creature::creature_component::creature_combat::CreatureCombatData;
+use crate::models::creature::creature_component::creature_extra::{
+    AbilityScores, CreatureExtraData,
+};
+use crate::models::creature::creature_metadata::variant_enum::CreatureVariant;
+use crate::models::creature::creature_struct::Creature;
+use crate::models::creature::items::spellcaster_entry::SpellcasterEntry;
+use crate::models::foundry::foundry_actor::{
+    FOUNDRY_PF2E_SCHEMA_VERSION, FoundryIdKind, action_item, damage_formula, foundry_id,
+    parse_feet, publication, rarity_slug, size_slug, slugify, trait_slugs,
+};
+use crate::models::item::item_metadata::type_enum::WeaponTypeEnum;
+use crate::models::item::weapon_struct::Weapon;
+use crate::models::response_data::ResponseCreature;
+use crate::traits::foundry_exportable::FoundryExportable;
+use serde_json::{Map, Value, json};
+
+/// Skills stored in `system.skills`, every other one is exported as a lore item
+const FOUNDRY_SKILLS: [&str; 16] = [
+    "acrobatics",
+    "arcana",
+    "athletics",
+    "crafting",
+    "deception",
+    "diplomacy",
+    "intimidation",
+    "medicine",
+    "nature",
+    "occultism",
+    "performance",
+    "religion",
+    "society",
+    "stealth",
+    "survival",
+    "thievery",
+];
+
+/// Exports the creature as is: variant and PWL adjustments are expected to be applied already.
+/// Sections that were not fetched are exported with neutral values.
+impl FoundryExportable for Creature {
+    fn to_foundry_actor(&self) -> Value {
+        let essential = &self.core_data.essential;
+        let extra = self.extra_data.as_ref();
+        let combat = self.combat_data.as_ref();
+
+        let mut items: Vec<Value> = vec![];
+        if let Some(combat) = combat {
+            items.extend(combat.weapons.iter().map(strike_item));
+        }
+        if let Some(extra) = extra {
+            items.extend(extra.actions.iter().map(action_item));
+            items.extend(lore_items(extra));
+        }
+        if let Some(spellcaster) = &self.spellcaster_data {
+            for entry in &spellcaster.spellcaster_entries {
+                items.extend(spellcasting_items(entry));
+            }
+        }
+
+        json!({
+            "_id": foundry_id(actor_id_kind(self.variant_data.variant), essential.id),
+            "name": essential.name,
+            "type": "npc",
+            "img": "systems/pf2e/icons/default-icons/npc.svg",
+            "system": {
+                "_migration": {"version": FOUNDRY_PF2E_SCHEMA_VERSION},
+                "abilities": abilities(extra),
+                "attributes": {
+                    "ac": {
+                        "value": combat.map_or(0, |x| x.ac),
+                        "details": extra.and_then(|x| x.ac_detail.clone()).unwrap_or_default(),
+                    },
+                    "hp": {
+                        "value": essential.hp,
+                        "max": essential.hp,
+                        "temp": 0,
+                        "details": extra.and_then(|x| x.hp_detail.clone()).unwrap_or_default(),
+                    },
+                    "speed": speed(extra),
+                    "immunities": combat.map_or_else(Vec::new, |x| {
+                        x.immunities.iter().map(|i| json!({"type": slugify(i)})).collect()
+                    }),
+                    "resistances": combat.map_or_else(Vec::new, resistances),
+                    "weaknesses": combat.map_or_else(Vec::new, |x| {
+                        x.weaknesses
+                            .iter()
+                            .map(|(name, value)| json!({"type": slugify(name), "value": value}))
+                            .collect()
+                    }),
+                },
+                "details": {
+                    "level": {"value": self.variant_data.level},
+                    "languages": {
+                        "value": extra.map_or_else(Vec::new, |x| {
+                            x.languages.iter().map(|l| slugify(l)).collect()
+                        }),
+                        "details": extra.and_then(|x| x.language_detail.clone()).unwrap_or_default(),
+                    },
+                    "blurb": essential.family,
+                    "publicNotes": "",
+                    "privateNotes": "",
+                    "publication": publication(&essential.source, &essential.license, essential.remaster),
+                },
+                "perception": perception(extra),
+                "resources": {
+                    "focus": {"value": essential.focus_points, "max": essential.focus_points},
+                },
+                "saves": saves(combat),
+                "skills": skills(extra),
+                "traits": {
+                    "rarity": rarity_slug(&essential.rarity),
+                    "size": {"value": size_slug(&essential.size)},
+                    "value": trait_slugs(&self.core_data.traits),
+                },
+            },
+            "items": items,
+            "flags": {
+                "bybe": {
+                    "id": essential.id,
+                    "variant": self.variant_data.variant.to_string(),
+                    "game": self.game_system.to_string(),
+                    "archiveLink": self.variant_data.archive_link,
+                },
+            },
+        })
+    }
+}
+
+impl FoundryExportable for ResponseCreature {
+    fn to_foundry_actor(&self) -> Value {
+        Creature::from(self.clone()).to_foundry_actor()
+    }
+}
+
+/// Variants of the same creature are distinct actors, they must not overwrite each other on import
+const fn actor_id_kind(variant: CreatureVariant) -> FoundryIdKind {
+    match variant {
+        CreatureVariant::Base => FoundryIdKind::Actor,
+        CreatureVariant::Elite => FoundryIdKind::EliteActor,
+        CreatureVariant::Weak => FoundryIdKind::WeakActor,
+    }
+}
+
+fn abilities(extra: Option<&CreatureExtraData>) -> Value {
+    let scores = extra.map(|x| &x.ability_scores);
+    let modifier = |f: fn(&AbilityScores) -> i64| json!({"mod": scores.map_or(0, f)});
+    json!({
+        "str": modifier(|x| x.strength),
+        "dex": modifier(|x| x.dexterity),
+        "con": modifier(|x| x.constitution),
+        "int": modifier(|x| x.intelligence),
+        "wis": modifier(|x| x.wisdom),
+        "cha": modifier(|x| x.charisma),
+    })
+}
+
+/// The land speed is stored as `Base`, every other one is an additional movement type
+fn speed(extra: Option<&CreatureExtraData>) -> Value {
+    let speeds = extra.map(|x| &x.speeds);
+    let is_land =
+        |name: &str| name.eq_ignore_ascii_case("base") || name.eq_ignore_ascii_case("land");
+    json!({
+        "value": speeds
+            .and_then(|x| x.iter().find(|(name, _)| is_land(name)))
+            .map_or(0, |(_, value)| *value),
+        "otherSpeeds": speeds.map_or_else(Vec::new, |x| {
+            x.iter()
+                .filter(|(name, _)| !is_land(name))
+                .map(|(name, value)| json!({"type": slugify(name), "value": value}))
+                .collect()
+        }),
+        "details": "",
+    })
+}
+
+fn resistances(combat: &CreatureCombatData) -> Vec<Value> {
+    combat
+        .resistances
+        .iter()
+        .map(|x| {
+            json!({
+                "type": slugify(&x.core.name),
+                "value": x.core.value,
+                "exceptions": x.exception_vs.iter().map(|e| slugify(e)).collect::<Vec<_>>(),
+                "doubleVs": x.double_vs.iter().map(|e| slugify(e)).collect::<Vec<_>>(),
+            })
+        })
+        .collect()
+}
+
+fn perception(extra: Option<&CreatureExtraData>) -> Value {
+    json!({
+        "mod": extra.map_or(0, |x| x.perception),
+        "details": extra.and_then(|x| x.perception_detail.clone()).unwrap_or_default(),
+        "vision": extra.is_none_or(|x| x.has_vision),
+        "senses": extra.map_or_else(Vec::new, |x| {
+            x.senses
+                .iter()
+                .map(|sense| {
+                    let mut value = Map::new();
+                    value.insert("type".into(), json!(slugify(&sense.name)));
+                    if let Some(acuity) = &sense.acuity {
+                        value.insert("acuity".into(), json!(slugify(acuity)));
+                    }
+                    if let Some(range) = sense.range.as_ref().and_then(|r| parse_feet(&r.value)) {
+                        value.insert("range".into(), json!(range));
+                    }
+                    Value::Object(value)
+                })
+                .collect()
+        }),
+    })
+}
+
+fn saves(combat: Option<&CreatureCombatData>) -> Value {
+    let saves = combat.map(|x| &x.saving_throws);
+    json!({
+        "fortitude": {
+            "value": saves.map_or(0, |x| x.fortitude),
+            "saveDetail": saves.and_then(|x| x.fortitude_detail.clone()).unwrap_or_default(),
+        },
+        "reflex": {
+            "value": saves.map_or(0, |x| x.reflex),
+            "saveDetail": saves.and_then(|x| x.reflex_detail.clone()).unwrap_or_default(),
+        },
+        "will": {
+            "value": saves.map_or(0, |x| x.will),
+            "saveDetail": saves.and_then(|x| x.will_detail.clone()).unwrap_or_default(),
+        },
+    })
+}
+
+fn skills(extra: Option<&CreatureExtraData>) -> Value {
+    Value::Object(
+        extra
+            .into_iter()
+            .flat_map(|x| &x.skills)
+            .filter_map(|skill| {
+                let slug = slugify(&skill.name);
+                FOUNDRY_SKILLS
+                    .contains(&slug.as_str())
+                    .then(|| (slug, json!({"base": skill.modifier})))
+            })
+            .collect(),
+    )
+}
+
+fn lore_items(extra: &CreatureExtraData) -> Vec<Value> {
+    extra
+        .skills
+        .iter()
+        .filter(|skill| !FOUNDRY_SKILLS.contains(&slugify(&skill.name).as_str()))
+        .enumerate()
+        .map(|(i, skill)| {
+            json!({
+                "_id": foundry_id(FoundryIdKind::Lore, i64::try_from(i).unwrap_or_default()),
+                "name": skill.name,
+                "type": "lore",
+                "system": {
+                    "mod": {"value": skill.modifier},
+                    "description": {"value": skill.description.clone().unwrap_or_default()},
+                },
+            })
+        })
+        .collect()
+}
+
+fn strike_item(weapon: &Weapon) -> Value {
+    let core = &weapon.item_core;
+    let data = &weapon.weapon_data;
+    let mut traits = trait_slugs(&core.traits);
+    if let Some(increment) = data
+        .range
+        .as_ref()
+        .and_then(|r| r.increment.as_deref().or(Some(r.value.as_str())))
+        .and_then(parse_feet)
+    {
+        traits.push(format!("range-increment-{increment}"));
+    }
+    let damage_rolls: Map<String, Value> = data
+        .damage_data
+        .iter()
+        .map(|x| {
+            (
+                foundry_id(FoundryIdKind::Strike, x.id),
+                json!({
+                    "damage": damage_formula(x.dice.as_ref(), x.bonus_dmg),
+                    "damageType": x.dmg_type.as_deref().map_or_else(|| "untyped".to_string(), slugify),
+                    "category": null,
+                }),
+            )
+        })
+        .collect();
+    let weapon_type = match data.weapon_type {
+        WeaponTypeEnum::Ranged => "ranged",
+        WeaponTypeEnum::Melee | WeaponTypeEnum::Generic => "melee",
+    };
+    json!({
+        "_id": foundry_id(FoundryIdKind::Strike, data.id),
+        "name": core.name,
+        "type": "melee",
+        "img": "systems/pf2e/icons/default-icons/melee.svg",
+        "system": {
+            "bonus": {"value": data.to_hit_bonus.unwrap_or_default()},
+            "damageRolls": damage_rolls,
+            "weaponType": {"value": weapon_type},
+            "attackEffects": {
+                "value": data
+                    .attack_effects
+                    .iter()
+                    .map(|x| slugify(&x.core_action.name))
+                    .collect::<Vec<_>>(),
+            },
+            "description": {"value": core.description},
+            "traits": {"rarity": rarity_slug(&core.rarity), "value": traits},
+            "publication": publication(&core.source, &core.license, core.remaster),
+        },
+    })
+}
+
+/// The spellcasting entry followed by its spells, which point to it through their location
+fn spellcasting_items(entry: &SpellcasterEntry) -> Vec<Value> {
+    let data = &entry.spellcaster_data;
+    let entry_id = foundry_id(FoundryIdKind::SpellcastingEntry, data.id);
+    let mut items = vec![json!({
+        "_id": entry_id,
+        "name": data.spellcasting_name,
+        "type": "spellcastingEntry",
+        "system": {
+            "spelldc": {"dc": data.spellcasting_dc_mod, "value": data.spellcasting_atk_mod},
+            "tradition": {"value": slugify(&data.spellcasting_tradition)},
+            "prepared": {
+                "value": slugify(&data.type_of_spellcaster),
+                "flexible": data.is_spellcasting_flexible.unwrap_or(false),
+            },
+            "showSlotlessLevels": {"value": false},
+        },
+    })];
+    items.extend(entry.spells.iter().map(|spell| {
+        json!({
+            "_id": foundry_id(FoundryIdKind::Spell, spell.id),
+            "name": spell.name,
+            "type": "spell",
+            "img": "systems/pf2e/icons/default-icons/spell.svg",
+            "system": {
+                "level": {"value": spell.level},
+                "location": {
+                    "value": entry_id,
+                    "heightenedLevel": (data.heighten_level > 0).then_some(data.heighten_level),
+                },
+                "time": {"value": spell.actions},
+                "range": {"value": spell.range.as_ref().map(|x| x.value.clone()).unwrap_or_default()},
+                "target": {"value": spell.target},
+                "duration": {
+                    "value": spell.duration.clone().unwrap_or_default(),
+                    "sustained": spell.sustained,
+                },
+                "area": spell.area_type.as_ref().zip(spell.area_value).map(|(area_type, value)| {
+                    json!({"type": slugify(area_type), "value": value})
+                }),
+                "defense": spell.saving_throw.as_ref().map(|save| {
+                    json!({
+                        "save": {
+                            "statistic": slugify(save),
+                            "basic": spell.basic_saving_throw.unwrap_or(false),
+                        },
+                    })
+                }),
+                "traits": {"rarity": slugify(&spell.rarity), "value": trait_slugs(&spell.traits)},
+                "publication": publication(&spell.source, &spell.license, spell.remaster),
+            },
+        })
+    }));
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::shared::trait_data::TraitData;
+
+    fn goblin_war_chanter() -> Creature {
+        serde_json::from_str(include_str!("fixtures/creature.json")).unwrap()
+    }
+
+    #[test]
+    fn actor_matches_the_pinned_schema() {
+        let schema =
+            serde_json::from_str(include_str!("fixtures/pf2e_npc_actor.schema.json")).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let actor = goblin_war_chanter().to_foundry_actor();
+        let errors: Vec<_> = validator
+            .iter_errors(&actor)
+            .map(|x| x.to_string())
+            .collect();
+        assert!(errors.is_empty(), "{errors:#?}");
+    }
+
+    #[test]
+    fn lores_and_spells_become_items() {
+        let actor = goblin_war_chanter().to_foundry_actor();
+        assert_eq!(actor["system"]["attributes"]["speed"]["value"], 25);
+        assert_eq!(actor["system"]["skills"]["performance"]["base"], 8);
+        let items = actor["items"].as_array().unwrap();
+        let of_type = |kind: &str| items.iter().filter(|x| x["type"] == kind).count();
+        assert_eq!(of_type("melee"), 2);
+        assert_eq!(of_type("lore"), 1);
+        let entry = items
+            .iter()
+            .find(|x| x["type"] == "spellcastingEntry")
+            .unwrap();
+        let spell = items.iter().find(|x| x["type"] == "spell").unwrap();
+        assert_eq!(spell["system"]["location"]["value"], entry["_id"]);
+    }
+
+    #[test]
+    fn spells_keep_their_traits() {
+        let mut creature = goblin_war_chanter();
+        let spell = &mut creature
+            .spellcaster_data
+            .as_mut()
+            .unwrap()
+            .spellcaster_entries[0]
+            .spells[0];
+        spell.traits = ["Concentrate", "Manipulate"]
+            .map(|name| TraitData {
+                name: name.to_string(),
+                description: None,
+                display_name: None,
+            })
+            .to_vec();
+        let actor = creature.to_foundry_actor();
+        let spell = actor["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|x| x["type"] == "spell")
+            .unwrap();
+        assert_eq!(
+            spell["system"]["traits"]["value"],
+            serde_json::json!(["concentrate", "manipulate"])
+        );
+    }
+}
@@ -1,6 +1,8 @@
+use crate::models::shared::name_match_mode_enum::NameMatchModeEnum;
 use crate::models::shared::pf_version_enum::GameSystemVersionEnum;
 use crate::models::shared::rarity_enum::RarityEnum;
 use crate::models::shared::size_enum::SizeEnum;
+use crate::models::shared::trait_filter_expression::TraitFilterExpression;
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
@@ -35,9 +37,12 @@ impl From<bool> for HazardComplexityEnum {
     }
 }
 
-#[derive(Serialize, Deserialize, IntoParams, ToSchema, Default)]
+#[derive(Serialize, Deserialize, IntoParams, ToSchema, Default, Clone)]
 pub struct HazardFieldFilters {
     pub name_filter: Option<String>,
+    pub name_match_mode: Option<NameMatchModeEnum>,
+    #[schema(example = "teleport")]
+    pub text_search_filter: Option<String>,
     pub source_filter: Option<Vec<String>>,
     pub complexity_filter: Option<HazardComplexityEnum>,
     pub rarity_filter: Option<Vec<RarityEnum>>,
@@ -45,6 +50,7 @@ pub struct HazardFieldFilters {
 
     pub trait_whitelist_filter: Option<Vec<String>>,
     pub trait_blacklist_filter: Option<Vec<String>>,
+    pub trait_expression_filter: Option<TraitFilterExpression>,
 
     #[schema(minimum = 0, example = 0)]
     pub min_ac_filter: Option<i64>,
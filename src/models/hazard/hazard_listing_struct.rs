@@ -2,10 +2,11 @@ use crate::models::hazard::hazard_field_filter::HazardComplexityEnum;
 use crate::models::routers_validator_structs::{OrderEnum, PaginatedRequest};
 use crate::models::shared::rarity_enum::RarityEnum;
 use crate::models::shared::size_enum::SizeEnum;
+use crate::models::shared::trait_filter_expression::TraitFilterExpression;
 use crate::traits::url::has_sort_fields::HasSortFields;
 use crate::traits::url::paginated_request_ext::PaginatedRequestExt;
 use serde::{Deserialize, Serialize};
-use strum::Display;
+use strum::{Display, EnumString};
 use utoipa::{IntoParams, ToSchema};
 
 #[derive(Serialize, Deserialize, ToSchema, Clone)]
@@ -57,9 +58,13 @@ pub struct HazardFilterQuery {
     pub hazard_table_fields_filter: HazardTableFieldsFilter,
     pub trait_whitelist_filter: Vec<String>,
     pub trait_blacklist_filter: Vec<String>,
+    pub trait_expression_filter: Option<TraitFilterExpression>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema, Default, Eq, PartialEq, Hash, Clone, Display)]
+#[derive(
+    Serialize, Deserialize, ToSchema, Default, Eq, PartialEq, Hash, Clone, Display, EnumString,
+)]
+#[strum(ascii_case_insensitive)]
 pub enum HazardSortEnum {
     #[serde(alias = "id", alias = "ID")]
     Id,
@@ -99,6 +104,9 @@ pub struct HazardListingSortData {
     // Optional here for swagger, kinda bad but w/e
     pub sort_by: Option<HazardSortEnum>,
     pub order_by: Option<OrderEnum>,
+    /// Multi-key sort, ex `level:desc,stealth:desc,name:asc`.
+    /// When given, `sort_by` and `order_by` are ignored.
+    pub sort: Option<String>,
 }
 
 impl HasSortFields for HazardListingSortData {
@@ -109,6 +117,9 @@ impl HasSortFields for HazardListingSortData {
     fn order_by_field(&self) -> &Option<OrderEnum> {
         &self.order_by
     }
+    fn sort_field(&self) -> &Option<String> {
+        &self.sort
+    }
 }
 
 impl PaginatedRequestExt for HazardListingPaginatedRequest {
@@ -1,3 +1,4 @@
+use crate::models::foundry::inline_markup::adjust_description_rolls;
 use crate::models::hazard::hazard_component::hazard_core::HazardEssentialData;
 use crate::models::hazard::hazard_field_filter::{HazardComplexityEnum, HazardFieldFilters};
 use crate::models::shared::action::Action;
@@ -7,6 +8,7 @@ use crate::models::shared::trait_data::TraitData;
 use crate::traits::filterable::Filterable;
 use crate::traits::has_complexity::HasComplexity;
 use crate::traits::has_level::HasLevel;
+use crate::traits::proficiency_without_level::{ProficiencyWithoutLevel, pwl_mod_from_level};
 use crate::traits::traits_enrichable::TraitsEnrichable;
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgRow;
@@ -82,6 +84,42 @@ impl HasLevel for Hazard {
         self.essential.level
     }
 }
+impl ProficiencyWithoutLevel for Hazard {
+    fn pwl_mod(&self) -> u64 {
+        pwl_mod_from_level(self.essential.level)
+    }
+
+    /// Lowers AC, saving throws, stealth and the DCs written in the descriptions.
+    /// Hardness and HP do not depend on the level.
+    fn remove_pwl_mod(self, pwl_mod: u64) -> Self {
+        let modifier = -i64::try_from(pwl_mod).unwrap_or(i64::MAX);
+        let mut hz = self;
+        let essential = &mut hz.essential;
+        essential.ac += modifier;
+        essential.stealth += modifier;
+        for save in [
+            &mut essential.fortitude,
+            &mut essential.reflex,
+            &mut essential.will,
+        ] {
+            *save = save.map(|x| x + modifier);
+        }
+        for description in [
+            &mut essential.description,
+            &mut essential.disable_description,
+            &mut essential.reset_description,
+            &mut essential.routine_description,
+        ] {
+            *description = adjust_description_rolls(description, modifier, 0);
+        }
+        for action in &mut hz.actions {
+            action.core_action.description =
+                adjust_description_rolls(&action.core_action.description, modifier, 0);
+        }
+        hz
+    }
+}
+
 impl HasComplexity for Hazard {
     fn complexity(&self) -> HazardComplexityEnum {
         self.essential.complexity
@@ -137,10 +175,10 @@ impl Filterable for Hazard {
 
     fn does_it_pass_string_filters(&self, filters: &Self::FilterImpl) -> bool {
         filters.name_filter.as_ref().is_none_or(|name| {
-            self.essential
-                .name
-                .to_lowercase()
-                .contains(name.to_lowercase().as_str())
+            filters
+                .name_match_mode
+                .unwrap_or_default()
+                .is_matching(name, self.essential.name.as_str())
         }) && filters.trait_whitelist_filter.as_ref().is_none_or(|x| {
             x.iter().any(|filter_trait| {
                 self.traits.iter().any(|cr_trait| {
@@ -159,6 +197,14 @@ impl Filterable for Hazard {
                         .eq(filter_trait.to_lowercase().as_str())
                 })
             })
+        }) && filters.trait_expression_filter.as_ref().is_none_or(|expr| {
+            expr.is_satisfied_by(
+                &self
+                    .traits
+                    .iter()
+                    .map(|cr_trait| cr_trait.name.as_str())
+                    .collect::<Vec<_>>(),
+            )
         })
     }
 
@@ -214,3 +260,49 @@ impl TraitsEnrichable for Hazard {
         "hazard"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn pwl_removes_the_level_from_proficiency_based_stats_only() {
+        let hazard: Hazard = serde_json::from_value(json!({
+            "essential": {
+                "id": 1, "name": "Spear Launcher", "ac": 18, "hardness": 8, "has_health": true,
+                "hp": 32, "stealth": 10, "stealth_detail": "trained", "description": "",
+                "disable_description":
+                    "@Check[thievery|dc:15]{Thievery DC 15} to disable the trigger plate.",
+                "reset_description": "", "routine_description": "", "complexity": "Simple",
+                "level": 2, "license": "ORC", "remaster": true, "source": "GM Core",
+                "will": null, "reflex": 5, "fortitude": 11, "rarity": "Common", "size": "Medium"
+            },
+            "traits": [],
+            "actions": [],
+            "game_system": "pf"
+        }))
+        .unwrap();
+        assert_eq!(hazard.clone().with_pwl(false), hazard);
+
+        let pwl = hazard.convert_to_pwl();
+        let essential = &pwl.essential;
+        assert_eq!(
+            (
+                essential.ac,
+                essential.stealth,
+                essential.hardness,
+                essential.hp
+            ),
+            (16, 8, 8, 32)
+        );
+        assert_eq!(
+            (essential.fortitude, essential.reflex, essential.will),
+            (Some(9), Some(3), None)
+        );
+        assert_eq!(
+            essential.disable_description,
+            "@Check[thievery|dc:13]{Thievery DC 13} to disable the trigger plate."
+        );
+    }
+}
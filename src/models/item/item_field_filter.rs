@@ -1,17 +1,23 @@
 use crate::models::item::item_metadata::type_enum::ItemTypeEnum;
+use crate::models::shared::name_match_mode_enum::NameMatchModeEnum;
 use crate::models::shared::pf_version_enum::GameSystemVersionEnum;
 use crate::models::shared::rarity_enum::RarityEnum;
 use crate::models::shared::size_enum::SizeEnum;
+use crate::models::shared::trait_filter_expression::TraitFilterExpression;
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize, Deserialize, IntoParams, ToSchema, Default)]
+#[derive(Serialize, Deserialize, IntoParams, ToSchema, Default, Clone)]
 pub struct ItemFieldFilters {
     pub name_filter: Option<String>,
+    pub name_match_mode: Option<NameMatchModeEnum>,
+    #[schema(example = "invisibility")]
+    pub text_search_filter: Option<String>,
     pub category_filter: Option<Vec<String>>,
     pub source_filter: Option<Vec<String>>,
     pub trait_whitelist_filter: Option<Vec<String>>,
     pub trait_blacklist_filter: Option<Vec<String>>,
+    pub trait_expression_filter: Option<TraitFilterExpression>,
 
     #[schema(minimum = 0., example = 0.)]
     pub min_bulk_filter: Option<f64>,
@@ -139,9 +139,10 @@ impl Filterable for Item {
 
     fn does_it_pass_string_filters(&self, filters: &Self::FilterImpl) -> bool {
         filters.name_filter.as_ref().is_none_or(|name| {
-            self.name
-                .to_lowercase()
-                .contains(name.to_lowercase().as_str())
+            filters
+                .name_match_mode
+                .unwrap_or_default()
+                .is_matching(name, self.name.as_str())
         }) && filters.category_filter.as_ref().is_none_or(|x| {
             x.iter().any(|cat| {
                 self.category
@@ -174,6 +175,14 @@ impl Filterable for Item {
                         .eq(filter_trait.to_lowercase().as_str())
                 })
             })
+        }) && filters.trait_expression_filter.as_ref().is_none_or(|expr| {
+            expr.is_satisfied_by(
+                &self
+                    .traits
+                    .iter()
+                    .map(|item_trait| item_trait.name.as_str())
+                    .collect::<Vec<_>>(),
+            )
         })
     }
 
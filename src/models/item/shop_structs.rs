@@ -1,14 +1,18 @@
 use crate::models::item::item_metadata::type_enum::ItemTypeEnum;
 use crate::models::routers_validator_structs::{Dice, OrderEnum, PaginatedRequest};
+use crate::models::shared::dice_expression::DiceExpression;
 use crate::models::shared::pf_version_enum::GameSystemVersionEnum;
 use crate::models::shared::rarity_enum::RarityEnum;
 use crate::models::shared::size_enum::SizeEnum;
+use crate::models::shared::trait_filter_expression::TraitFilterExpression;
 use crate::traits::template_enum::{GenericTemplate, ItemTemplate};
 use crate::traits::url::has_sort_fields::HasSortFields;
 use crate::traits::url::paginated_request_ext::PaginatedRequestExt;
 pub use schemas::*;
 use serde::{Deserialize, Serialize};
-use strum::{Display, EnumIter};
+#[allow(unused_imports)] // Used in schema
+use serde_json::json;
+use strum::{Display, EnumIter, EnumString};
 use utoipa::{IntoParams, ToSchema};
 
 #[derive(Serialize, Deserialize, ToSchema, PartialEq, Clone, Copy)]
@@ -120,6 +124,7 @@ mod schemas {
         pub source_filter: Option<Vec<String>>,
         pub trait_whitelist_filter: Option<Vec<String>>,
         pub trait_blacklist_filter: Option<Vec<String>>,
+        pub trait_expression_filter: Option<TraitFilterExpression>,
         pub type_filter: Option<Vec<ItemTypeEnum>>,
         pub rarity_filter: Option<Vec<RarityEnum>>,
         pub size_filter: Option<Vec<SizeEnum>>,
@@ -128,10 +133,10 @@ mod schemas {
         pub min_level: Option<u8>,
         #[schema(minimum = 0, maximum = 30, example = 5)]
         pub max_level: Option<u8>,
-        #[schema(min_items = 1)]
-        pub equippable_dices: Vec<Dice>,
-        #[schema(min_items = 1)]
-        pub consumable_dices: Vec<Dice>,
+        #[schema(value_type = Vec<String>, min_items = 1, example = json!(["1d4+1"]))]
+        pub equippable_dices: Vec<DiceExpression>,
+        #[schema(value_type = Vec<String>, min_items = 1, example = json!(["2d6", "4d6kh3"]))]
+        pub consumable_dices: Vec<DiceExpression>,
 
         #[schema(minimum = 0, maximum = 100, example = 25)]
         pub equipment_percentage: Option<u8>,
@@ -161,6 +166,7 @@ mod schemas {
         pub item_table_fields_filter: ItemTableFieldsFilter,
         pub trait_whitelist_filter: Vec<String>,
         pub trait_blacklist_filter: Vec<String>,
+        pub trait_expression_filter: Option<TraitFilterExpression>,
         pub n_of_equipment: i64,
         pub n_of_consumables: i64,
         pub n_of_weapons: i64,
@@ -169,8 +175,19 @@ mod schemas {
     }
 
     #[derive(
-        Serialize, Deserialize, ToSchema, Default, Eq, PartialEq, Hash, Clone, Display, Copy,
+        Serialize,
+        Deserialize,
+        ToSchema,
+        Default,
+        Eq,
+        PartialEq,
+        Hash,
+        Clone,
+        Display,
+        EnumString,
+        Copy,
     )]
+    #[strum(ascii_case_insensitive)]
     pub enum ItemSortEnum {
         #[serde(alias = "id", alias = "ID")]
         Id,
@@ -187,6 +204,15 @@ mod schemas {
         Rarity,
         #[serde(alias = "source", alias = "SOURCE")]
         Source,
+        #[serde(alias = "price", alias = "PRICE")]
+        Price,
+        #[serde(alias = "bulk", alias = "BULK")]
+        Bulk,
+        #[serde(alias = "hp", alias = "HP")]
+        Hp,
+        /// AC bonus of armors and shields, other items come last
+        #[serde(alias = "ac", alias = "AC")]
+        Ac,
     }
 
     #[derive(Serialize, Deserialize, IntoParams, ToSchema, Eq, PartialEq, Hash, Default)]
@@ -194,6 +220,9 @@ mod schemas {
         // Optional here for swagger, kinda bad but w/e
         pub sort_by: Option<ItemSortEnum>,
         pub order_by: Option<OrderEnum>,
+        /// Multi-key sort, ex `level:desc,price:asc,name:asc`.
+        /// When given, `sort_by` and `order_by` are ignored.
+        pub sort: Option<String>,
     }
 
     #[derive(Serialize, Deserialize, IntoParams, Eq, PartialEq, Hash)]
@@ -211,6 +240,9 @@ impl HasSortFields for ShopSortData {
     fn order_by_field(&self) -> &Option<OrderEnum> {
         &self.order_by
     }
+    fn sort_field(&self) -> &Option<String> {
+        &self.sort
+    }
 }
 
 impl PaginatedRequestExt for ShopPaginatedRequest {
@@ -226,6 +258,83 @@ impl PaginatedRequestExt for ShopPaginatedRequest {
     }
 }
 
+/// `RandomShopData` as written by share format version 1, before dice expressions
+#[derive(Deserialize)]
+pub struct LegacyRandomShopData<T: GenericTemplate + ItemTemplate> {
+    category_filter: Option<Vec<String>>,
+    source_filter: Option<Vec<String>>,
+    trait_whitelist_filter: Option<Vec<String>>,
+    trait_blacklist_filter: Option<Vec<String>>,
+    trait_expression_filter: Option<TraitFilterExpression>,
+    type_filter: Option<Vec<ItemTypeEnum>>,
+    rarity_filter: Option<Vec<RarityEnum>>,
+    size_filter: Option<Vec<SizeEnum>>,
+    min_level: Option<u8>,
+    max_level: Option<u8>,
+    equippable_dices: Vec<Dice>,
+    consumable_dices: Vec<Dice>,
+    equipment_percentage: Option<u8>,
+    weapon_percentage: Option<u8>,
+    armor_percentage: Option<u8>,
+    shield_percentage: Option<u8>,
+    shop_template: Option<T>,
+    game_system_version: Option<GameSystemVersionEnum>,
+}
+
+impl<T: GenericTemplate + ItemTemplate> From<LegacyRandomShopData<T>> for RandomShopData<T> {
+    fn from(legacy: LegacyRandomShopData<T>) -> Self {
+        Self {
+            category_filter: legacy.category_filter,
+            source_filter: legacy.source_filter,
+            trait_whitelist_filter: legacy.trait_whitelist_filter,
+            trait_blacklist_filter: legacy.trait_blacklist_filter,
+            trait_expression_filter: legacy.trait_expression_filter,
+            type_filter: legacy.type_filter,
+            rarity_filter: legacy.rarity_filter,
+            size_filter: legacy.size_filter,
+            min_level: legacy.min_level,
+            max_level: legacy.max_level,
+            equippable_dices: legacy
+                .equippable_dices
+                .iter()
+                .map(DiceExpression::from)
+                .collect(),
+            consumable_dices: legacy
+                .consumable_dices
+                .iter()
+                .map(DiceExpression::from)
+                .collect(),
+            equipment_percentage: legacy.equipment_percentage,
+            weapon_percentage: legacy.weapon_percentage,
+            armor_percentage: legacy.armor_percentage,
+            shield_percentage: legacy.shield_percentage,
+            shop_template: legacy.shop_template,
+            game_system_version: legacy.game_system_version,
+        }
+    }
+}
+
+impl<T: GenericTemplate + ItemTemplate> RandomShopData<T> {
+    pub fn is_valid(&self) -> bool {
+        let percentages = [
+            self.equipment_percentage,
+            self.weapon_percentage,
+            self.armor_percentage,
+            self.shield_percentage,
+        ];
+        percentages
+            .iter()
+            .flatten()
+            .map(|x| u16::from(*x))
+            .sum::<u16>()
+            <= 100
+            && self
+                .min_level
+                .zip(self.max_level)
+                .is_none_or(|(min, max)| min <= max)
+    }
+}
+
 impl<T> From<T> for ShopTemplateData
 where
     T: GenericTemplate + ToString + ItemTemplate,
@@ -3,6 +3,7 @@ use crate::models::item::item_metadata::type_enum::WeaponTypeEnum;
 use crate::models::item::item_struct::Item;
 use crate::models::routers_validator_structs::Dice;
 use crate::models::shared::action::Action;
+use crate::models::shared::dice_expression::DiceExpression;
 use crate::models::shared::range_data::RangeData;
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgRow;
@@ -63,10 +64,9 @@ impl Weapon {
         self.weapon_data
             .damage_data
             .iter()
-            .map(|x| {
-                let b = x.bonus_dmg as f64;
-                x.clone().dice.map_or(0, |dice| dice.get_avg_dmg(b))
-            })
+            // Damage without dice is not counted, as it has always been
+            .filter(|x| x.dice.is_some())
+            .map(|x| x.damage_expression().average().floor() as i64)
             .sum()
     }
 }
@@ -80,6 +80,13 @@ pub struct DamageData {
     pub dice: Option<Dice>,
 }
 
+impl DamageData {
+    /// Dice and bonus as a single expression, ex `2d6+4`
+    pub fn damage_expression(&self) -> DiceExpression {
+        DiceExpression::from_dice_and_bonus(self.dice.as_ref(), self.bonus_dmg)
+    }
+}
+
 impl<'r> FromRow<'r, PgRow> for DamageData {
     fn from_row(row: &'r PgRow) -> Result<Self, Error> {
         Ok(Self {
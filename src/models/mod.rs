@@ -2,6 +2,7 @@ pub mod bestiary_structs;
 pub mod creature;
 pub mod db;
 pub mod encounter_structs;
+pub mod foundry;
 pub mod hazard;
 pub mod item;
 pub mod npc;
@@ -9,5 +10,10 @@ pub mod ordered_float_to_schema;
 pub mod response_data;
 pub mod routers_validator_structs;
 pub mod scales_struct;
+pub mod share_envelope;
+pub mod share_limits;
+pub mod share_qr;
+pub mod share_signing;
 pub mod shared;
 pub mod shearable_data;
+pub mod stat_block;
@@ -11,7 +11,10 @@ use crate::models::item::item_struct::Item;
 use crate::models::item::shield_struct::ShieldData;
 use crate::models::item::weapon_struct::WeaponData;
 use crate::models::shared::game_system_enum::GameSystem;
+use crate::models::shared::listing_cursor::KeysetCursor;
+use crate::models::shared::text_search::TextSearchMatch;
 use crate::services::url_calculator::next_url;
+use crate::traits::proficiency_without_level::{ProficiencyWithoutLevel, pwl_mod_from_level};
 use crate::traits::response::listing_response::ListingResponse;
 use crate::traits::url::paginated_request_ext::PaginatedRequestExt;
 use serde::{Deserialize, Serialize};
@@ -28,12 +31,25 @@ pub struct CreatureResponseDataModifiers {
     pub spellcasting_data: Option<bool>,
 }
 
+impl CreatureResponseDataModifiers {
+    /// Every section of the creature, as needed to export or print it whole
+    pub const fn all(is_pwl_on: bool) -> Self {
+        Self {
+            is_pwl_on: Some(is_pwl_on),
+            extra_data: Some(true),
+            combat_data: Some(true),
+            spellcasting_data: Some(true),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, ToSchema, Default)]
 pub struct HazardListingResponse {
-    results: Option<Vec<ResponseHazard>>,
+    pub(crate) results: Option<Vec<ResponseHazard>>,
     count: usize,
     total: usize,
     next: Option<String>,
+    search_matches: Option<Vec<TextSearchMatch>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, ToSchema, PartialEq, Eq, Debug)]
@@ -53,12 +69,26 @@ impl From<(Hazard, GameSystem)> for ResponseHazard {
     }
 }
 
+impl ProficiencyWithoutLevel for ResponseHazard {
+    fn pwl_mod(&self) -> u64 {
+        self.core_hazard.pwl_mod()
+    }
+
+    fn remove_pwl_mod(self, pwl_mod: u64) -> Self {
+        Self {
+            core_hazard: self.core_hazard.remove_pwl_mod(pwl_mod),
+            game: self.game,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, ToSchema, Default)]
 pub struct BestiaryResponse {
-    results: Option<Vec<ResponseCreature>>,
+    pub(crate) results: Option<Vec<ResponseCreature>>,
     count: usize,
     total: usize,
     next: Option<String>,
+    search_matches: Option<Vec<TextSearchMatch>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, ToSchema, Hash, PartialEq, Eq, Debug)]
@@ -84,6 +114,29 @@ impl From<Creature> for ResponseCreature {
     }
 }
 
+impl ProficiencyWithoutLevel for ResponseCreature {
+    fn pwl_mod(&self) -> u64 {
+        pwl_mod_from_level(self.core_data.essential.base_level)
+    }
+
+    fn remove_pwl_mod(self, pwl_mod: u64) -> Self {
+        Self::from(Creature::from(self).remove_pwl_mod(pwl_mod))
+    }
+}
+
+impl From<ResponseCreature> for Creature {
+    fn from(cr: ResponseCreature) -> Self {
+        Self {
+            core_data: cr.core_data,
+            variant_data: cr.variant_data,
+            extra_data: cr.extra_data,
+            combat_data: cr.combat_data,
+            spellcaster_data: cr.spellcaster_data,
+            game_system: cr.game,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, ToSchema, PartialEq, Eq, Debug)]
 pub struct ResponseItem {
     pub core_item: Item,
@@ -127,6 +180,7 @@ pub struct ShopListingResponse {
     pub(crate) total: usize,
     pub(crate) game: GameSystem,
     pub(crate) next: Option<String>,
+    pub(crate) search_matches: Option<Vec<TextSearchMatch>>,
 }
 
 impl ShopListingResponse {
@@ -137,6 +191,7 @@ impl ShopListingResponse {
             total: 0,
             game: game_system,
             next: None,
+            search_matches: None,
         }
     }
 }
@@ -149,6 +204,7 @@ impl ListingResponse for ShopListingResponse {
         count: usize,
         next: Option<String>,
         total: usize,
+        search_matches: Option<Vec<TextSearchMatch>>,
     ) -> Self {
         Self {
             results: Some(results),
@@ -156,6 +212,7 @@ impl ListingResponse for ShopListingResponse {
             next,
             total,
             game: GameSystem::Starfinder,
+            search_matches,
         }
     }
 }
@@ -168,12 +225,14 @@ impl ListingResponse for BestiaryResponse {
         count: usize,
         next: Option<String>,
         total: usize,
+        search_matches: Option<Vec<TextSearchMatch>>,
     ) -> Self {
         Self {
             results: Some(results.into_iter().map(ResponseCreature::from).collect()),
             count,
             next,
             total,
+            search_matches,
         }
     }
 }
@@ -186,30 +245,50 @@ impl ListingResponse for HazardListingResponse {
         count: usize,
         next: Option<String>,
         total: usize,
+        search_matches: Option<Vec<TextSearchMatch>>,
     ) -> Self {
         Self {
             results: Some(results),
             count,
             next,
             total,
+            search_matches,
         }
     }
 }
 
+/// Total count, rows, search matches and the sort keys of the last row of a listing page
+pub type ListingPage<T> = (u32, Vec<T>, Vec<TextSearchMatch>, Option<String>);
+
+/// `last_keys` are the sort keys of the last row of the page, as a JSON array.
+/// They become the page token of the `next` url.
 pub fn convert_result_to_response<P, R>(
     pagination: &P,
-    result: anyhow::Result<(u32, Vec<R::Item>)>,
+    result: anyhow::Result<ListingPage<R::Item>>,
 ) -> R
 where
     P: PaginatedRequestExt,
     R: ListingResponse,
 {
-    match result {
-        Ok((total, items)) => {
+    match result.and_then(|page| Ok((pagination.sort_spec()?, page))) {
+        Ok((sort_spec, (total, items, search_matches, last_keys))) => {
             let count = items.len();
+            let page_token = last_keys.and_then(|keys| {
+                KeysetCursor {
+                    sort: sort_spec.clone(),
+                    keys: serde_json::from_str(&keys).ok()?,
+                }
+                .to_token()
+                .ok()
+            });
+            let next_cursor = pagination
+                .paginated_request()
+                .cursor
+                .saturating_add(count as u32);
             let next = (count >= pagination.paginated_request().page_size.unsigned_abs() as usize)
-                .then(|| next_url(pagination, count as u32));
-            R::from_results(items, count, next, total as usize)
+                .then(|| next_url(pagination, &sort_spec, next_cursor, page_token.as_deref()));
+            let search_matches = (!search_matches.is_empty()).then_some(search_matches);
+            R::from_results(items, count, next, total as usize, search_matches)
         }
         Err(_) => R::default(),
     }
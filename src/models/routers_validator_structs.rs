@@ -1,11 +1,13 @@
-use nanorand::{Rng, WyRand};
+use crate::models::shared::dice_expression::DiceExpression;
 use serde::{Deserialize, Serialize};
 #[allow(unused_imports)]
 use serde_json::json;
 use strum::Display;
 use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize, Deserialize, ToSchema, Default, Eq, PartialEq, Hash, Clone, Display, Copy)]
+#[derive(
+    Serialize, Deserialize, ToSchema, Default, Eq, PartialEq, Hash, Clone, Display, Copy, Debug,
+)]
 pub enum OrderEnum {
     #[default]
     #[serde(alias = "ascending", alias = "ASCENDING")]
@@ -20,6 +22,10 @@ pub struct PaginatedRequest {
     pub cursor: u32,
     #[schema(minimum = -1, maximum = 100, example = 100)]
     pub page_size: i16,
+    /// Opaque token from a `next` url. When given the page starts right after the last row of
+    /// the previous one instead of skipping `cursor` rows.
+    #[serde(default)]
+    pub page_token: Option<String>,
 }
 
 impl Default for PaginatedRequest {
@@ -27,10 +33,12 @@ impl Default for PaginatedRequest {
         Self {
             cursor: 0,
             page_size: 100,
+            page_token: None,
         }
     }
 }
 
+/// Dice as stored, `DiceExpression` handles anything more than `NdM`
 #[derive(Serialize, Deserialize, ToSchema, Eq, PartialEq, Hash, Clone, Debug)]
 pub struct Dice {
     #[schema(minimum = 0, maximum = 255, example = 1)]
@@ -45,27 +53,11 @@ impl Dice {
     /// It returns the sum of `n_of_dices` rolls.
     /// IT SHOULD NEVER BE <1, OTHERWISE WE BREAK THE CONTRACT OF THE METHOD.
     pub fn roll(&self) -> u32 {
-        let mut roll_result = 0_u32;
-        let n_of_dices = u32::try_from(self.n_of_dices).unwrap_or(1);
-        let dice_size = u32::try_from(self.dice_size).unwrap_or(1);
-        for _ in 0..n_of_dices {
-            roll_result += WyRand::new().generate_range(1..=dice_size);
-        }
-        roll_result
+        u32::try_from(DiceExpression::from(self).roll()).unwrap_or(0)
     }
 
     pub fn get_avg_dmg(&self, bonus_dmg: f64) -> i64 {
-        // avg dice value is
-        // AVG = (((M+1)/2)∗N)+B
-        //
-        // M = max value of the dice
-        // N = number of dices
-        // B = bonus dmg
-        let m = f64::from(self.dice_size);
-        let n = f64::from(self.n_of_dices);
-        let b = bonus_dmg;
-        let avg: f64 = f64::midpoint(m, 1.).mul_add(n, b);
-        avg.floor() as i64
+        (DiceExpression::from(self).average() + bonus_dmg).floor() as i64
     }
 
     pub const fn from_optional_dice_number_and_size(
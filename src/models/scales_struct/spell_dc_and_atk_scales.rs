@@ -1,3 +1,4 @@
+use crate::traits::proficiency_without_level::{ProficiencyWithoutLevel, pwl_mod_from_level};
 use sqlx::FromRow;
 #[derive(Default, Eq, PartialEq, Clone, FromRow)]
 pub struct SpellDcAndAtkScales {
@@ -16,3 +17,22 @@ pub struct SpellDcAndAtkScales {
     #[sqlx(try_from = "i32")]
     pub moderate_atk_bonus: i64,
 }
+
+impl ProficiencyWithoutLevel for SpellDcAndAtkScales {
+    fn pwl_mod(&self) -> u64 {
+        pwl_mod_from_level(self.level)
+    }
+
+    fn remove_pwl_mod(self, pwl_mod: u64) -> Self {
+        let modifier = -i64::try_from(pwl_mod).unwrap_or(i64::MAX);
+        Self {
+            level: self.level,
+            extreme_dc: self.extreme_dc + modifier,
+            extreme_atk_bonus: self.extreme_atk_bonus + modifier,
+            high_dc: self.high_dc + modifier,
+            high_atk_bonus: self.high_atk_bonus + modifier,
+            moderate_dc: self.moderate_dc + modifier,
+            moderate_atk_bonus: self.moderate_atk_bonus + modifier,
+        }
+    }
+}
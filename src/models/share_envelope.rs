@@ -0,0 +1,563 @@
+use crate::models::share_limits::ShareDecodeLimits;
+use crate::models::share_signing::{ShareSigningKeys, ShareVerification};
+use crate::models::shared::game_system_enum::GameSystem;
+use crate::models::shearable_data::{
+    LegacyShareableEncounter, LegacyShareableGeneratorRecipe, ShareableEncounter,
+    ShareableGeneratorRecipe, ShareableNpcList, ShareableShop,
+};
+use crate::traits::base64::base64_decode::Base64Decode;
+use crate::traits::base64::base64_encode::Base64Encode;
+use anyhow::{Result, bail, ensure};
+use base64ct::{Base64Url, Encoding};
+use postcard::take_from_bytes;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use utoipa::ToSchema;
+
+/// First byte of every share code written with an envelope.
+///
+/// Codes written before envelopes existed start with the length of a name instead,
+/// they are told apart by also checking that a valid header follows.
+pub const SHARE_MAGIC_BYTE: u8 = 0xBB;
+/// Version of the payload structs written by `encode_share`.
+/// Version 2 writes the dice of shop recipes as expressions, the other payloads are unchanged.
+pub const SHARE_FORMAT_VERSION: u8 = 2;
+/// Version reported for codes written before envelopes existed
+pub const LEGACY_SHARE_FORMAT_VERSION: u8 = 0;
+
+#[derive(Serialize, Deserialize, ToSchema, Display, Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ShareKind {
+    Encounter,
+    Shop,
+    NpcList,
+    GeneratorRecipe,
+}
+
+/// Describes the payload that follows it, it is never compressed separately from it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ShareHeader {
+    pub magic: u8,
+    pub version: u8,
+    pub kind: ShareKind,
+    pub game: GameSystem,
+}
+
+impl ShareHeader {
+    pub const fn new(kind: ShareKind, game: GameSystem) -> Self {
+        Self {
+            magic: SHARE_MAGIC_BYTE,
+            version: SHARE_FORMAT_VERSION,
+            kind,
+            game,
+        }
+    }
+}
+
+impl Base64Decode for ShareHeader {}
+
+#[derive(Serialize)]
+struct ShareEnvelope<T> {
+    header: ShareHeader,
+    payload: T,
+}
+
+impl<T: Serialize + Send + Sync> Base64Encode for ShareEnvelope<T> {}
+
+/// Something that can be shared. New versions of a payload keep reading the previous ones,
+/// upgrading them to the latest struct.
+pub trait SharePayload: Serialize + DeserializeOwned + Send + Sync {
+    const KIND: ShareKind;
+
+    /// Reads a payload written with the given format version,
+    /// payloads that did not change since version 1 are read as they are
+    fn from_versioned_bytes(version: u8, bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            (1..=SHARE_FORMAT_VERSION).contains(&version),
+            "Unsupported share format version {version}"
+        );
+        from_exact_bytes(bytes)
+    }
+
+    /// Reads a payload written before envelopes existed
+    fn from_legacy_bytes(bytes: &[u8]) -> Result<Self> {
+        from_exact_bytes(bytes)
+    }
+
+    /// Checks the element counts and quantities of the decoded payload
+    fn check_limits(&self, limits: &ShareDecodeLimits) -> Result<()>;
+}
+
+impl SharePayload for ShareableEncounter {
+    const KIND: ShareKind = ShareKind::Encounter;
+
+    fn from_legacy_bytes(bytes: &[u8]) -> Result<Self> {
+        from_exact_bytes(bytes)
+            .or_else(|_| from_exact_bytes::<LegacyShareableEncounter>(bytes).map(Self::from))
+    }
+
+    fn check_limits(&self, limits: &ShareDecodeLimits) -> Result<()> {
+        ShareDecodeLimits::check_element_count(
+            "creatures",
+            self.creatures_data.len(),
+            limits.max_creatures,
+        )?;
+        ShareDecodeLimits::check_element_count(
+            "hazards",
+            self.hazards_data.len(),
+            limits.max_hazards,
+        )?;
+        limits.check_quantities("creatures", self.creatures_data.iter().map(|x| x.qty))?;
+        limits.check_quantities("hazards", self.hazards_data.iter().map(|x| x.qty))?;
+        Ok(())
+    }
+}
+
+impl SharePayload for ShareableShop {
+    const KIND: ShareKind = ShareKind::Shop;
+
+    fn check_limits(&self, limits: &ShareDecodeLimits) -> Result<()> {
+        ShareDecodeLimits::check_element_count("items", self.items_data.len(), limits.max_items)?;
+        limits.check_quantities("items", self.items_data.iter().map(|x| x.qty))?;
+        Ok(())
+    }
+}
+
+impl SharePayload for ShareableNpcList {
+    const KIND: ShareKind = ShareKind::NpcList;
+
+    fn check_limits(&self, limits: &ShareDecodeLimits) -> Result<()> {
+        ShareDecodeLimits::check_element_count("npcs", self.npcs_data.len(), limits.max_npcs)?;
+        Ok(())
+    }
+}
+
+impl SharePayload for ShareableGeneratorRecipe {
+    const KIND: ShareKind = ShareKind::GeneratorRecipe;
+
+    fn from_versioned_bytes(version: u8, bytes: &[u8]) -> Result<Self> {
+        match version {
+            1 => from_exact_bytes::<LegacyShareableGeneratorRecipe>(bytes).map(Self::from),
+            SHARE_FORMAT_VERSION => from_exact_bytes(bytes),
+            _ => bail!("Unsupported share format version {version}"),
+        }
+    }
+
+    fn from_legacy_bytes(_: &[u8]) -> Result<Self> {
+        bail!("Generator recipes have always been shared with an envelope")
+    }
+
+    fn check_limits(&self, limits: &ShareDecodeLimits) -> Result<()> {
+        for count in self.filter_lengths() {
+            ShareDecodeLimits::check_element_count(
+                "filter values",
+                count,
+                limits.max_filter_values,
+            )?;
+        }
+        for expression in self.trait_expressions() {
+            ShareDecodeLimits::check_element_count(
+                "trait expression nodes",
+                expression.node_count(),
+                limits.max_expression_nodes,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A decoded share code, upgraded to the latest payload struct
+#[derive(Clone, Debug)]
+pub struct DecodedShare<T> {
+    /// Version the code was written with, `LEGACY_SHARE_FORMAT_VERSION` for codes without envelope
+    pub version: u8,
+    /// Missing for codes without envelope
+    pub game: Option<GameSystem>,
+    pub verification: ShareVerification,
+    pub payload: T,
+}
+
+/// Any payload a share code can hold, for callers that do not know what they were given
+#[derive(Clone)]
+pub enum SharedPayload {
+    Encounter(ShareableEncounter),
+    Shop(ShareableShop),
+    NpcList(ShareableNpcList),
+    GeneratorRecipe(ShareableGeneratorRecipe),
+}
+
+pub async fn encode_share<T: SharePayload>(payload: T, game: GameSystem) -> Result<String> {
+    ShareEnvelope {
+        header: ShareHeader::new(T::KIND, game),
+        payload,
+    }
+    .encode()
+    .await
+}
+
+/// Same as `encode_share`, signing the code with the first of the given keys
+pub async fn encode_signed_share<T: SharePayload>(
+    payload: T,
+    game: GameSystem,
+    keys: &ShareSigningKeys,
+) -> Result<String> {
+    Ok(keys.sign(encode_share(payload, game).await?))
+}
+
+/// Decodes a share code holding a `T`, be it enveloped or written before envelopes existed,
+/// under the default `ShareDecodeLimits`. Signed codes are not verified.
+pub async fn decode_share<T: SharePayload>(share_code: &str) -> Result<DecodedShare<T>> {
+    decode_verified_share(
+        share_code,
+        &ShareDecodeLimits::default(),
+        &ShareSigningKeys::default(),
+    )
+    .await
+}
+
+/// Same as `decode_share` under the given limits, verifying codes signed with one of the given keys
+pub async fn decode_verified_share<T: SharePayload>(
+    share_code: &str,
+    limits: &ShareDecodeLimits,
+    keys: &ShareSigningKeys,
+) -> Result<DecodedShare<T>> {
+    let (bytes, verification) = decompress_share_code(share_code, limits, keys).await?;
+    let decoded = match read_header(&bytes) {
+        Some((header, payload)) => {
+            ensure!(
+                header.kind == T::KIND,
+                "Expected a shared {}, got a shared {}",
+                T::KIND,
+                header.kind
+            );
+            DecodedShare {
+                version: header.version,
+                game: Some(header.game),
+                verification,
+                payload: T::from_versioned_bytes(header.version, payload)?,
+            }
+        }
+        None => DecodedShare {
+            version: LEGACY_SHARE_FORMAT_VERSION,
+            game: None,
+            verification,
+            payload: T::from_legacy_bytes(&bytes)?,
+        },
+    };
+    decoded.payload.check_limits(limits)?;
+    Ok(decoded)
+}
+
+/// Decodes a share code of any kind. Codes written before envelopes existed do not say what
+/// they hold, they are tried as an encounter, then as a shop and lastly as a npc list.
+pub async fn decode_any_share(share_code: &str) -> Result<DecodedShare<SharedPayload>> {
+    decode_any_share_with_limits(
+        share_code,
+        &ShareDecodeLimits::default(),
+        &ShareSigningKeys::default(),
+    )
+    .await
+}
+
+pub async fn decode_any_share_with_limits(
+    share_code: &str,
+    limits: &ShareDecodeLimits,
+    keys: &ShareSigningKeys,
+) -> Result<DecodedShare<SharedPayload>> {
+    let (bytes, verification) = decompress_share_code(share_code, limits, keys).await?;
+    let decoded = if let Some((header, payload)) = read_header(&bytes) {
+        let payload = match header.kind {
+            ShareKind::Encounter => SharedPayload::Encounter(
+                ShareableEncounter::from_versioned_bytes(header.version, payload)?,
+            ),
+            ShareKind::Shop => SharedPayload::Shop(ShareableShop::from_versioned_bytes(
+                header.version,
+                payload,
+            )?),
+            ShareKind::NpcList => SharedPayload::NpcList(ShareableNpcList::from_versioned_bytes(
+                header.version,
+                payload,
+            )?),
+            ShareKind::GeneratorRecipe => SharedPayload::GeneratorRecipe(
+                ShareableGeneratorRecipe::from_versioned_bytes(header.version, payload)?,
+            ),
+        };
+        DecodedShare {
+            version: header.version,
+            game: Some(header.game),
+            verification,
+            payload,
+        }
+    } else {
+        let payload = ShareableEncounter::from_legacy_bytes(&bytes)
+            .map(SharedPayload::Encounter)
+            .or_else(|_| ShareableShop::from_legacy_bytes(&bytes).map(SharedPayload::Shop))
+            .or_else(|_| ShareableNpcList::from_legacy_bytes(&bytes).map(SharedPayload::NpcList))?;
+        DecodedShare {
+            version: LEGACY_SHARE_FORMAT_VERSION,
+            game: None,
+            verification,
+            payload,
+        }
+    };
+    match &decoded.payload {
+        SharedPayload::Encounter(x) => x.check_limits(limits)?,
+        SharedPayload::Shop(x) => x.check_limits(limits)?,
+        SharedPayload::NpcList(x) => x.check_limits(limits)?,
+        SharedPayload::GeneratorRecipe(x) => x.check_limits(limits)?,
+    }
+    Ok(decoded)
+}
+
+async fn decompress_share_code(
+    share_code: &str,
+    limits: &ShareDecodeLimits,
+    keys: &ShareSigningKeys,
+) -> Result<(Vec<u8>, ShareVerification)> {
+    // Checked before verifying too, so that the signature is never computed over an oversized code
+    ShareDecodeLimits {
+        max_input_len: limits
+            .max_input_len
+            .saturating_add(keys.max_signature_len()),
+        ..*limits
+    }
+    .check_input_len(share_code.len())?;
+    let (share_code, verification) = keys.verify(share_code)?;
+    limits.check_input_len(share_code.len())?;
+    let compressed = Base64Url::decode_vec(share_code)?;
+    let bytes = ShareHeader::decompress_binary(compressed, limits.max_decompressed_size).await?;
+    Ok((bytes, verification))
+}
+
+/// Splits the header from the payload, if the bytes start with a valid one
+fn read_header(bytes: &[u8]) -> Option<(ShareHeader, &[u8])> {
+    let (header, payload): (ShareHeader, &[u8]) = take_from_bytes(bytes).ok()?;
+    (header.magic == SHARE_MAGIC_BYTE && (1..=SHARE_FORMAT_VERSION).contains(&header.version))
+        .then_some((header, payload))
+}
+
+/// Postcard ignores trailing bytes, they are rejected here so that a payload
+/// is not mistaken for a shorter one sharing its prefix.
+fn from_exact_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    let (value, rest) = take_from_bytes(bytes)?;
+    ensure!(
+        rest.is_empty(),
+        "The share code has unexpected trailing data"
+    );
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::creature::creature_metadata::variant_enum::CreatureVariant;
+    use crate::models::encounter_structs::{RandomCreatureData, RandomEncounterData};
+    use crate::models::share_limits::ShareDecodeError;
+    use crate::models::shared::trait_filter_expression::TraitFilterExpression;
+    use crate::models::shearable_data::{
+        SharableCreature, SharableHazard, ShareableItem, ShareableShop,
+    };
+
+    fn encounter() -> ShareableEncounter {
+        ShareableEncounter {
+            encounter_name: "Osi".to_string(),
+            creatures_data: vec![SharableCreature {
+                id: 1,
+                qty: 2,
+                variant: CreatureVariant::Elite,
+                game: GameSystem::Starfinder,
+            }],
+            hazards_data: vec![SharableHazard {
+                id: 3,
+                qty: 1,
+                game: GameSystem::Starfinder,
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn enveloped_codes_round_trip() {
+        let code = encode_share(encounter(), GameSystem::Starfinder)
+            .await
+            .unwrap();
+        let decoded = decode_share::<ShareableEncounter>(&code).await.unwrap();
+        assert_eq!(decoded.version, SHARE_FORMAT_VERSION);
+        assert_eq!(decoded.game, Some(GameSystem::Starfinder));
+        assert_eq!(decoded.payload, encounter());
+        assert_eq!(decoded.verification, ShareVerification::Unverified);
+        assert!(decode_share::<ShareableShop>(&code).await.is_err());
+        assert!(matches!(
+            decode_any_share(&code).await.unwrap().payload,
+            SharedPayload::Encounter(x) if x == encounter()
+        ));
+    }
+
+    #[tokio::test]
+    async fn signed_codes_are_verified() {
+        let keys: ShareSigningKeys = "osi:a-secret-that-is-at-least-32-bytes".parse().unwrap();
+        let code = encode_signed_share(encounter(), GameSystem::Starfinder, &keys)
+            .await
+            .unwrap();
+        let decoded = decode_verified_share::<ShareableEncounter>(
+            &code,
+            &ShareDecodeLimits::default(),
+            &keys,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            decoded.verification,
+            ShareVerification::Verified {
+                key_id: "osi".to_string()
+            }
+        );
+        assert_eq!(
+            decode_share::<ShareableEncounter>(&code)
+                .await
+                .unwrap()
+                .verification,
+            ShareVerification::Unverified
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_signed_codes_are_rejected_before_verifying() {
+        let keys: ShareSigningKeys = "osi:a-secret-that-is-at-least-32-bytes".parse().unwrap();
+        let limits = ShareDecodeLimits::default();
+        let code = format!(
+            "{}.osi.{}",
+            "A".repeat(limits.max_input_len),
+            "A".repeat(limits.max_input_len)
+        );
+        let error = decode_verified_share::<ShareableEncounter>(&code, &limits, &keys)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ShareDecodeError>(),
+            Some(ShareDecodeError::InputTooLong { length, .. }) if *length == code.len()
+        ));
+    }
+
+    #[tokio::test]
+    async fn generator_recipes_round_trip() {
+        let recipe = ShareableGeneratorRecipe::Encounter(Box::new(RandomEncounterData {
+            party_levels: vec![3, 3, 4],
+            creature_percentage: Some(80),
+            hazard_percentage: Some(20),
+            creature_data: Some(RandomCreatureData {
+                trait_expression_filter: Some("undead & !mindless".parse().unwrap()),
+                ..RandomCreatureData::default()
+            }),
+            hazard_data: None,
+            challenge: None,
+            adventure_group: None,
+        }));
+        let code = encode_share(recipe, GameSystem::Pathfinder).await.unwrap();
+        let decoded = decode_any_share(&code).await.unwrap();
+        assert!(matches!(
+            decoded.payload,
+            SharedPayload::GeneratorRecipe(ShareableGeneratorRecipe::Encounter(x))
+                if x.party_levels == vec![3, 3, 4]
+                    && x.creature_data.as_ref().unwrap().trait_expression_filter
+                        == Some("undead & !mindless".parse().unwrap())
+        ));
+    }
+
+    #[tokio::test]
+    async fn deeply_nested_recipes_are_rejected() {
+        let recipe = ShareableGeneratorRecipe::Encounter(Box::new(RandomEncounterData {
+            party_levels: vec![3],
+            creature_percentage: None,
+            hazard_percentage: None,
+            creature_data: Some(RandomCreatureData {
+                trait_expression_filter: Some(TraitFilterExpression::Trait("nested".to_string())),
+                ..RandomCreatureData::default()
+            }),
+            hazard_data: None,
+            challenge: None,
+            adventure_group: None,
+        }));
+        let code = encode_share(recipe, GameSystem::Pathfinder).await.unwrap();
+        let bytes = ShareHeader::decompress_binary(
+            Base64Url::decode_vec(&code).unwrap(),
+            ShareDecodeLimits::DEFAULT.max_decompressed_size,
+        )
+        .await
+        .unwrap();
+        // Wraps the trait in AllOf groups of a single child, 2 bytes per level
+        let leaf = [&[0, 6][..], b"nested"].concat();
+        let pos = bytes.windows(leaf.len()).position(|x| x == leaf).unwrap();
+        for (levels, is_ok) in [(1, true), (30_000, false)] {
+            let crafted = [&bytes[..pos], &[1, 1].repeat(levels), &bytes[pos..]].concat();
+            let code = Base64Url::encode_string(
+                &ShareableShop::compress_binary_data(crafted).await.unwrap(),
+            );
+            assert_eq!(decode_any_share(&code).await.is_ok(), is_ok);
+            assert_eq!(
+                decode_share::<ShareableGeneratorRecipe>(&code)
+                    .await
+                    .is_ok(),
+                is_ok
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn recipes_with_too_many_filter_values_are_rejected() {
+        let recipe = |n: usize| {
+            ShareableGeneratorRecipe::Encounter(Box::new(RandomEncounterData {
+                party_levels: vec![3],
+                creature_percentage: None,
+                hazard_percentage: None,
+                creature_data: Some(RandomCreatureData {
+                    source_filter: Some(vec!["Core".to_string(); n]),
+                    ..RandomCreatureData::default()
+                }),
+                hazard_data: None,
+                challenge: None,
+                adventure_group: None,
+            }))
+        };
+        let max = ShareDecodeLimits::default().max_filter_values;
+        for (n, is_ok) in [(max, true), (max + 1, false)] {
+            let code = encode_share(recipe(n), GameSystem::Pathfinder)
+                .await
+                .unwrap();
+            assert_eq!(
+                decode_share::<ShareableGeneratorRecipe>(&code)
+                    .await
+                    .is_ok(),
+                is_ok
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn legacy_codes_are_upgraded() {
+        let legacy = decode_share::<ShareableEncounter>("KLUv_QBYaQAAA09zaQIAAQIAAQECAA==")
+            .await
+            .unwrap();
+        assert_eq!(legacy.version, LEGACY_SHARE_FORMAT_VERSION);
+        assert_eq!(legacy.game, None);
+        assert_eq!(legacy.payload.creatures_data.len(), 2);
+        assert!(legacy.payload.hazards_data.is_empty());
+
+        let current =
+            decode_share::<ShareableEncounter>("KLUv_QBYoQAAA09zaQIAAQIAAQECAAIAAQABAQA=")
+                .await
+                .unwrap();
+        assert_eq!(current.payload.hazards_data.len(), 2);
+
+        let shop = decode_any_share("KLUv_QBYWQAAA09zaQIAAQABAQA=")
+            .await
+            .unwrap();
+        assert!(matches!(
+            shop.payload,
+            SharedPayload::Shop(x) if x.items_data[1] == ShareableItem {
+                id: 1,
+                qty: 1,
+                game: GameSystem::Pathfinder,
+            }
+        ));
+    }
+}
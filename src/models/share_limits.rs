@@ -0,0 +1,291 @@
+use anyhow::bail;
+use std::fmt;
+use std::str::FromStr;
+
+/// Caps applied while decoding share codes, so that a crafted code can not make
+/// the backend allocate more than a small, known amount of memory.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ShareDecodeLimits {
+    /// Length of the base64 share code
+    pub max_input_len: usize,
+    /// Size of the payload once decompressed, in bytes
+    pub max_decompressed_size: usize,
+    pub max_creatures: usize,
+    pub max_hazards: usize,
+    pub max_items: usize,
+    pub max_npcs: usize,
+    /// Quantity of a single creature, hazard or item
+    pub max_quantity: u64,
+    /// Values of a single filter of a generator recipe
+    pub max_filter_values: usize,
+    /// Traits and groups of a single trait filter expression
+    pub max_expression_nodes: usize,
+}
+
+impl ShareDecodeLimits {
+    pub const DEFAULT: Self = Self {
+        max_input_len: 16 * 1024,
+        max_decompressed_size: 64 * 1024,
+        max_creatures: 64,
+        max_hazards: 64,
+        max_items: 512,
+        max_npcs: 256,
+        max_quantity: 100,
+        max_filter_values: 512,
+        max_expression_nodes: 256,
+    };
+
+    pub const fn check_input_len(&self, length: usize) -> Result<(), ShareDecodeError> {
+        if length > self.max_input_len {
+            return Err(ShareDecodeError::InputTooLong {
+                length,
+                max: self.max_input_len,
+            });
+        }
+        Ok(())
+    }
+
+    pub const fn check_element_count(
+        element: &'static str,
+        count: usize,
+        max: usize,
+    ) -> Result<(), ShareDecodeError> {
+        if count > max {
+            return Err(ShareDecodeError::TooManyElements {
+                element,
+                count,
+                max,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn check_quantities(
+        &self,
+        element: &'static str,
+        mut quantities: impl Iterator<Item = u64>,
+    ) -> Result<(), ShareDecodeError> {
+        quantities
+            .find(|qty| *qty > self.max_quantity)
+            .map_or(Ok(()), |quantity| {
+                Err(ShareDecodeError::QuantityTooLarge {
+                    element,
+                    quantity,
+                    max: self.max_quantity,
+                })
+            })
+    }
+}
+
+impl Default for ShareDecodeLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Written as comma separated `name=value` pairs, ex `max_items=1024,max_npcs=32`.
+/// Limits left out keep their default.
+impl FromStr for ShareDecodeLimits {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut limits = Self::DEFAULT;
+        for pair in s.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+            let Some((name, value)) = pair.split_once('=') else {
+                bail!("Share decode limits must be written as name=value")
+            };
+            let value = value.trim();
+            match name.trim() {
+                "max_input_len" => limits.max_input_len = value.parse()?,
+                "max_decompressed_size" => limits.max_decompressed_size = value.parse()?,
+                "max_creatures" => limits.max_creatures = value.parse()?,
+                "max_hazards" => limits.max_hazards = value.parse()?,
+                "max_items" => limits.max_items = value.parse()?,
+                "max_npcs" => limits.max_npcs = value.parse()?,
+                "max_quantity" => limits.max_quantity = value.parse()?,
+                "max_filter_values" => limits.max_filter_values = value.parse()?,
+                "max_expression_nodes" => limits.max_expression_nodes = value.parse()?,
+                name => bail!("Unknown share decode limit {name}"),
+            }
+        }
+        Ok(limits)
+    }
+}
+
+/// A share code exceeding one of the `ShareDecodeLimits`.
+/// Malformed codes (bad base64, compression or payload) are reported by the underlying decoders.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ShareDecodeError {
+    InputTooLong {
+        length: usize,
+        max: usize,
+    },
+    DecompressedTooLarge {
+        max: usize,
+    },
+    TooManyElements {
+        element: &'static str,
+        count: usize,
+        max: usize,
+    },
+    QuantityTooLarge {
+        element: &'static str,
+        quantity: u64,
+        max: u64,
+    },
+}
+
+impl fmt::Display for ShareDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InputTooLong { length, max } => write!(
+                f,
+                "The share code is {length} characters long, at most {max} are allowed"
+            ),
+            Self::DecompressedTooLarge { max } => {
+                write!(f, "The share code expands to more than {max} bytes")
+            }
+            Self::TooManyElements {
+                element,
+                count,
+                max,
+            } => write!(
+                f,
+                "The share code has {count} {element}, at most {max} are allowed"
+            ),
+            Self::QuantityTooLarge {
+                element,
+                quantity,
+                max,
+            } => write!(
+                f,
+                "The share code has {quantity} copies of one of its {element}, at most {max} are allowed"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ShareDecodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::share_envelope::{
+        decode_any_share_with_limits, decode_verified_share, encode_share,
+    };
+    use crate::models::share_signing::ShareSigningKeys;
+    use crate::models::shared::game_system_enum::GameSystem;
+    use crate::models::shearable_data::{ShareableItem, ShareableShop};
+    use crate::traits::base64::base64_encode::Base64Encode;
+    use base64ct::{Base64Url, Encoding};
+    use proptest::prelude::*;
+    use std::future::Future;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn shop(items: usize, qty: u64) -> ShareableShop {
+        ShareableShop {
+            shop_name: "Osi".to_string(),
+            items_data: (0..items as u64)
+                .map(|id| ShareableItem {
+                    id,
+                    qty,
+                    game: GameSystem::Pathfinder,
+                })
+                .collect(),
+        }
+    }
+
+    fn limit_error(result: anyhow::Result<impl Sized>) -> ShareDecodeError {
+        result
+            .err()
+            .and_then(|e| e.downcast_ref::<ShareDecodeError>().cloned())
+            .expect("a limit should have been exceeded")
+    }
+
+    #[test]
+    fn limits_are_parsed_over_the_defaults() {
+        let limits = ShareDecodeLimits::from_str(" max_items = 1024, max_quantity=5,").unwrap();
+        assert_eq!(
+            limits,
+            ShareDecodeLimits {
+                max_items: 1024,
+                max_quantity: 5,
+                ..ShareDecodeLimits::DEFAULT
+            }
+        );
+        assert_eq!(
+            ShareDecodeLimits::from_str("").unwrap(),
+            ShareDecodeLimits::DEFAULT
+        );
+        assert!(ShareDecodeLimits::from_str("max_items").is_err());
+        assert!(ShareDecodeLimits::from_str("max_items=-1").is_err());
+        assert!(ShareDecodeLimits::from_str("max_spells=3").is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn truncated_codes_are_rejected(items in 1..20usize, cut in 1..40usize) {
+            let code = block_on(encode_share(shop(items, 1), GameSystem::Pathfinder)).unwrap();
+            let truncated = &code[..code.len().saturating_sub(cut)];
+            prop_assert!(block_on(decode_any_share_with_limits(truncated, &ShareDecodeLimits::DEFAULT, &ShareSigningKeys::default())).is_err());
+        }
+
+        #[test]
+        fn malformed_codes_never_panic(code in "[A-Za-z0-9_=-]{0,256}") {
+            let _ = block_on(decode_any_share_with_limits(&code, &ShareDecodeLimits::DEFAULT, &ShareSigningKeys::default()));
+        }
+
+        #[test]
+        fn malformed_payloads_never_panic(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+            let compressed = block_on(ShareableShop::compress_binary_data(bytes)).unwrap();
+            let code = Base64Url::encode_string(&compressed);
+            let _ = block_on(decode_any_share_with_limits(&code, &ShareDecodeLimits::DEFAULT, &ShareSigningKeys::default()));
+        }
+
+        #[test]
+        fn oversized_inputs_are_rejected_before_decoding(extra in 1..1024usize) {
+            let code = "A".repeat(ShareDecodeLimits::DEFAULT.max_input_len + extra);
+            let rejected = matches!(
+                limit_error(block_on(decode_verified_share::<ShareableShop>(&code, &ShareDecodeLimits::DEFAULT, &ShareSigningKeys::default()))),
+                ShareDecodeError::InputTooLong { .. }
+            );
+            prop_assert!(rejected);
+        }
+
+        #[test]
+        fn decompression_bombs_are_rejected(size in 64 * 1024 + 1..1024 * 1024usize) {
+            let compressed = block_on(ShareableShop::compress_binary_data(vec![0; size])).unwrap();
+            let code = Base64Url::encode_string(&compressed);
+            let rejected = matches!(
+                limit_error(block_on(decode_verified_share::<ShareableShop>(&code, &ShareDecodeLimits::DEFAULT, &ShareSigningKeys::default()))),
+                ShareDecodeError::DecompressedTooLarge { .. }
+            );
+            prop_assert!(rejected);
+        }
+
+        #[test]
+        fn element_counts_and_quantities_are_capped(items in 1..64usize, qty in 0..1000u64) {
+            let limits = ShareDecodeLimits {
+                max_items: 32,
+                ..ShareDecodeLimits::DEFAULT
+            };
+            let code = block_on(encode_share(shop(items, qty), GameSystem::Pathfinder)).unwrap();
+            let result = block_on(decode_verified_share::<ShareableShop>(&code, &limits, &ShareSigningKeys::default()));
+            if items > limits.max_items {
+                let rejected = matches!(limit_error(result), ShareDecodeError::TooManyElements { .. });
+                prop_assert!(rejected);
+            } else if qty > limits.max_quantity {
+                let rejected = matches!(limit_error(result), ShareDecodeError::QuantityTooLarge { .. });
+                prop_assert!(rejected);
+            } else {
+                prop_assert_eq!(result.unwrap().payload, shop(items, qty));
+            }
+        }
+    }
+}
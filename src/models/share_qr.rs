@@ -0,0 +1,64 @@
+use qrcode::EcLevel;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// How much of a QR code can be damaged while still being readable.
+/// Higher levels hold less data.
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub enum QrErrorCorrection {
+    /// 7%
+    Low,
+    /// 15%
+    #[default]
+    Medium,
+    /// 25%
+    Quartile,
+    /// 30%
+    High,
+}
+
+impl From<QrErrorCorrection> for EcLevel {
+    fn from(value: QrErrorCorrection) -> Self {
+        match value {
+            QrErrorCorrection::Low => Self::L,
+            QrErrorCorrection::Medium => Self::M,
+            QrErrorCorrection::Quartile => Self::Q,
+            QrErrorCorrection::High => Self::H,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Eq, PartialEq, Debug)]
+pub struct QrOptions {
+    pub error_correction: QrErrorCorrection,
+    /// Side of a single module (the QR code "pixel"), in pixels.
+    /// Whole images are at most 2048 pixels wide, so large codes need a smaller module size
+    #[schema(minimum = 1, maximum = 64, example = 8)]
+    pub module_size: u32,
+}
+
+impl Default for QrOptions {
+    fn default() -> Self {
+        Self {
+            error_correction: QrErrorCorrection::default(),
+            module_size: 8,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct QrImages {
+    pub svg: String,
+    pub png: Vec<u8>,
+    /// Side of both images, in pixels
+    pub size: u32,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ShareQrCode {
+    /// Share code held by the QR code, it differs from the given one when it had to be shortened
+    pub share_code: String,
+    /// Whether names were dropped from the shared payload to fit in a QR code
+    pub shortened: bool,
+    pub images: QrImages,
+}
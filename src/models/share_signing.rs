@@ -0,0 +1,206 @@
+use anyhow::{Result, bail, ensure};
+use base64ct::{Base64UrlUnpadded, Encoding};
+use hmac::{Hmac, KeyInit, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Separates a share code from the id of the key that signed it and its signature.
+/// It is not part of the Base64Url alphabet, so unsigned codes never contain it.
+const SIGNATURE_SEPARATOR: char = '.';
+/// Length of a HMAC-SHA256 once encoded in unpadded Base64Url
+const SIGNATURE_LEN: usize = 43;
+
+#[derive(Clone)]
+pub struct ShareSigningKey {
+    pub id: String,
+    secret: Vec<u8>,
+}
+
+impl ShareSigningKey {
+    pub fn new(id: impl Into<String>, secret: impl Into<Vec<u8>>) -> Result<Self> {
+        let id = id.into();
+        let secret = secret.into();
+        ensure!(
+            !id.is_empty()
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "Share signing key ids must be non empty and made of alphanumeric characters, '-' or '_'"
+        );
+        ensure!(
+            secret.len() >= 32,
+            "Share signing key {id} must be at least 32 bytes long"
+        );
+        Ok(Self { id, secret })
+    }
+
+    fn mac(&self, share_code: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(share_code.as_bytes());
+        mac
+    }
+}
+
+impl std::fmt::Debug for ShareSigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShareSigningKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Server secrets used to sign share codes.
+///
+/// The first key signs new codes, every key is accepted when verifying,
+/// so that a key can be rotated without invalidating the codes it signed.
+/// Without keys, codes are not signed at all.
+#[derive(Clone, Debug, Default)]
+pub struct ShareSigningKeys {
+    keys: Vec<ShareSigningKey>,
+}
+
+impl ShareSigningKeys {
+    pub fn new(keys: Vec<ShareSigningKey>) -> Result<Self> {
+        for (i, key) in keys.iter().enumerate() {
+            if keys[..i].iter().any(|x| x.id == key.id) {
+                bail!("Share signing key {} is defined more than once", key.id);
+            }
+        }
+        Ok(Self { keys })
+    }
+
+    /// Appends the id of the signing key and the signature to the given share code
+    pub fn sign(&self, share_code: String) -> String {
+        match self.keys.first() {
+            Some(key) => {
+                let signature =
+                    Base64UrlUnpadded::encode_string(&key.mac(&share_code).finalize().into_bytes());
+                format!(
+                    "{share_code}{SIGNATURE_SEPARATOR}{}{SIGNATURE_SEPARATOR}{signature}",
+                    key.id
+                )
+            }
+            None => share_code,
+        }
+    }
+
+    /// Longest suffix `sign` appends, the separators, the id of a key and the signature
+    pub fn max_signature_len(&self) -> usize {
+        self.keys
+            .iter()
+            .map(|x| x.id.len() + 2 * SIGNATURE_SEPARATOR.len_utf8() + SIGNATURE_LEN)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Strips the signature from the given share code, checking it when it was made with a known key.
+    /// Codes that were tampered with after being signed are rejected.
+    pub fn verify<'a>(&self, share_code: &'a str) -> Result<(&'a str, ShareVerification)> {
+        let mut parts = share_code.split(SIGNATURE_SEPARATOR);
+        let (Some(code), key_id, signature, None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            bail!("The share code signature is malformed")
+        };
+        let (Some(key_id), Some(signature)) = (key_id, signature) else {
+            ensure!(key_id.is_none(), "The share code signature is malformed");
+            return Ok((code, ShareVerification::Unverified));
+        };
+        let Some(key) = self.keys.iter().find(|x| x.id == key_id) else {
+            return Ok((code, ShareVerification::Unverified));
+        };
+        let signature = Base64UrlUnpadded::decode_vec(signature)?;
+        if key.mac(code).verify_slice(&signature).is_err() {
+            bail!("The share code does not match its signature")
+        }
+        Ok((
+            code,
+            ShareVerification::Verified {
+                key_id: key.id.clone(),
+            },
+        ))
+    }
+}
+
+/// Parses keys written as `id:secret` pairs separated by commas, ex `2025:secret,2024:old-secret`
+impl FromStr for ShareSigningKeys {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(
+            s.split(',')
+                .map(str::trim)
+                .filter(|x| !x.is_empty())
+                .map(|x| match x.split_once(':') {
+                    Some((id, secret)) => ShareSigningKey::new(id.trim(), secret.trim()),
+                    None => bail!("Share signing keys must be written as id:secret"),
+                })
+                .collect::<Result<_>>()?,
+        )
+    }
+}
+
+/// Whether a share code was signed by one of the server keys.
+/// Codes signed with a key that is not configured anymore are unverified.
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Eq, PartialEq)]
+pub enum ShareVerification {
+    Verified { key_id: String },
+    Unverified,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(s: &str) -> ShareSigningKeys {
+        ShareSigningKeys::from_str(s).unwrap()
+    }
+
+    const CODE: &str = "KLUv_QBYWQAAA09zaQIAAQABAQA=";
+    const NEW_SECRET: &str = "new-secret-that-is-at-least-32-bytes";
+    const OLD_SECRET: &str = "old-secret-that-is-at-least-32-bytes";
+
+    #[test]
+    fn signed_codes_verify_across_key_rotation() {
+        let old = keys(&format!("old:{OLD_SECRET}"));
+        let rotated = keys(&format!("new:{NEW_SECRET},old:{OLD_SECRET}"));
+        let signed = old.sign(CODE.to_string());
+        assert_eq!(
+            rotated.verify(&signed).unwrap(),
+            (
+                CODE,
+                ShareVerification::Verified {
+                    key_id: "old".to_string()
+                }
+            )
+        );
+        assert!(rotated.sign(CODE.to_string()).contains(".new."));
+        assert_eq!(
+            rotated.max_signature_len(),
+            old.sign(CODE.to_string()).len() - CODE.len()
+        );
+        assert_eq!(
+            keys(&format!("new:{NEW_SECRET}")).verify(&signed).unwrap(),
+            (CODE, ShareVerification::Unverified)
+        );
+    }
+
+    #[test]
+    fn tampered_and_unsigned_codes() {
+        let keys = keys(&format!("new:{NEW_SECRET}"));
+        assert_eq!(
+            keys.verify(CODE).unwrap(),
+            (CODE, ShareVerification::Unverified)
+        );
+        let signed = keys.sign(CODE.to_string());
+        let tampered = signed.replacen("KLUv_QBYWQ", "KLUv_QBYWA", 1);
+        assert!(keys.verify(&tampered).is_err());
+        assert!(keys.verify(&format!("{signed}.extra")).is_err());
+        assert!(ShareSigningKeys::from_str("new:short").is_err());
+    }
+}
@@ -21,6 +21,16 @@ pub struct CoreAction {
     pub slug: Option<String>,
     #[sqlx(try_from = "String")]
     pub rarity: RarityEnum,
+
+    /// Uses allowed per `frequency_per`, only set for abilities with a frequency
+    #[sqlx(default)]
+    #[serde(default)]
+    #[schema(example = 1)]
+    pub frequency_max: Option<i32>,
+    /// Period of the frequency, ex `day` or `PT1M`
+    #[sqlx(default)]
+    #[serde(default)]
+    pub frequency_per: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Eq, Hash, PartialEq, Debug, ToSchema)]
@@ -28,3 +38,47 @@ pub struct Action {
     pub core_action: CoreAction,
     pub traits: Vec<TraitData>,
 }
+
+impl Action {
+    /// Abilities with a frequency, ex once per day, which the elite and
+    /// weak adjustments change by twice as much
+    pub const fn is_limited_use(&self) -> bool {
+        self.core_action.frequency_max.is_some() || self.core_action.frequency_per.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(None, None, false)]
+    #[case(Some(1), Some("day"), true)]
+    #[case(None, Some("PT1M"), true)]
+    fn limited_use_comes_from_the_frequency(
+        #[case] frequency_max: Option<i32>,
+        #[case] frequency_per: Option<&str>,
+        #[case] expected: bool,
+    ) {
+        let action = Action {
+            core_action: CoreAction {
+                id: 1,
+                name: String::from("Breath Weapon"),
+                action_type: String::from("action"),
+                n_of_actions: Some(2),
+                category: Some(String::from("offensive")),
+                description: String::from("Frequency once per day; can't use it again."),
+                license: String::from("ORC"),
+                remaster: true,
+                source: String::from("Monster Core"),
+                slug: None,
+                rarity: RarityEnum::Common,
+                frequency_max,
+                frequency_per: frequency_per.map(String::from),
+            },
+            traits: vec![],
+        };
+        assert_eq!(action.is_limited_use(), expected);
+    }
+}
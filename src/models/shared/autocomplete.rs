@@ -0,0 +1,209 @@
+use crate::models::shared::name_match_mode_enum::{FUZZY_NAME_THRESHOLD, WordTrigrams, trigrams};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use strum::{Display, EnumIter};
+use utoipa::{IntoParams, ToSchema};
+
+pub const DEFAULT_AUTOCOMPLETE_LIMIT: u8 = 10;
+pub const MAX_AUTOCOMPLETE_LIMIT: u8 = 50;
+
+#[derive(Serialize, Deserialize, IntoParams, Default, Eq, PartialEq, Hash, Clone)]
+pub struct AutocompleteQuery {
+    pub query: String,
+    #[param(minimum = 1, maximum = 50, example = 10)]
+    pub limit: Option<u8>,
+}
+
+impl AutocompleteQuery {
+    pub fn limit(&self) -> usize {
+        usize::from(
+            self.limit
+                .unwrap_or(DEFAULT_AUTOCOMPLETE_LIMIT)
+                .clamp(1, MAX_AUTOCOMPLETE_LIMIT),
+        )
+    }
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    ToSchema,
+    Default,
+    Display,
+    EnumIter,
+    Eq,
+    PartialEq,
+    Hash,
+    Clone,
+    Copy,
+    Debug,
+)]
+pub enum AutocompleteEntityEnum {
+    #[default]
+    #[serde(alias = "creature", alias = "CREATURE")]
+    Creature,
+    #[serde(alias = "hazard", alias = "HAZARD")]
+    Hazard,
+    #[serde(alias = "item", alias = "ITEM")]
+    Item,
+    #[serde(alias = "trait", alias = "TRAIT")]
+    Trait,
+}
+
+impl From<String> for AutocompleteEntityEnum {
+    fn from(value: String) -> Self {
+        Self::from_str(value.as_str()).unwrap_or_default()
+    }
+}
+
+impl FromStr for AutocompleteEntityEnum {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "CREATURE" => Ok(Self::Creature),
+            "HAZARD" => Ok(Self::Hazard),
+            "ITEM" => Ok(Self::Item),
+            "TRAIT" => Ok(Self::Trait),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug, sqlx::FromRow)]
+pub struct AutocompleteSuggestion {
+    /// Missing for traits, their name is their id
+    pub id: Option<i64>,
+    pub name: String,
+    #[sqlx(try_from = "String")]
+    pub entity_type: AutocompleteEntityEnum,
+}
+
+/// Suggestion of the in-memory index, with what ranking needs computed when the index is built
+#[derive(Clone, Debug)]
+pub struct AutocompleteEntry {
+    pub suggestion: AutocompleteSuggestion,
+    lowercase_name: String,
+    trigrams: WordTrigrams,
+}
+
+impl From<AutocompleteSuggestion> for AutocompleteEntry {
+    fn from(suggestion: AutocompleteSuggestion) -> Self {
+        let lowercase_name = suggestion.name.to_lowercase();
+        Self {
+            trigrams: WordTrigrams::new(lowercase_name.as_str()),
+            lowercase_name,
+            suggestion,
+        }
+    }
+}
+
+/// Picks the best `limit` suggestions for `query` from the given index.
+///
+/// Names starting with the query come first, then names with a word starting with it,
+/// then names containing it and lastly names similar enough to it ("owlbar" -> "Owlbear").
+/// Ties are broken by similarity, shorter names and alphabetical order.
+pub fn rank_autocomplete_suggestions(
+    index: &[AutocompleteEntry],
+    query: &str,
+    limit: usize,
+) -> Vec<AutocompleteSuggestion> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return vec![];
+    }
+    let query_trigrams = trigrams(query.as_str());
+    let mut ranked: Vec<(u8, f32, &AutocompleteSuggestion)> = index
+        .iter()
+        .filter_map(|entry| {
+            let name = entry.lowercase_name.as_str();
+            let similarity = entry.trigrams.word_similarity(&query_trigrams);
+            let tier = if name.starts_with(query.as_str()) {
+                0
+            } else if name
+                .split(|c: char| !c.is_alphanumeric())
+                .any(|w| w.starts_with(query.as_str()))
+            {
+                1
+            } else if name.contains(query.as_str()) {
+                2
+            } else if similarity >= FUZZY_NAME_THRESHOLD {
+                3
+            } else {
+                return None;
+            };
+            Some((tier, similarity, &entry.suggestion))
+        })
+        .collect();
+    ranked.sort_by(|(a_tier, a_sim, a), (b_tier, b_sim, b)| {
+        a_tier
+            .cmp(b_tier)
+            .then(b_sim.total_cmp(a_sim))
+            .then(a.name.len().cmp(&b.name.len()))
+            .then(a.name.cmp(&b.name))
+    });
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(_, _, entry)| entry.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn entry(
+        id: Option<i64>,
+        name: &str,
+        entity_type: AutocompleteEntityEnum,
+    ) -> AutocompleteEntry {
+        AutocompleteEntry::from(AutocompleteSuggestion {
+            id,
+            name: name.to_string(),
+            entity_type,
+        })
+    }
+
+    #[rstest]
+    fn prefix_matches_come_before_fuzzy_ones() {
+        let index = vec![
+            entry(Some(1), "Hobgoblin", AutocompleteEntityEnum::Creature),
+            entry(Some(2), "Goblin Warrior", AutocompleteEntityEnum::Creature),
+            entry(None, "Goblin", AutocompleteEntityEnum::Trait),
+            entry(Some(3), "Goblin Pox", AutocompleteEntityEnum::Hazard),
+            entry(Some(4), "Owlbear", AutocompleteEntityEnum::Creature),
+        ];
+        let names: Vec<String> = rank_autocomplete_suggestions(&index, "gob", 10)
+            .into_iter()
+            .map(|x| x.name)
+            .collect();
+        assert_eq!(
+            names,
+            ["Goblin", "Goblin Pox", "Goblin Warrior", "Hobgoblin"]
+        );
+        let typo = rank_autocomplete_suggestions(&index, "owlbar", 10);
+        assert_eq!(
+            typo,
+            [entry(Some(4), "Owlbear", AutocompleteEntityEnum::Creature).suggestion]
+        );
+    }
+
+    #[rstest]
+    #[case("", 10, 0)]
+    #[case("goblin", 1, 1)]
+    fn respects_limit_and_blank_queries(
+        #[case] query: &str,
+        #[case] limit: usize,
+        #[case] expected: usize,
+    ) {
+        let index = vec![
+            entry(Some(1), "Goblin Warrior", AutocompleteEntityEnum::Creature),
+            entry(Some(2), "Goblin Commando", AutocompleteEntityEnum::Creature),
+        ];
+        assert_eq!(
+            expected,
+            rank_autocomplete_suggestions(&index, query, limit).len()
+        );
+    }
+}
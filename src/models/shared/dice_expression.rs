@@ -0,0 +1,635 @@
+use crate::models::routers_validator_structs::Dice;
+use anyhow::{Result, bail, ensure};
+use nanorand::{Rng, WyRand};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+pub const MAX_N_OF_DICES: u16 = 255;
+pub const MAX_DICE_SIZE: u16 = 1000;
+pub const MAX_TERMS: usize = 32;
+/// Largest multiplier or flat value, so that parsed expressions never overflow
+pub const MAX_MULTIPLIER: i64 = 1_000_000;
+/// Upper bound of the steps needed to compute a distribution, past it the expression is refused
+const MAX_DISTRIBUTION_STEPS: u64 = 50_000_000;
+
+/// Sum of dice groups and flat values, ex `2d6+1d4+3`, `4d6kh3` or `1d8*2-1`.
+///
+/// It is written and read as its compact string, objects with `n_of_dices` and `dice_size`
+/// are accepted as well from the formats that used to hold a `Dice`.
+#[derive(Clone, Default, Eq, PartialEq, Hash, Debug)]
+pub struct DiceExpression {
+    terms: Vec<DiceTerm>,
+}
+
+/// Dice group times the multiplier, or the multiplier alone for flat values.
+/// Subtracted terms have a negative multiplier.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct DiceTerm {
+    pub dice: Option<DiceGroup>,
+    pub multiplier: i64,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct DiceGroup {
+    pub n_of_dices: u16,
+    pub dice_size: u16,
+    pub keep: Option<Keep>,
+}
+
+/// Dice kept out of a group, the others are rolled and discarded
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Keep {
+    Highest(u16),
+    Lowest(u16),
+}
+
+impl DiceExpression {
+    pub fn terms(&self) -> &[DiceTerm] {
+        &self.terms
+    }
+
+    /// Damage as stored for weapons, ex `2d6` and `4` become `2d6+4`
+    pub fn from_dice_and_bonus(dice: Option<&Dice>, bonus: i64) -> Self {
+        let mut expression = dice.map(Self::from).unwrap_or_default();
+        if bonus != 0 {
+            expression.terms.push(DiceTerm {
+                dice: None,
+                multiplier: bonus,
+            });
+        }
+        expression
+    }
+
+    /// Same dice, with the flat values merged into a single one shifted by `bonus`,
+    /// ex `2d6+1+2` with a bonus of 2 becomes `2d6+5`
+    #[must_use]
+    pub fn with_bonus(&self, bonus: i64) -> Self {
+        let flat = self
+            .terms
+            .iter()
+            .filter(|x| x.dice.is_none())
+            .map(|x| x.multiplier)
+            .fold(bonus, i64::saturating_add);
+        let mut terms: Vec<DiceTerm> = self
+            .terms
+            .iter()
+            .filter(|x| x.dice.is_some())
+            .copied()
+            .collect();
+        if flat != 0 || terms.is_empty() {
+            terms.push(DiceTerm {
+                dice: None,
+                multiplier: flat,
+            });
+        }
+        Self { terms }
+    }
+
+    pub fn roll(&self) -> i64 {
+        self.roll_with(&mut WyRand::new())
+    }
+
+    /// Same seed, same result
+    pub fn roll_seeded(&self, seed: u64) -> i64 {
+        self.roll_with(&mut WyRand::new_seed(seed))
+    }
+
+    pub fn roll_with(&self, rng: &mut WyRand) -> i64 {
+        self.terms
+            .iter()
+            .map(|term| {
+                term.dice
+                    .map_or(1, |dice| dice.roll_with(rng))
+                    .saturating_mul(term.multiplier)
+            })
+            .fold(0, i64::saturating_add)
+    }
+
+    pub fn min(&self) -> i64 {
+        self.terms
+            .iter()
+            .map(|x| x.bounds().0)
+            .fold(0, i64::saturating_add)
+    }
+
+    pub fn max(&self) -> i64 {
+        self.terms
+            .iter()
+            .map(|x| x.bounds().1)
+            .fold(0, i64::saturating_add)
+    }
+
+    pub fn average(&self) -> f64 {
+        self.terms
+            .iter()
+            .map(|term| term.dice.map_or(1., DiceGroup::average) * term.multiplier as f64)
+            .sum()
+    }
+
+    /// Probability of every possible result.
+    /// Expressions too large to compute exactly, ex `255d1000`, are refused.
+    pub fn distribution(&self) -> Result<BTreeMap<i64, f64>> {
+        let mut result = BTreeMap::from([(0_i64, 1.)]);
+        for term in &self.terms {
+            let values: Vec<(i64, f64)> = match term.dice {
+                Some(dice) => dice
+                    .distribution()?
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, p)| *p > 0.)
+                    .map(|(sum, p)| Some((term.multiplier.checked_mul(sum as i64)?, p)))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| anyhow::anyhow!("The dice expression {self} overflows"))?,
+                None => vec![(term.multiplier, 1.)],
+            };
+            ensure!(
+                (result.len() as u64).saturating_mul(values.len() as u64) <= MAX_DISTRIBUTION_STEPS,
+                "The dice expression {self} is too large to compute its distribution"
+            );
+            let mut next = BTreeMap::new();
+            for (total, p_total) in &result {
+                for (value, p_value) in &values {
+                    let Some(sum) = total.checked_add(*value) else {
+                        bail!("The dice expression {self} overflows");
+                    };
+                    *next.entry(sum).or_insert(0.) += p_total * p_value;
+                }
+            }
+            result = next;
+        }
+        Ok(result)
+    }
+}
+
+impl DiceTerm {
+    /// Min and max value of the term
+    fn bounds(&self) -> (i64, i64) {
+        let (min, max) = self.dice.map_or((1, 1), |dice| {
+            let kept = i64::from(dice.kept());
+            (kept, kept * i64::from(dice.dice_size))
+        });
+        let (a, b) = (
+            min.saturating_mul(self.multiplier),
+            max.saturating_mul(self.multiplier),
+        );
+        (a.min(b), a.max(b))
+    }
+}
+
+impl DiceGroup {
+    const fn kept(&self) -> u16 {
+        match self.keep {
+            Some(Keep::Highest(n) | Keep::Lowest(n)) => n,
+            None => self.n_of_dices,
+        }
+    }
+
+    fn roll_with(&self, rng: &mut WyRand) -> i64 {
+        let mut rolls: Vec<u32> = (0..self.n_of_dices)
+            .map(|_| rng.generate_range(1..=u32::from(self.dice_size)))
+            .collect();
+        let kept = usize::from(self.kept());
+        match self.keep {
+            Some(Keep::Highest(_)) => rolls.sort_unstable_by(|a, b| b.cmp(a)),
+            Some(Keep::Lowest(_)) => rolls.sort_unstable(),
+            None => {}
+        }
+        rolls.iter().take(kept).map(|x| i64::from(*x)).sum()
+    }
+
+    fn average(self) -> f64 {
+        if self.keep.is_none() {
+            return f64::from(self.n_of_dices) * f64::midpoint(f64::from(self.dice_size), 1.);
+        }
+        // Groups keeping dice are small enough to be computed when parsed
+        self.distribution().map_or(0., |distribution| {
+            distribution
+                .iter()
+                .enumerate()
+                .map(|(sum, p)| sum as f64 * p)
+                .sum()
+        })
+    }
+
+    /// Steps needed by `distribution`
+    fn distribution_steps(&self) -> u64 {
+        let n = u64::from(self.n_of_dices) + 1;
+        let size = u64::from(self.dice_size);
+        let kept = u64::from(self.kept());
+        match self.keep {
+            None => n * n * size * size / 2,
+            Some(_) => size * n * n * (kept * size + 1),
+        }
+    }
+
+    /// Probability of every sum, indexed by the sum
+    fn distribution(&self) -> Result<Vec<f64>> {
+        ensure!(
+            self.distribution_steps() <= MAX_DISTRIBUTION_STEPS,
+            "The dice group {self} is too large to compute its distribution"
+        );
+        let size = usize::from(self.dice_size);
+        let face = 1. / size as f64;
+        let Some(keep) = self.keep else {
+            let mut sums = vec![1.];
+            for _ in 0..self.n_of_dices {
+                let mut next = vec![0.; sums.len() + size];
+                for (sum, p) in sums.iter().enumerate() {
+                    for value in 1..=size {
+                        next[sum + value] += p * face;
+                    }
+                }
+                sums = next;
+            }
+            return Ok(sums);
+        };
+        // Faces are visited from the kept end, so the first dice placed are the kept ones.
+        // States are the number of dice placed and the sum of the kept ones.
+        let n = usize::from(self.n_of_dices);
+        let kept = usize::from(self.kept());
+        let faces: Vec<usize> = match keep {
+            Keep::Highest(_) => (1..=size).rev().collect(),
+            Keep::Lowest(_) => (1..=size).collect(),
+        };
+        let mut states = vec![vec![0.; kept * size + 1]; n + 1];
+        states[0][0] = 1.;
+        for value in faces {
+            let mut next = vec![vec![0.; kept * size + 1]; n + 1];
+            for placed in 0..=n {
+                for sum in 0..=kept * size {
+                    let p = states[placed][sum];
+                    if p == 0. {
+                        continue;
+                    }
+                    let mut ways = 1.;
+                    for count in 0..=n - placed {
+                        let added = (placed + count).min(kept) - placed.min(kept);
+                        next[placed + count][sum + added * value] +=
+                            p * ways * face.powi(count as i32);
+                        ways = ways * (n - placed - count) as f64 / (count + 1) as f64;
+                    }
+                }
+            }
+            states = next;
+        }
+        Ok(states.swap_remove(n))
+    }
+}
+
+impl From<&Dice> for DiceExpression {
+    fn from(dice: &Dice) -> Self {
+        Self {
+            terms: vec![DiceTerm {
+                dice: Some(DiceGroup {
+                    n_of_dices: u16::try_from(dice.n_of_dices).unwrap_or(1),
+                    dice_size: u16::try_from(dice.dice_size).unwrap_or(1).max(1),
+                    keep: None,
+                }),
+                multiplier: 1,
+            }],
+        }
+    }
+}
+
+impl fmt::Display for DiceGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}d{}", self.n_of_dices, self.dice_size)?;
+        match self.keep {
+            Some(Keep::Highest(n)) => write!(f, "kh{n}"),
+            Some(Keep::Lowest(n)) => write!(f, "kl{n}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for DiceExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+        for (i, term) in self.terms.iter().enumerate() {
+            if term.multiplier < 0 {
+                write!(f, "-")?;
+            } else if i > 0 {
+                write!(f, "+")?;
+            }
+            let multiplier = term.multiplier.unsigned_abs();
+            match term.dice {
+                Some(dice) if multiplier == 1 => write!(f, "{dice}")?,
+                Some(dice) => write!(f, "{dice}*{multiplier}")?,
+                None => write!(f, "{multiplier}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DiceExpression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let input: Vec<u8> = s
+            .bytes()
+            .filter(|x| !x.is_ascii_whitespace())
+            .map(|x| x.to_ascii_lowercase())
+            .collect();
+        ensure!(!input.is_empty(), "The dice expression is empty");
+        let mut parser = ExpressionParser { input, pos: 0 };
+        let mut terms = vec![];
+        let mut sign = match parser.peek() {
+            Some(b'-') => {
+                parser.pos += 1;
+                -1
+            }
+            Some(b'+') => {
+                parser.pos += 1;
+                1
+            }
+            _ => 1,
+        };
+        loop {
+            let mut term = parser.term()?;
+            term.multiplier *= sign;
+            terms.push(term);
+            ensure!(
+                terms.len() <= MAX_TERMS,
+                "A dice expression has at most {MAX_TERMS} terms"
+            );
+            sign = match parser.next() {
+                None => break,
+                Some(b'+') => 1,
+                Some(b'-') => -1,
+                Some(c) => bail!("Unexpected '{}' in the dice expression {s}", char::from(c)),
+            };
+        }
+        Ok(Self { terms })
+    }
+}
+
+struct ExpressionParser {
+    input: Vec<u8>,
+    pos: usize,
+}
+
+impl ExpressionParser {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    fn number(&mut self) -> Result<Option<i64>> {
+        let start = self.pos;
+        while self.peek().is_some_and(|x| x.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Ok(None);
+        }
+        Ok(Some(
+            std::str::from_utf8(&self.input[start..self.pos])?.parse()?,
+        ))
+    }
+
+    /// Factors joined by `*`, at most one of them being a dice group
+    fn term(&mut self) -> Result<DiceTerm> {
+        let mut term = DiceTerm {
+            dice: None,
+            multiplier: 1,
+        };
+        loop {
+            let number = self.number()?;
+            if self.peek() == Some(b'd') {
+                self.pos += 1;
+                ensure!(
+                    term.dice.is_none(),
+                    "Dice groups can not be multiplied together"
+                );
+                term.dice = Some(self.dice_group(number.unwrap_or(1))?);
+            } else {
+                let Some(number) = number else {
+                    bail!("Expected a number or a dice group");
+                };
+                term.multiplier = term
+                    .multiplier
+                    .checked_mul(number)
+                    .filter(|x| *x <= MAX_MULTIPLIER)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Multipliers and flat values are at most {MAX_MULTIPLIER}")
+                    })?;
+            }
+            if self.peek() != Some(b'*') {
+                return Ok(term);
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// What follows the `d` of `4d6kh3`
+    fn dice_group(&mut self, n_of_dices: i64) -> Result<DiceGroup> {
+        let Some(dice_size) = self.number()? else {
+            bail!("Expected the size of the dice after 'd'");
+        };
+        let n_of_dices = u16::try_from(n_of_dices)
+            .ok()
+            .filter(|x| *x <= MAX_N_OF_DICES);
+        let dice_size = u16::try_from(dice_size)
+            .ok()
+            .filter(|x| (1..=MAX_DICE_SIZE).contains(x));
+        let (Some(n_of_dices), Some(dice_size)) = (n_of_dices, dice_size) else {
+            bail!("Dice groups have at most {MAX_N_OF_DICES} dice of size 1 to {MAX_DICE_SIZE}");
+        };
+        let keep = if self.peek() == Some(b'k') {
+            self.pos += 1;
+            let lowest = match self.peek() {
+                Some(b'l') => true,
+                Some(b'h') => false,
+                _ => bail!("Expected 'kh' or 'kl'"),
+            };
+            self.pos += 1;
+            let n = self
+                .number()?
+                .and_then(|x| u16::try_from(x).ok())
+                .filter(|x| *x <= n_of_dices);
+            let Some(n) = n else {
+                bail!("At most the {n_of_dices} rolled dice can be kept");
+            };
+            Some(if lowest {
+                Keep::Lowest(n)
+            } else {
+                Keep::Highest(n)
+            })
+        } else {
+            None
+        };
+        let group = DiceGroup {
+            n_of_dices,
+            dice_size,
+            keep,
+        };
+        ensure!(
+            keep.is_none() || group.distribution_steps() <= MAX_DISTRIBUTION_STEPS,
+            "The dice group {group} keeps too many dice"
+        );
+        Ok(group)
+    }
+}
+
+impl Serialize for DiceExpression {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawDiceExpression {
+    Compact(String),
+    Dice(Dice),
+}
+
+impl<'de> Deserialize<'de> for DiceExpression {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Untagged enums need a self describing format, binary ones only hold the string
+        let raw = if deserializer.is_human_readable() {
+            RawDiceExpression::deserialize(deserializer)?
+        } else {
+            RawDiceExpression::Compact(String::deserialize(deserializer)?)
+        };
+        match raw {
+            RawDiceExpression::Compact(s) => s.parse().map_err(de::Error::custom),
+            RawDiceExpression::Dice(dice) => Ok(Self::from(&dice)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("2d6+1d4+3", "2d6+1d4+3", 6, 19, 12.5)]
+    #[case(" D20 - 2 ", "1d20-2", -1, 18, 8.5)]
+    #[case("1d8*2+1", "1d8*2+1", 3, 17, 10.)]
+    #[case("3*2d4", "2d4*3", 6, 24, 15.)]
+    #[case("-3", "-3", -3, -3, -3.)]
+    #[case("4d6kh3", "4d6kh3", 3, 18, 12.244_598_765_432_098)]
+    #[case("2d20kl1", "2d20kl1", 1, 20, 7.175)]
+    fn expressions_are_parsed_with_their_stats(
+        #[case] input: &str,
+        #[case] canonical: &str,
+        #[case] min: i64,
+        #[case] max: i64,
+        #[case] average: f64,
+    ) {
+        let expression: DiceExpression = input.parse().unwrap();
+        assert_eq!(expression.to_string(), canonical);
+        assert_eq!(expression.min(), min);
+        assert_eq!(expression.max(), max);
+        assert!((expression.average() - average).abs() < 1e-9);
+        let distribution = expression.distribution().unwrap();
+        assert_eq!(distribution.keys().next(), Some(&min));
+        assert_eq!(distribution.keys().last(), Some(&max));
+        assert!((distribution.values().sum::<f64>() - 1.).abs() < 1e-9);
+        for seed in 0..200 {
+            assert!((min..=max).contains(&expression.roll_seeded(seed)));
+        }
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("2d")]
+    #[case("1d6*1d4")]
+    #[case("3d6kh4")]
+    #[case("1d0")]
+    #[case("256d6")]
+    #[case("1d6+")]
+    #[case("1d6/2")]
+    #[case("1d6*9223372036854775807")]
+    #[case("1000*1001")]
+    fn invalid_expressions_are_refused(#[case] input: &str) {
+        assert!(input.parse::<DiceExpression>().is_err());
+    }
+
+    #[rstest]
+    #[case("2d6+1+2", 2, "2d6+5")]
+    #[case("2d6+4", -4, "2d6")]
+    #[case("1d8", -2, "1d8-2")]
+    #[case("5", -5, "0")]
+    fn bonuses_are_merged_into_a_single_flat_value(
+        #[case] input: &str,
+        #[case] bonus: i64,
+        #[case] expected: &str,
+    ) {
+        let expression: DiceExpression = input.parse().unwrap();
+        assert_eq!(expression.with_bonus(bonus).to_string(), expected);
+    }
+
+    #[test]
+    fn stats_saturate_instead_of_overflowing() {
+        let expression = DiceExpression {
+            terms: vec![
+                DiceTerm {
+                    dice: Some(DiceGroup {
+                        n_of_dices: 1,
+                        dice_size: 6,
+                        keep: None,
+                    }),
+                    multiplier: i64::MAX,
+                },
+                DiceTerm {
+                    dice: None,
+                    multiplier: i64::MAX,
+                },
+            ],
+        };
+        assert_eq!(expression.min(), i64::MAX);
+        assert_eq!(expression.max(), i64::MAX);
+        assert!(expression.distribution().is_err());
+    }
+
+    #[test]
+    fn rolls_are_reproducible_with_a_seed() {
+        let expression: DiceExpression = "10d20kh5+1d6".parse().unwrap();
+        assert_eq!(expression.roll_seeded(42), expression.roll_seeded(42));
+    }
+
+    #[test]
+    fn two_dice_follow_the_expected_distribution() {
+        let distribution = "2d6"
+            .parse::<DiceExpression>()
+            .unwrap()
+            .distribution()
+            .unwrap();
+        assert!((distribution[&7] - 6. / 36.).abs() < 1e-12);
+        assert!((distribution[&2] - 1. / 36.).abs() < 1e-12);
+        assert!(
+            "255d1000"
+                .parse::<DiceExpression>()
+                .unwrap()
+                .distribution()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn both_compact_and_legacy_forms_are_deserialized() {
+        let compact: DiceExpression = serde_json::from_str("\"2d6+1\"").unwrap();
+        let legacy: DiceExpression =
+            serde_json::from_str(r#"{"n_of_dices": 2, "dice_size": 6}"#).unwrap();
+        assert_eq!(serde_json::to_string(&compact).unwrap(), "\"2d6+1\"");
+        assert_eq!(legacy.to_string(), "2d6");
+        let bytes = postcard::to_allocvec(&compact).unwrap();
+        assert_eq!(
+            postcard::from_bytes::<DiceExpression>(&bytes).unwrap(),
+            compact
+        );
+    }
+}
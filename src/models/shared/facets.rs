@@ -0,0 +1,50 @@
+use crate::models::bestiary_structs::BestiaryRanges;
+use crate::models::hazard::hazard_struct::HazardRanges;
+use crate::models::item::shop_structs::ShopRanges;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A value of a filterable dimension and the number of results it would lead to,
+/// given every other active filter.
+#[derive(Serialize, Deserialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug, sqlx::FromRow)]
+pub struct FacetValue {
+    #[schema(example = "Common")]
+    pub value: String,
+    #[schema(minimum = 0, example = 42)]
+    pub count: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+pub struct BestiaryFacets {
+    pub source: Vec<FacetValue>,
+    pub family: Vec<FacetValue>,
+    pub rarity: Vec<FacetValue>,
+    pub size: Vec<FacetValue>,
+    pub creature_type: Vec<FacetValue>,
+    pub traits: Vec<FacetValue>,
+    pub role: Vec<FacetValue>,
+    pub level: Vec<FacetValue>,
+    pub ranges: BestiaryRanges,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+pub struct HazardFacets {
+    pub source: Vec<FacetValue>,
+    pub rarity: Vec<FacetValue>,
+    pub size: Vec<FacetValue>,
+    pub traits: Vec<FacetValue>,
+    pub level: Vec<FacetValue>,
+    pub ranges: HazardRanges,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+pub struct ShopFacets {
+    pub source: Vec<FacetValue>,
+    pub category: Vec<FacetValue>,
+    pub rarity: Vec<FacetValue>,
+    pub size: Vec<FacetValue>,
+    pub item_type: Vec<FacetValue>,
+    pub traits: Vec<FacetValue>,
+    pub level: Vec<FacetValue>,
+    pub ranges: ShopRanges,
+}
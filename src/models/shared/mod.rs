@@ -1,10 +1,18 @@
 pub mod action;
 pub mod alignment_enum;
+pub mod autocomplete;
 pub mod condition_data;
+pub mod dice_expression;
+pub mod facets;
 pub mod game_system_enum;
+pub mod listing_cursor;
+pub mod name_match_mode_enum;
 pub mod pf_version_enum;
 pub mod range_data;
 pub mod rarity_enum;
 pub mod size_enum;
+pub mod sort_spec;
 pub mod status_enum;
+pub mod text_search;
 pub mod trait_data;
+pub mod trait_filter_expression;
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use strum::{Display, EnumIter};
+use utoipa::ToSchema;
+
+/// Minimum trigram word similarity for a name to be considered a fuzzy match.
+/// It mirrors the default `pg_trgm.similarity_threshold`.
+pub const FUZZY_NAME_THRESHOLD: f32 = 0.3;
+
+#[derive(
+    Serialize, Deserialize, Eq, PartialEq, Hash, Default, ToSchema, Clone, EnumIter, Display, Copy,
+)]
+pub enum NameMatchModeEnum {
+    #[default]
+    #[serde(alias = "substring", alias = "SUBSTRING")]
+    Substring,
+    #[serde(alias = "fuzzy", alias = "FUZZY")]
+    Fuzzy,
+}
+
+impl NameMatchModeEnum {
+    /// Checks if `name` matches the `filter`. Substring matches are always accepted,
+    /// in fuzzy mode names similar enough to the filter are accepted too ("goblen" -> "Goblin").
+    pub fn is_matching(self, filter: &str, name: &str) -> bool {
+        name.to_lowercase().contains(filter.to_lowercase().as_str())
+            || (self == Self::Fuzzy && word_similarity(filter, name) >= FUZZY_NAME_THRESHOLD)
+    }
+}
+
+/// Trigrams of every word of `s`, built like `pg_trgm` does: lowercased alphanumeric words
+/// padded with two spaces in front and one at the end.
+pub fn trigrams(s: &str) -> BTreeSet<[char; 3]> {
+    s.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .flat_map(|w| {
+            let padded: Vec<char> = format!("  {w} ").chars().collect();
+            padded
+                .windows(3)
+                .map(|x| [x[0], x[1], x[2]])
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Trigram similarity of the two strings, from 0 (nothing in common) to 1 (same trigrams).
+pub fn trigram_similarity(a: &str, b: &str) -> f32 {
+    set_similarity(&trigrams(a), &trigrams(b))
+}
+
+fn set_similarity(a: &BTreeSet<[char; 3]>, b: &BTreeSet<[char; 3]>) -> f32 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.;
+    }
+    a.intersection(b).count() as f32 / union as f32
+}
+
+/// Greatest similarity between `query` and the whole `text` or any of its words,
+/// an approximation of `pg_trgm` `word_similarity` good enough for short names.
+pub fn word_similarity(query: &str, text: &str) -> f32 {
+    WordTrigrams::new(text).word_similarity(&trigrams(query))
+}
+
+/// Trigrams of a text and of each of its words, computed once to be compared to many queries
+#[derive(Clone, Debug, Default)]
+pub struct WordTrigrams {
+    text: BTreeSet<[char; 3]>,
+    words: Vec<BTreeSet<[char; 3]>>,
+}
+
+impl WordTrigrams {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: trigrams(text),
+            words: text.split_whitespace().map(trigrams).collect(),
+        }
+    }
+
+    /// Same as `word_similarity`, given the `trigrams` of the query
+    pub fn word_similarity(&self, query: &BTreeSet<[char; 3]>) -> f32 {
+        self.words
+            .iter()
+            .map(|w| set_similarity(query, w))
+            .fold(set_similarity(query, &self.text), f32::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("goblen", "Goblin Warrior", true)]
+    #[case("owlbar", "Owlbear", true)]
+    #[case("bear", "Owlbear", true)]
+    #[case("dragon", "Goblin Warrior", false)]
+    fn fuzzy_mode_tolerates_typos(#[case] filter: &str, #[case] name: &str, #[case] ok: bool) {
+        assert_eq!(ok, NameMatchModeEnum::Fuzzy.is_matching(filter, name));
+    }
+
+    #[rstest]
+    fn substring_mode_does_not_tolerate_typos() {
+        assert!(!NameMatchModeEnum::Substring.is_matching("goblen", "Goblin Warrior"));
+        assert!(NameMatchModeEnum::Substring.is_matching("GOBLIN", "Goblin Warrior"));
+    }
+
+    #[rstest]
+    fn similarity_is_bounded() {
+        assert!((trigram_similarity("Owlbear", "owlbear") - 1.).abs() < f32::EPSILON);
+        assert!(trigram_similarity("", "").abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::models::routers_validator_structs::OrderEnum;
+use anyhow::{Result, anyhow, bail, ensure};
+use std::str::FromStr;
+
+/// Maximum number of keys of a sort spec, ties past them are broken by id
+pub const MAX_SORT_KEYS: usize = 5;
+
+/// A key of a multi-key sort, the following keys only order the rows it considers equal
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct SortKeySpec<S> {
+    pub sort_by: S,
+    pub order_by: OrderEnum,
+}
+
+impl<S> SortKeySpec<S> {
+    pub const fn new(sort_by: S, order_by: OrderEnum) -> Self {
+        Self { sort_by, order_by }
+    }
+}
+
+fn parse_order(s: &str) -> Option<OrderEnum> {
+    match s.trim().to_uppercase().as_str() {
+        "ASC" | "ASCENDING" => Some(OrderEnum::Ascending),
+        "DESC" | "DESCENDING" => Some(OrderEnum::Descending),
+        _ => None,
+    }
+}
+
+/// Parses a comma separated list of keys, each one optionally followed by its order, ex
+/// `level:desc,brute:desc,name:asc` or `level desc, brute desc, name`. Keys default to ascending.
+pub fn parse_sort_spec<S>(spec: &str) -> Result<Vec<SortKeySpec<S>>>
+where
+    S: FromStr + PartialEq,
+{
+    let mut keys: Vec<SortKeySpec<S>> = Vec::new();
+    for raw_key in spec.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+        let (field, order_by) = raw_key
+            .rsplit_once([':', ' '])
+            .and_then(|(field, order)| Some((field, parse_order(order)?)))
+            .unwrap_or((raw_key, OrderEnum::Ascending));
+        let sort_by = S::from_str(field.trim())
+            .map_err(|_| anyhow!("Unknown sort key \"{}\"", field.trim()))?;
+        if keys.iter().any(|k| k.sort_by == sort_by) {
+            bail!("Sort key \"{}\" is repeated", field.trim());
+        }
+        keys.push(SortKeySpec::new(sort_by, order_by));
+    }
+    ensure!(!keys.is_empty(), "The sort spec has no keys");
+    ensure!(
+        keys.len() <= MAX_SORT_KEYS,
+        "The sort spec can have at most {MAX_SORT_KEYS} keys"
+    );
+    Ok(keys)
+}
+
+/// Formats the keys so that `parse_sort_spec` gives them back, ex `Level:Descending,Name:Ascending`
+pub fn format_sort_spec<S: ToString>(keys: &[SortKeySpec<S>]) -> String {
+    keys.iter()
+        .map(|k| format!("{}:{}", k.sort_by.to_string(), k.order_by))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::bestiary_structs::CreatureSortEnum;
+    use crate::models::creature::creature_metadata::creature_role::CreatureRoleEnum;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("level:desc,brute:desc,name:asc")]
+    #[case(" level DESC , Brute descending, name")]
+    fn parses_multi_key_specs(#[case] spec: &str) {
+        let keys = parse_sort_spec::<CreatureSortEnum>(spec).unwrap();
+        assert_eq!(
+            keys,
+            vec![
+                SortKeySpec::new(CreatureSortEnum::Level, OrderEnum::Descending),
+                SortKeySpec::new(
+                    CreatureSortEnum::RolePercentage(CreatureRoleEnum::Brute),
+                    OrderEnum::Descending
+                ),
+                SortKeySpec::new(CreatureSortEnum::Name, OrderEnum::Ascending),
+            ]
+        );
+        assert_eq!(
+            parse_sort_spec::<CreatureSortEnum>(&format_sort_spec(&keys)).unwrap(),
+            keys
+        );
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("level:desc,unknown")]
+    #[case("level,level:desc")]
+    #[case("id,name,level,hp,size,rarity")]
+    fn rejects_invalid_specs(#[case] spec: &str) {
+        assert!(parse_sort_spec::<CreatureSortEnum>(spec).is_err());
+    }
+}
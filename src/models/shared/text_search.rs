@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Full-text search match of a listing entry: its relevance and a snippet of the matching text,
+/// with the matched terms wrapped in `<mark>` tags.
+#[derive(Serialize, Deserialize, ToSchema, Clone, PartialEq, Debug)]
+pub struct TextSearchMatch {
+    pub id: i64,
+    #[schema(example = 0.6)]
+    pub rank: f32,
+    #[schema(example = "Swallow Whole: The creature swallows a <mark>Medium</mark> or smaller...")]
+    pub snippet: String,
+}
@@ -0,0 +1,506 @@
+use anyhow::{bail, ensure};
+use serde::de::{DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, de};
+use std::fmt;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+/// Boolean expression over the traits of an entity. Traits are matched case-insensitively
+/// and exactly. Groups can't be empty.
+///
+/// It can be deserialized both from its structured form, ex
+/// ```json
+/// {"all_of": [{"trait": "undead"}, {"none_of": [{"trait": "mindless"}]}]}
+/// ```
+/// or from a compact string, where `!` binds tighter than `&`, that binds tighter than `|`
+/// (`NOT`, `AND` and `OR` are accepted as well), ex
+/// ```text
+/// undead & (incorporeal | spirit) & !mindless
+/// ```
+/// Negations, parentheses and groups can be nested up to `MAX_NESTING_DEPTH` levels.
+/// The two forms can be mixed: every child of a group can be a compact string.
+/// Binary formats, as used by share codes, only hold the structured form.
+#[derive(Serialize, ToSchema, Clone, Eq, PartialEq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TraitFilterExpression {
+    Trait(String),
+    AllOf(Vec<Self>),
+    AnyOf(Vec<Self>),
+    NoneOf(Vec<Self>),
+}
+
+pub const MAX_NESTING_DEPTH: usize = 32;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ExpressionKind {
+    Trait,
+    AllOf,
+    AnyOf,
+    NoneOf,
+}
+
+const EXPRESSION_KINDS: &[&str] = &["trait", "all_of", "any_of", "none_of"];
+
+impl ExpressionKind {
+    /// Builds a group of this kind, None for traits
+    fn group(&self) -> Option<fn(Vec<TraitFilterExpression>) -> TraitFilterExpression> {
+        match self {
+            Self::Trait => None,
+            Self::AllOf => Some(TraitFilterExpression::AllOf),
+            Self::AnyOf => Some(TraitFilterExpression::AnyOf),
+            Self::NoneOf => Some(TraitFilterExpression::NoneOf),
+        }
+    }
+}
+
+/// Deserializes an expression found inside `depth` groups. Every format goes through it,
+/// so that crafted inputs fail past `MAX_NESTING_DEPTH` instead of recursing without bound.
+#[derive(Clone, Copy)]
+struct ExpressionSeed {
+    depth: usize,
+}
+
+impl ExpressionSeed {
+    const fn children(self) -> ChildrenSeed {
+        ChildrenSeed {
+            depth: self.depth + 1,
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ExpressionSeed {
+    type Value = TraitFilterExpression;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        // Binary formats are not self describing, they only hold the structured form
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(self)
+        } else {
+            deserializer.deserialize_enum("TraitFilterExpression", EXPRESSION_KINDS, self)
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for ExpressionSeed {
+    type Value = TraitFilterExpression;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a trait filter expression")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        parse(v, self.depth).map_err(E::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let Some(kind) = map.next_key::<ExpressionKind>()? else {
+            return Err(de::Error::custom("Empty trait filter expression"));
+        };
+        let expr = match kind.group() {
+            Some(group) => group(map.next_value_seed(self.children())?),
+            None => TraitFilterExpression::Trait(map.next_value()?),
+        };
+        if map.next_key::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::custom(
+                "A trait filter expression has a single key",
+            ));
+        }
+        Ok(expr)
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let (kind, variant) = data.variant::<ExpressionKind>()?;
+        Ok(match kind.group() {
+            Some(group) => group(variant.newtype_variant_seed(self.children())?),
+            None => TraitFilterExpression::Trait(variant.newtype_variant()?),
+        })
+    }
+}
+
+/// Children of a group, that can't be empty
+struct ChildrenSeed {
+    depth: usize,
+}
+
+impl<'de> DeserializeSeed<'de> for ChildrenSeed {
+    type Value = Vec<TraitFilterExpression>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        if self.depth > MAX_NESTING_DEPTH {
+            return Err(de::Error::custom(format!(
+                "Trait filter expression is nested more than {MAX_NESTING_DEPTH} levels deep"
+            )));
+        }
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ChildrenSeed {
+    type Value = Vec<TraitFilterExpression>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a list of trait filter expressions")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut children = vec![];
+        while let Some(child) = seq.next_element_seed(ExpressionSeed { depth: self.depth })? {
+            children.push(child);
+        }
+        if children.is_empty() {
+            return Err(de::Error::custom("Empty group in trait filter expression"));
+        }
+        Ok(children)
+    }
+}
+
+impl<'de> Deserialize<'de> for TraitFilterExpression {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ExpressionSeed { depth: 0 }.deserialize(deserializer)
+    }
+}
+
+impl TraitFilterExpression {
+    /// Every trait the expression mentions, in order of appearance
+    pub fn trait_names(&self) -> Vec<&str> {
+        match self {
+            Self::Trait(name) => vec![name.as_str()],
+            Self::AllOf(children) | Self::AnyOf(children) | Self::NoneOf(children) => {
+                children.iter().flat_map(Self::trait_names).collect()
+            }
+        }
+    }
+
+    /// Traits and groups in the expression
+    pub fn node_count(&self) -> usize {
+        match self {
+            Self::Trait(_) => 1,
+            Self::AllOf(children) | Self::AnyOf(children) | Self::NoneOf(children) => {
+                1 + children.iter().map(Self::node_count).sum::<usize>()
+            }
+        }
+    }
+
+    /// Evaluates the expression against the given trait names.
+    pub fn is_satisfied_by<S: AsRef<str>>(&self, traits: &[S]) -> bool {
+        match self {
+            Self::Trait(name) => traits.iter().any(|t| t.as_ref().eq_ignore_ascii_case(name)),
+            Self::AllOf(children) => children.iter().all(|x| x.is_satisfied_by(traits)),
+            Self::AnyOf(children) => children.iter().any(|x| x.is_satisfied_by(traits)),
+            Self::NoneOf(children) => !children.iter().any(|x| x.is_satisfied_by(traits)),
+        }
+    }
+}
+
+impl fmt::Display for TraitFilterExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |children: &[Self], separator: &str| {
+            children
+                .iter()
+                .map(|x| match x {
+                    Self::AllOf(_) | Self::AnyOf(_) => format!("({x})"),
+                    _ => x.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        match self {
+            Self::Trait(name) => write!(f, "{name}"),
+            Self::AllOf(children) => write!(f, "{}", join(children, " & ")),
+            Self::AnyOf(children) => write!(f, "{}", join(children, " | ")),
+            Self::NoneOf(children) => match children.as_slice() {
+                [Self::Trait(name)] => write!(f, "!{name}"),
+                _ => write!(f, "!({})", join(children, " | ")),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+enum Token {
+    And,
+    Or,
+    Not,
+    OpenParenthesis,
+    CloseParenthesis,
+    Trait(String),
+}
+
+fn tokenize(s: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '&' | '|' => {
+                // && and || are accepted as aliases
+                if chars.peek() == Some(&c) {
+                    chars.next();
+                }
+                tokens.push(if c == '&' { Token::And } else { Token::Or });
+            }
+            '!' => tokens.push(Token::Not),
+            '(' => tokens.push(Token::OpenParenthesis),
+            ')' => tokens.push(Token::CloseParenthesis),
+            c if c.is_alphanumeric() || c == '-' || c == '_' || c == '\'' => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_alphanumeric() || next == '-' || next == '_' || next == '\'' {
+                        word.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(match word.to_uppercase().as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Trait(word),
+                });
+            }
+            _ => bail!("Unexpected character '{c}' in trait filter expression"),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn next_if(&mut self, token: &Token) -> bool {
+        let matches = self.tokens.get(self.pos) == Some(token);
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    /// Parses a negated or parenthesized expression, failing instead of
+    /// recursing without bound on inputs like `!!!!…` or `((((…`
+    fn parse_nested(
+        &mut self,
+        parse: fn(&mut Self) -> anyhow::Result<TraitFilterExpression>,
+    ) -> anyhow::Result<TraitFilterExpression> {
+        ensure!(
+            self.depth < MAX_NESTING_DEPTH,
+            "Trait filter expression is nested more than {MAX_NESTING_DEPTH} levels deep"
+        );
+        self.depth += 1;
+        let expr = parse(self);
+        self.depth -= 1;
+        expr
+    }
+
+    fn parse_or(&mut self) -> anyhow::Result<TraitFilterExpression> {
+        let mut children = vec![self.parse_and()?];
+        while self.next_if(&Token::Or) {
+            children.push(self.parse_and()?);
+        }
+        Ok(if children.len() == 1 {
+            children.remove(0)
+        } else {
+            TraitFilterExpression::AnyOf(children)
+        })
+    }
+
+    fn parse_and(&mut self) -> anyhow::Result<TraitFilterExpression> {
+        let mut children = vec![self.parse_unary()?];
+        while self.next_if(&Token::And) {
+            children.push(self.parse_unary()?);
+        }
+        Ok(if children.len() == 1 {
+            children.remove(0)
+        } else {
+            TraitFilterExpression::AllOf(children)
+        })
+    }
+
+    fn parse_unary(&mut self) -> anyhow::Result<TraitFilterExpression> {
+        match self.advance() {
+            Some(Token::Not) => Ok(TraitFilterExpression::NoneOf(vec![
+                self.parse_nested(Self::parse_unary)?,
+            ])),
+            Some(Token::OpenParenthesis) => {
+                let expr = self.parse_nested(Self::parse_or)?;
+                ensure!(
+                    self.next_if(&Token::CloseParenthesis),
+                    "Missing closing parenthesis in trait filter expression"
+                );
+                Ok(expr)
+            }
+            Some(Token::Trait(name)) => Ok(TraitFilterExpression::Trait(name)),
+            Some(token) => bail!("Unexpected token {token:?} in trait filter expression"),
+            None => bail!("Trait filter expression ended unexpectedly"),
+        }
+    }
+}
+
+/// Parses a compact expression found inside `depth` groups
+fn parse(s: &str, depth: usize) -> anyhow::Result<TraitFilterExpression> {
+    let mut parser = Parser {
+        tokens: tokenize(s)?,
+        pos: 0,
+        depth,
+    };
+    let expr = parser.parse_or()?;
+    ensure!(
+        parser.pos == parser.tokens.len(),
+        "Unexpected trailing tokens in trait filter expression"
+    );
+    Ok(expr)
+}
+
+impl FromStr for TraitFilterExpression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn t(name: &str) -> TraitFilterExpression {
+        TraitFilterExpression::Trait(name.to_string())
+    }
+
+    #[rstest]
+    fn parse_compact_expression_with_precedence() {
+        assert_eq!(
+            TraitFilterExpression::from_str("undead & (incorporeal | spirit) & !mindless").unwrap(),
+            TraitFilterExpression::AllOf(vec![
+                t("undead"),
+                TraitFilterExpression::AnyOf(vec![t("incorporeal"), t("spirit")]),
+                TraitFilterExpression::NoneOf(vec![t("mindless")]),
+            ])
+        );
+        assert_eq!(
+            TraitFilterExpression::from_str("fire OR cold AND NOT water").unwrap(),
+            TraitFilterExpression::AnyOf(vec![
+                t("fire"),
+                TraitFilterExpression::AllOf(vec![
+                    t("cold"),
+                    TraitFilterExpression::NoneOf(vec![t("water")]),
+                ]),
+            ])
+        );
+    }
+
+    #[rstest]
+    #[case("undead &")]
+    #[case("(undead")]
+    #[case("undead)")]
+    #[case("undead $ fire")]
+    #[case("")]
+    #[case("()")]
+    #[case(&"!".repeat(100_000))]
+    #[case(&format!("{}undead", "(".repeat(100_000)))]
+    fn parse_invalid_compact_expression(#[case] input: &str) {
+        assert!(TraitFilterExpression::from_str(input).is_err());
+    }
+
+    #[rstest]
+    fn parse_up_to_max_nesting_depth() {
+        let nested = |depth: usize| format!("{}undead{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(TraitFilterExpression::from_str(&nested(MAX_NESTING_DEPTH)).is_ok());
+        assert!(TraitFilterExpression::from_str(&nested(MAX_NESTING_DEPTH + 1)).is_err());
+    }
+
+    #[rstest]
+    #[case(r#"{"all_of": []}"#)]
+    #[case(r#"{"all_of": [{"trait": "undead"}, {"none_of": []}]}"#)]
+    fn deserialize_rejects_empty_groups(#[case] input: &str) {
+        assert!(serde_json::from_str::<TraitFilterExpression>(input).is_err());
+    }
+
+    #[rstest]
+    fn deserialize_up_to_max_nesting_depth() {
+        let nested = |depth: usize| {
+            format!(
+                "{}\"undead\"{}",
+                r#"{"none_of": ["#.repeat(depth),
+                "]}".repeat(depth)
+            )
+        };
+        assert!(serde_json::from_str::<TraitFilterExpression>(&nested(MAX_NESTING_DEPTH)).is_ok());
+        assert!(
+            serde_json::from_str::<TraitFilterExpression>(&nested(MAX_NESTING_DEPTH + 1)).is_err()
+        );
+        // Compact strings count the groups they are nested in
+        let mixed = format!(
+            "{}\"!undead\"{}",
+            r#"{"none_of": ["#.repeat(MAX_NESTING_DEPTH),
+            "]}".repeat(MAX_NESTING_DEPTH)
+        );
+        assert!(serde_json::from_str::<TraitFilterExpression>(&mixed).is_err());
+    }
+
+    #[rstest]
+    fn binary_rejects_deep_nesting() {
+        // AllOf with a single child, 2 bytes per level, then the trait "x"
+        let mut bytes = [1, 1].repeat(100_000);
+        bytes.extend([0, 1, b'x']);
+        assert!(postcard::from_bytes::<TraitFilterExpression>(&bytes).is_err());
+        assert!(postcard::from_bytes::<TraitFilterExpression>(&[1, 1, 0, 1, b'x']).is_ok());
+        assert!(postcard::from_bytes::<TraitFilterExpression>(&[1, 0]).is_err());
+    }
+
+    #[rstest]
+    fn deserialize_structured_and_mixed_forms() {
+        let structured: TraitFilterExpression = serde_json::from_str(
+            r#"{"all_of": [{"trait": "undead"}, {"none_of": ["mindless"]}, "incorporeal | spirit"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            structured,
+            TraitFilterExpression::from_str("undead & !mindless & (incorporeal | spirit)").unwrap()
+        );
+    }
+
+    #[rstest]
+    fn binary_round_trips() {
+        let expr =
+            TraitFilterExpression::from_str("undead & (incorporeal | spirit) & !mindless").unwrap();
+        let bytes = postcard::to_allocvec(&expr).unwrap();
+        assert_eq!(
+            expr,
+            postcard::from_bytes::<TraitFilterExpression>(&bytes).unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case(vec!["Undead", "Spirit"], true)]
+    #[case(vec!["undead", "spirit", "mindless"], false)]
+    #[case(vec!["undead"], false)]
+    #[case(vec!["spirit"], false)]
+    fn evaluate_expression(#[case] traits: Vec<&str>, #[case] expected: bool) {
+        let expr =
+            TraitFilterExpression::from_str("undead & (incorporeal | spirit) & !mindless").unwrap();
+        assert_eq!(expected, expr.is_satisfied_by(&traits));
+    }
+
+    #[rstest]
+    fn display_round_trips() {
+        let expr =
+            TraitFilterExpression::from_str("undead & (incorporeal | spirit) & !mindless").unwrap();
+        assert_eq!(
+            expr,
+            TraitFilterExpression::from_str(expr.to_string().as_str()).unwrap()
+        );
+    }
+}
@@ -1,10 +1,27 @@
-use crate::models::response_data::ResponseNpc;
+use crate::models::response_data::{
+    EncounterInfoResponse, ResponseCreature, ResponseHazard, ResponseNpc, ShopListingResponse,
+};
+use crate::models::share_signing::ShareVerification;
 use crate::models::shared::game_system_enum::GameSystem;
 
 use crate::models::creature::creature_metadata::variant_enum::CreatureVariant;
+use crate::models::encounter_structs::RandomEncounterData;
+use crate::models::item::shop_structs::{
+    LegacyRandomShopData, PfShopTemplateEnum, RandomShopData, SfShopTemplateEnum,
+};
+use crate::models::npc::class_enum::{PfClass, SfClass};
+use crate::models::npc::job_enum::{PfJob, SfJob};
+use crate::models::npc::name_origin_enum::{PfNameOriginFilter, SfNameOriginFilter};
+use crate::models::npc::request_npc_struct::RandomNpcData;
+use crate::models::shared::trait_filter_expression::TraitFilterExpression;
 use crate::traits::base64::base64_decode::Base64Decode;
 use crate::traits::base64::base64_encode::Base64Encode;
+use crate::traits::class_enum::ClassEnum;
+use crate::traits::job_enum::JobEnum;
+use crate::traits::name_system::NameOriginFilter;
+use crate::traits::template_enum::{GenericTemplate, ItemTemplate};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use utoipa::ToSchema;
 
 #[derive(Serialize, Deserialize, Clone, ToSchema)]
@@ -32,6 +49,16 @@ pub struct ShareableEncounter {
     pub(crate) hazards_data: Vec<SharableHazard>,
 }
 
+impl From<LegacyShareableEncounter> for ShareableEncounter {
+    fn from(legacy: LegacyShareableEncounter) -> Self {
+        Self {
+            encounter_name: legacy.encounter_name,
+            creatures_data: legacy.creatures_data,
+            hazards_data: vec![],
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, ToSchema, Eq, PartialEq, Debug)]
 pub struct ShareableItem {
     pub(crate) id: u64,
@@ -54,6 +81,223 @@ pub struct SharableHazard {
     pub(crate) game: GameSystem,
 }
 
+/// Input of one of the generators, shared so that another GM can generate their own variations of it.
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub enum ShareableGeneratorRecipe {
+    Encounter(Box<RandomEncounterData>),
+    PfShop(RandomShopData<PfShopTemplateEnum>),
+    SfShop(RandomShopData<SfShopTemplateEnum>),
+    PfNpc(RandomNpcData<PfClass, PfNameOriginFilter, PfJob>),
+    SfNpc(RandomNpcData<SfClass, SfNameOriginFilter, SfJob>),
+}
+
+/// Generator recipe as written by share format version 1, where shop dice were `Dice`
+#[derive(Deserialize)]
+pub enum LegacyShareableGeneratorRecipe {
+    Encounter(Box<RandomEncounterData>),
+    PfShop(LegacyRandomShopData<PfShopTemplateEnum>),
+    SfShop(LegacyRandomShopData<SfShopTemplateEnum>),
+    PfNpc(RandomNpcData<PfClass, PfNameOriginFilter, PfJob>),
+    SfNpc(RandomNpcData<SfClass, SfNameOriginFilter, SfJob>),
+}
+
+impl From<LegacyShareableGeneratorRecipe> for ShareableGeneratorRecipe {
+    fn from(legacy: LegacyShareableGeneratorRecipe) -> Self {
+        match legacy {
+            LegacyShareableGeneratorRecipe::Encounter(x) => Self::Encounter(x),
+            LegacyShareableGeneratorRecipe::PfShop(x) => Self::PfShop(x.into()),
+            LegacyShareableGeneratorRecipe::SfShop(x) => Self::SfShop(x.into()),
+            LegacyShareableGeneratorRecipe::PfNpc(x) => Self::PfNpc(x),
+            LegacyShareableGeneratorRecipe::SfNpc(x) => Self::SfNpc(x),
+        }
+    }
+}
+
+impl ShareableGeneratorRecipe {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Self::Encounter(x) => x.is_valid(),
+            Self::PfShop(x) => x.is_valid(),
+            Self::SfShop(x) => x.is_valid(),
+            Self::PfNpc(x) => x.is_valid(),
+            Self::SfNpc(x) => x.is_valid(),
+        }
+    }
+
+    /// Length of every list of filter values in the recipe
+    pub fn filter_lengths(&self) -> Vec<usize> {
+        match self {
+            Self::Encounter(x) => {
+                let mut lengths = vec![x.party_levels.len()];
+                if let Some(cr) = &x.creature_data {
+                    lengths.extend([
+                        len(&cr.source_filter),
+                        len(&cr.trait_whitelist_filter),
+                        len(&cr.trait_blacklist_filter),
+                        len(&cr.family_filter),
+                        len(&cr.rarity_filter),
+                        len(&cr.size_filter),
+                        len(&cr.alignment_filter),
+                        len(&cr.type_filter),
+                        len(&cr.role_filter),
+                        cr.attack_list.as_ref().map_or(0, HashMap::len),
+                        len(&cr.immunity_whitelist_filter),
+                        len(&cr.immunity_blacklist_filter),
+                        len(&cr.resistance_whitelist_filter),
+                        len(&cr.resistance_blacklist_filter),
+                        len(&cr.weakness_whitelist_filter),
+                        len(&cr.weakness_blacklist_filter),
+                        len(&cr.speed_type_filter),
+                        len(&cr.sense_filter),
+                        len(&cr.language_filter),
+                        len(&cr.spell_tradition_filter),
+                        len(&cr.spellcaster_type_filter),
+                        len(&cr.spell_name_filter),
+                    ]);
+                }
+                if let Some(hz) = &x.hazard_data {
+                    lengths.extend([
+                        len(&hz.source_filter),
+                        len(&hz.trait_whitelist_filter),
+                        len(&hz.trait_blacklist_filter),
+                        len(&hz.rarity_filter),
+                        len(&hz.size_filter),
+                    ]);
+                }
+                lengths
+            }
+            Self::PfShop(x) => shop_filter_lengths(x),
+            Self::SfShop(x) => shop_filter_lengths(x),
+            Self::PfNpc(x) => npc_filter_lengths(x),
+            Self::SfNpc(x) => npc_filter_lengths(x),
+        }
+    }
+
+    /// Every trait filter expression of the recipe
+    pub fn trait_expressions(&self) -> Vec<&TraitFilterExpression> {
+        match self {
+            Self::Encounter(x) => [
+                x.creature_data
+                    .as_ref()
+                    .and_then(|cr| cr.trait_expression_filter.as_ref()),
+                x.hazard_data
+                    .as_ref()
+                    .and_then(|hz| hz.trait_expression_filter.as_ref()),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            Self::PfShop(x) => x.trait_expression_filter.iter().collect(),
+            Self::SfShop(x) => x.trait_expression_filter.iter().collect(),
+            Self::PfNpc(_) | Self::SfNpc(_) => vec![],
+        }
+    }
+}
+
+fn len<T>(values: &Option<Vec<T>>) -> usize {
+    values.as_ref().map_or(0, Vec::len)
+}
+
+fn shop_filter_lengths<T: GenericTemplate + ItemTemplate>(x: &RandomShopData<T>) -> Vec<usize> {
+    vec![
+        len(&x.category_filter),
+        len(&x.source_filter),
+        len(&x.trait_whitelist_filter),
+        len(&x.trait_blacklist_filter),
+        len(&x.type_filter),
+        len(&x.rarity_filter),
+        len(&x.size_filter),
+        x.equippable_dices.len(),
+        x.consumable_dices.len(),
+    ]
+}
+
+fn npc_filter_lengths<C: ClassEnum, N: NameOriginFilter, J: JobEnum>(
+    x: &RandomNpcData<C, N, J>,
+) -> Vec<usize> {
+    vec![
+        len(&x.gender_filter),
+        len(&x.class_filter),
+        len(&x.job_filter),
+        x.name_origin_filter
+            .get_ancestries_filter()
+            .map_or(0, |x| x.len()),
+        x.name_origin_filter
+            .get_cultures_filter()
+            .map_or(0, |x| x.len()),
+    ]
+}
+
+/// Filter values of a shared recipe that no longer exist in the requested game system.
+/// The recipe still works, those values just do not match anything.
+#[derive(Serialize, Deserialize, Clone, ToSchema, Default, Debug, PartialEq, Eq)]
+pub struct MissingRecipeValues {
+    pub(crate) sources: Vec<String>,
+    pub(crate) traits: Vec<String>,
+    pub(crate) families: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ImportedGeneratorRecipe {
+    pub(crate) recipe: ShareableGeneratorRecipe,
+    pub(crate) missing: MissingRecipeValues,
+    pub(crate) verification: ShareVerification,
+}
+
+/// Ids of a share code that do not match an entity of the requested game system anymore.
+/// They are reported instead of being silently left out of the hydrated response.
+#[derive(Serialize, Deserialize, Clone, ToSchema, Default, Debug, PartialEq, Eq)]
+pub struct UnresolvedShareIds {
+    pub(crate) creatures: Vec<u64>,
+    pub(crate) hazards: Vec<u64>,
+    pub(crate) items: Vec<u64>,
+}
+
+pub use schemas::*;
+
+#[allow(clippy::option_if_let_else)]
+mod schemas {
+    use super::*;
+
+    /// A fetched entity of a share code, with the quantity it was shared with
+    #[derive(Serialize, Deserialize, Clone, ToSchema, Debug, PartialEq, Eq)]
+    pub struct QuantifiedEntity<T> {
+        pub(crate) entity: T,
+        pub(crate) quantity: u64,
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct HydratedEncounterContent {
+    pub(crate) creatures: Vec<QuantifiedEntity<ResponseCreature>>,
+    pub(crate) hazards: Vec<QuantifiedEntity<ResponseHazard>>,
+}
+
+/// A shared encounter with its creatures and hazards fetched, each one with its quantity.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct HydratedEncounterResponse {
+    pub(crate) encounter_name: String,
+    pub(crate) results: HydratedEncounterContent,
+    /// Creatures and hazards of the encounter, counting every copy
+    pub(crate) count: usize,
+    /// Only computed when the party levels are given
+    pub(crate) encounter_info: Option<EncounterInfoResponse>,
+    pub(crate) unresolved: UnresolvedShareIds,
+    pub(crate) game: GameSystem,
+    pub(crate) verification: ShareVerification,
+}
+
+/// A shared shop with its items fetched, their quantity being the shared one.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct HydratedShopResponse {
+    pub(crate) shop_name: String,
+    pub(crate) listing: ShopListingResponse,
+    /// Sum of the price of every item times its quantity, in cp
+    pub(crate) total_price: i64,
+    pub(crate) unresolved: UnresolvedShareIds,
+    pub(crate) verification: ShareVerification,
+}
+
 impl Base64Encode for ShareableShop {}
 
 impl Base64Encode for LegacyShareableEncounter {}
@@ -0,0 +1,517 @@
+use crate::models::creature::creature_component::creature_combat::CreatureCombatData;
+use crate::models::creature::creature_component::creature_extra::CreatureExtraData;
+use crate::models::creature::items::spellcaster_entry::SpellcasterEntry;
+use crate::models::foundry::foundry_actor::damage_formula;
+use crate::models::foundry::inline_markup::{
+    DescriptionFormat, DescriptionRolls, render_description,
+};
+use crate::models::item::item_metadata::type_enum::WeaponTypeEnum;
+use crate::models::item::weapon_struct::Weapon;
+use crate::models::response_data::ResponseCreature;
+use crate::models::shared::action::Action;
+use crate::models::shared::rarity_enum::RarityEnum;
+use crate::models::stat_block::stat_block_struct::{
+    ActionGlyph, StatBlock, StatLine, ordinal, signed,
+};
+use crate::traits::stat_block_renderable::StatBlockRenderable;
+use itertools::Itertools;
+
+impl StatBlockRenderable for ResponseCreature {
+    fn to_stat_block(&self) -> StatBlock {
+        let essential = &self.core_data.essential;
+        let extra = self.extra_data.as_ref();
+        let combat = self.combat_data.as_ref();
+        let actions = extra.map_or_else(Vec::new, |x| x.actions.iter().collect());
+        let in_category = |category: &str| {
+            actions
+                .iter()
+                .filter(|x| {
+                    x.core_action
+                        .category
+                        .as_deref()
+                        .is_some_and(|c| c.eq_ignore_ascii_case(category))
+                })
+                .map(|x| action_line(x))
+                .collect::<Vec<_>>()
+        };
+
+        let mut identity = vec![];
+        if let Some(extra) = extra {
+            identity.extend(identity_lines(extra));
+        }
+        identity.extend(in_category("interaction"));
+
+        let mut defense = vec![];
+        if let Some(combat) = combat {
+            defense.extend(defense_lines(combat, essential.hp, extra));
+        } else {
+            defense.push(StatLine::new("HP", essential.hp.to_string()));
+        }
+        defense.extend(in_category("defensive"));
+
+        let mut offense = vec![];
+        if let Some(extra) = extra
+            && !extra.speeds.is_empty()
+        {
+            offense.push(StatLine::new("Speed", speeds(extra)));
+        }
+        if let Some(combat) = combat {
+            offense.extend(combat.weapons.iter().map(strike_line));
+        }
+        if let Some(spellcaster) = &self.spellcaster_data {
+            offense.extend(spellcaster.spellcaster_entries.iter().map(spells_line));
+        }
+        offense.extend(
+            actions
+                .iter()
+                .filter(|x| {
+                    !x.core_action.category.as_deref().is_some_and(|c| {
+                        c.eq_ignore_ascii_case("interaction") || c.eq_ignore_ascii_case("defensive")
+                    })
+                })
+                .map(|x| action_line(x)),
+        );
+
+        let mut traits = vec![];
+        if essential.rarity != RarityEnum::Common {
+            traits.push(essential.rarity.to_string());
+        }
+        traits.push(essential.size.to_string());
+        traits.extend(self.core_data.traits.iter().map(|x| x.name.clone()));
+        StatBlock {
+            name: essential.name.clone(),
+            kind: String::from("Creature"),
+            level: self.variant_data.level,
+            traits,
+            sections: vec![identity, defense, offense],
+            rolls: DescriptionRolls::from_descriptions(
+                actions
+                    .iter()
+                    .copied()
+                    .chain(
+                        combat.iter().flat_map(|x| {
+                            x.weapons.iter().flat_map(|w| &w.weapon_data.attack_effects)
+                        }),
+                    )
+                    .map(|x| x.core_action.description.as_str()),
+            ),
+        }
+    }
+}
+
+/// Activity with its glyph, traits and description, shared by creatures and hazards
+pub fn action_line(action: &Action) -> StatLine {
+    let core = &action.core_action;
+    let mut text = String::new();
+    if !action.traits.is_empty() {
+        text.push_str(&format!(
+            "({}) ",
+            action
+                .traits
+                .iter()
+                .map(|x| x.name.to_lowercase())
+                .join(", ")
+        ));
+    }
+    text.push_str(&render_description(
+        &core.description,
+        DescriptionFormat::Text,
+    ));
+    StatLine::new(core.name.clone(), text.trim_end()).with_glyph(ActionGlyph::from_action(
+        &core.action_type,
+        core.n_of_actions,
+    ))
+}
+
+fn identity_lines(extra: &CreatureExtraData) -> Vec<StatLine> {
+    let mut perception = signed(extra.perception.into());
+    let senses = extra
+        .senses
+        .iter()
+        .map(|x| {
+            let mut sense = x.name.to_lowercase();
+            if let Some(acuity) = &x.acuity {
+                sense.push_str(&format!(" ({})", acuity.to_lowercase()));
+            }
+            if let Some(range) = &x.range {
+                sense.push_str(&format!(" {}", range.value));
+            }
+            sense
+        })
+        .chain(extra.perception_detail.clone())
+        .join(", ");
+    if !senses.is_empty() {
+        perception.push_str(&format!("; {senses}"));
+    }
+    let mut lines = vec![StatLine::new("Perception", perception)];
+    if !extra.languages.is_empty() {
+        let mut languages = extra.languages.join(", ");
+        if let Some(detail) = &extra.language_detail {
+            languages.push_str(&format!("; {detail}"));
+        }
+        lines.push(StatLine::new("Languages", languages));
+    }
+    if !extra.skills.is_empty() {
+        lines.push(StatLine::new(
+            "Skills",
+            extra
+                .skills
+                .iter()
+                .map(|x| format!("{} {}", x.name, signed(x.modifier)))
+                .join(", "),
+        ));
+    }
+    let scores = &extra.ability_scores;
+    lines.push(
+        StatLine::new("Str", signed(scores.strength))
+            .and("Dex", signed(scores.dexterity))
+            .and("Con", signed(scores.constitution))
+            .and("Int", signed(scores.intelligence))
+            .and("Wis", signed(scores.wisdom))
+            .and("Cha", signed(scores.charisma)),
+    );
+    if !extra.items.is_empty() {
+        lines.push(StatLine::new(
+            "Items",
+            extra
+                .items
+                .iter()
+                .map(|x| {
+                    if x.quantity > 1 {
+                        format!("{} ({})", x.name.to_lowercase(), x.quantity)
+                    } else {
+                        x.name.to_lowercase()
+                    }
+                })
+                .join(", "),
+        ));
+    }
+    lines
+}
+
+fn defense_lines(
+    combat: &CreatureCombatData,
+    hp: i64,
+    extra: Option<&CreatureExtraData>,
+) -> Vec<StatLine> {
+    let saves = &combat.saving_throws;
+    let mut ac = combat.ac.to_string();
+    if let Some(detail) = extra.and_then(|x| x.ac_detail.as_ref()) {
+        ac.push_str(&format!(" ({detail})"));
+    }
+    let mut ac_line = StatLine::new("AC", ac)
+        .and("Fort", signed(saves.fortitude))
+        .and("Ref", signed(saves.reflex))
+        .and("Will", signed(saves.will));
+    let save_details = [
+        &saves.fortitude_detail,
+        &saves.reflex_detail,
+        &saves.will_detail,
+    ]
+    .into_iter()
+    .flatten()
+    .join(", ");
+    if !save_details.is_empty() {
+        ac_line = ac_line.and("", save_details);
+    }
+
+    let mut hp = hp.to_string();
+    if let Some(detail) = extra.and_then(|x| x.hp_detail.as_ref()) {
+        hp.push_str(&format!(" ({detail})"));
+    }
+    let mut hp_line = StatLine::new("HP", hp);
+    if !combat.immunities.is_empty() {
+        hp_line = hp_line.and("Immunities", combat.immunities.join(", ").to_lowercase());
+    }
+    if !combat.resistances.is_empty() {
+        hp_line = hp_line.and(
+            "Resistances",
+            combat
+                .resistances
+                .iter()
+                .map(|x| {
+                    let mut resistance = format!("{} {}", x.core.name.to_lowercase(), x.core.value);
+                    if !x.exception_vs.is_empty() {
+                        resistance.push_str(&format!(" (except {})", x.exception_vs.join(", ")));
+                    }
+                    if !x.double_vs.is_empty() {
+                        resistance.push_str(&format!(" (double vs. {})", x.double_vs.join(", ")));
+                    }
+                    resistance
+                })
+                .join(", "),
+        );
+    }
+    if !combat.weaknesses.is_empty() {
+        hp_line = hp_line.and(
+            "Weaknesses",
+            combat
+                .weaknesses
+                .iter()
+                .map(|(name, value)| format!("{} {value}", name.to_lowercase()))
+                .join(", "),
+        );
+    }
+    vec![ac_line, hp_line]
+}
+
+/// The land speed comes first, then the other movement types
+fn speeds(extra: &CreatureExtraData) -> String {
+    let is_land =
+        |name: &str| name.eq_ignore_ascii_case("base") || name.eq_ignore_ascii_case("land");
+    extra
+        .speeds
+        .iter()
+        .sorted_by_key(|(name, _)| !is_land(name))
+        .map(|(name, value)| {
+            if is_land(name) {
+                format!("{value} feet")
+            } else {
+                format!("{} {value} feet", name.to_lowercase())
+            }
+        })
+        .join(", ")
+}
+
+fn strike_line(weapon: &Weapon) -> StatLine {
+    let data = &weapon.weapon_data;
+    let mut traits = weapon
+        .item_core
+        .traits
+        .iter()
+        .map(|x| x.name.to_lowercase())
+        .collect::<Vec<_>>();
+    if let Some(range) = &data.range {
+        traits.push(format!(
+            "range increment {}",
+            range.increment.as_ref().unwrap_or(&range.value)
+        ));
+    }
+    let mut attack = format!(
+        "{} {}",
+        weapon.item_core.name.to_lowercase(),
+        signed(data.to_hit_bonus.unwrap_or_default())
+    );
+    if !traits.is_empty() {
+        attack.push_str(&format!(" ({})", traits.join(", ")));
+    }
+    let damage = data
+        .damage_data
+        .iter()
+        .map(|x| {
+            let formula = damage_formula(x.dice.as_ref(), x.bonus_dmg);
+            match &x.dmg_type {
+                Some(dmg_type) => format!("{formula} {}", dmg_type.to_lowercase()),
+                None => formula,
+            }
+        })
+        .chain(
+            data.attack_effects
+                .iter()
+                .map(|x| x.core_action.name.to_lowercase()),
+        )
+        .join(" plus ");
+    let label = match data.weapon_type {
+        WeaponTypeEnum::Ranged => "Ranged",
+        WeaponTypeEnum::Melee | WeaponTypeEnum::Generic => "Melee",
+    };
+    StatLine::new(label, attack)
+        .with_glyph(Some(ActionGlyph::OneAction))
+        .and("Damage", damage)
+}
+
+/// Spells of the entry grouped by rank, the highest first
+fn spells_line(entry: &SpellcasterEntry) -> StatLine {
+    let data = &entry.spellcaster_data;
+    let mut line = StatLine::new(
+        data.spellcasting_name.clone(),
+        format!(
+            "DC {}, attack {}",
+            data.spellcasting_dc_mod,
+            signed(data.spellcasting_atk_mod)
+        ),
+    );
+    for (level, spells) in &entry
+        .spells
+        .iter()
+        .sorted_by_key(|x| -x.level)
+        .chunk_by(|x| x.level)
+    {
+        line = line.and(
+            ordinal(level),
+            spells.map(|x| x.name.to_lowercase()).join(", "),
+        );
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::creature::creature_struct::Creature;
+    use crate::models::stat_block::stat_block_struct::StatBlockFormat;
+    use serde_json::{Value, json};
+
+    #[test]
+    fn follows_the_published_layout() {
+        let trait_data =
+            |name: &str| json!({"name": name, "description": null, "display_name": null});
+        let item_core = |name: &str, traits: Value| {
+            json!({
+                "id": 1, "name": name, "bulk": 0.1, "quantity": 1, "base_item": name,
+                "category": "Martial", "description": "", "hardness": 0, "hp": 0, "level": 0,
+                "price": 10, "usage": "held-in-one-hand", "group": null, "item_type": "Weapon",
+                "material_grade": null, "material_type": null, "number_of_uses": null,
+                "license": "ORC", "remaster": true, "source": "Monster Core", "rarity": "Common",
+                "size": "Small", "traits": traits, "status": "Valid"
+            })
+        };
+        let weapon_data = |bonus_dmg: i64, dmg_type: &str, range: Value, weapon_type: &str| {
+            json!({
+                "id": 1, "to_hit_bonus": 7,
+                "damage_data": [{
+                    "id": 1, "bonus_dmg": bonus_dmg, "dmg_type": dmg_type,
+                    "dice": {"n_of_dices": 1, "dice_size": 6}
+                }],
+                "n_of_potency_runes": 0, "n_of_striking_runes": 0, "property_runes": [],
+                "range": range, "reload": null, "weapon_type": weapon_type, "splash_dmg": null,
+                "attack_effects": []
+            })
+        };
+        let creature: Creature = serde_json::from_value(json!({
+            "core_data": {
+                "essential": {
+                    "id": 1, "aon_id": null, "name": "Goblin War Chanter", "hp": 16,
+                    "base_level": 1, "size": "Small", "family": "Goblin", "rarity": "Common",
+                    "license": "ORC", "remaster": true, "source": "Monster Core",
+                    "cr_type": "Creature", "alignment": "No Alignment", "focus_points": 1,
+                    "status": "Valid"
+                },
+                "derived": {
+                    "archive_link": null,
+                    "attack_data": {"melee": true, "ranged": true, "spellcaster": true},
+                    "role_data": {}
+                },
+                "traits": [trait_data("Goblin"), trait_data("Humanoid")]
+            },
+            "variant_data": {"variant": "Base", "level": 1, "archive_link": null},
+            "extra_data": {
+                "actions": [{
+                    "core_action": {
+                        "id": 1, "name": "Goblin Song", "action_type": "action",
+                        "n_of_actions": 1, "category": "Offensive",
+                        "description": "The goblin sings annoying songs.", "license": "ORC",
+                        "remaster": true, "source": "Monster Core", "slug": null,
+                        "rarity": "Common"
+                    },
+                    "traits": [trait_data("Auditory"), trait_data("Concentrate")]
+                }],
+                "skills": [
+                    {"name": "Performance", "description": null, "modifier": 8, "proficiency": 2},
+                    {"name": "Stealth", "description": null, "modifier": 5, "proficiency": 2},
+                    {
+                        "name": "Lore: Goblin Songs", "description": null, "modifier": 6,
+                        "proficiency": 2
+                    }
+                ],
+                "items": [],
+                "languages": ["Common", "Goblin"],
+                "senses": [
+                    {"id": 1, "name": "Darkvision", "range": null, "acuity": null},
+                    {
+                        "id": 2, "name": "Scent", "acuity": "Imprecise",
+                        "range": {"id": 1, "value": "30 feet", "increment": null, "max": null}
+                    }
+                ],
+                "speeds": {"Base": 25, "climb": 10},
+                "ability_scores": {
+                    "charisma": 3, "constitution": 1, "dexterity": 3,
+                    "intelligence": 0, "strength": 0, "wisdom": 1
+                },
+                "hp_detail": null,
+                "ac_detail": null,
+                "language_detail": null,
+                "perception": 5,
+                "perception_detail": null,
+                "has_vision": true
+            },
+            "combat_data": {
+                "weapons": [
+                    {
+                        "item_core": item_core(
+                            "Dogslicer",
+                            json!([trait_data("Agile"), trait_data("Backstabber")])
+                        ),
+                        "weapon_data": weapon_data(0, "Slashing", Value::Null, "Melee")
+                    },
+                    {
+                        "item_core": item_core("Shortbow", json!([])),
+                        "weapon_data": weapon_data(
+                            1,
+                            "Piercing",
+                            json!({
+                                "id": 1, "value": "60 feet", "increment": "60 feet", "max": null
+                            }),
+                            "Ranged"
+                        )
+                    }
+                ],
+                "armors": [],
+                "shields": [],
+                "resistances": [{
+                    "core": {"id": 1, "name": "Fire", "value": 2},
+                    "double_vs": [],
+                    "exception_vs": ["Cold Iron"]
+                }],
+                "immunities": ["Sleep"],
+                "weaknesses": {"Good": 3},
+                "saving_throws": {
+                    "fortitude": 5, "reflex": 7, "will": 4, "fortitude_detail": null,
+                    "reflex_detail": null, "will_detail": "+1 status vs fear"
+                },
+                "ac": 16,
+                "conditions": []
+            },
+            "spellcaster_data": {
+                "spellcaster_entries": [{
+                    "spellcaster_data": {
+                        "id": 1, "spellcasting_name": "Occult Innate Spells",
+                        "is_spellcasting_flexible": null, "type_of_spellcaster": "Innate",
+                        "spellcasting_dc_mod": 17, "spellcasting_atk_mod": 9,
+                        "spellcasting_tradition": "Occult", "heighten_level": 0
+                    },
+                    "spells": [{
+                        "id": 1, "name": "Fear", "area_type": null, "area_value": null,
+                        "counteraction": false, "basic_saving_throw": false,
+                        "saving_throw": "Will", "sustained": false, "duration": "varies",
+                        "level": 1, "range": null, "target": "1 creature", "actions": "2",
+                        "license": "ORC", "remaster": true, "source": "Player Core",
+                        "rarity": "Common", "slot": 0, "creature_id": 1,
+                        "spellcasting_entry_id": 1
+                    }]
+                }]
+            },
+            "game_system": "pf"
+        }))
+        .unwrap();
+        let markdown = ResponseCreature::from(creature)
+            .to_stat_block()
+            .render(StatBlockFormat::Markdown);
+        for expected in [
+            "### Goblin War Chanter *Creature 1*",
+            "`SMALL` `GOBLIN` `HUMANOID`",
+            "**Perception** +5; darkvision, scent (imprecise) 30 feet",
+            "**Skills** Performance +8, Stealth +5, Lore: Goblin Songs +6",
+            "**Str** +0; **Dex** +3; **Con** +1; **Int** +0; **Wis** +1; **Cha** +3",
+            "**AC** 16; **Fort** +5; **Ref** +7; **Will** +4; +1 status vs fear",
+            "**HP** 16; **Immunities** sleep; **Resistances** fire 2 (except Cold Iron); **Weaknesses** good 3",
+            "**Speed** 25 feet, climb 10 feet",
+            "**Melee** ◆ dogslicer +7 (agile, backstabber); **Damage** 1d6 slashing",
+            "**Ranged** ◆ shortbow +7 (range increment 60 feet); **Damage** 1d6+1 piercing",
+            "**Occult Innate Spells** DC 17, attack +9; **1st** fear",
+            "**Goblin Song** ◆ (auditory, concentrate) The goblin sings annoying songs.",
+        ] {
+            assert!(markdown.contains(expected), "{expected} not in {markdown}");
+        }
+    }
+}
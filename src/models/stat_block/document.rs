@@ -0,0 +1,122 @@
+use crate::models::response_data::ShopListingResponse;
+use crate::models::shearable_data::HydratedEncounterContent;
+use crate::models::stat_block::stat_block_struct::{StatBlock, StatBlockFormat, escape_html};
+use crate::traits::stat_block_renderable::StatBlockRenderable;
+
+/// Stat blocks of a whole shared encounter, for session prep or printing.
+/// Identical creatures and hazards are printed once, with their total quantity.
+pub fn encounter_document(
+    title: &str,
+    encounter: &HydratedEncounterContent,
+    format: StatBlockFormat,
+) -> String {
+    let quantity = |x: u64| i64::try_from(x).unwrap_or(i64::MAX);
+    let blocks = encounter
+        .creatures
+        .iter()
+        .map(|x| (x.entity.to_stat_block(), quantity(x.quantity)))
+        .chain(
+            encounter
+                .hazards
+                .iter()
+                .map(|x| (x.entity.to_stat_block(), quantity(x.quantity))),
+        );
+    render_document(title, merge_quantities(blocks), format)
+}
+
+/// Stat blocks of every item of the shop, printed with the quantity in stock
+pub fn shop_document(title: &str, shop: &ShopListingResponse, format: StatBlockFormat) -> String {
+    let blocks = shop
+        .results
+        .iter()
+        .flatten()
+        .map(|x| (x.to_stat_block(), x.core_item.quantity));
+    render_document(title, merge_quantities(blocks), format)
+}
+
+fn merge_quantities(blocks: impl Iterator<Item = (StatBlock, i64)>) -> Vec<(StatBlock, i64)> {
+    let mut merged: Vec<(StatBlock, i64)> = vec![];
+    for (block, quantity) in blocks {
+        match merged.iter_mut().find(|(x, _)| *x == block) {
+            Some((_, total)) => *total = total.saturating_add(quantity),
+            None => merged.push((block, quantity)),
+        }
+    }
+    merged
+}
+
+fn render_document(title: &str, blocks: Vec<(StatBlock, i64)>, format: StatBlockFormat) -> String {
+    match format {
+        StatBlockFormat::Markdown => {
+            let mut document = vec![format!("# {title}")];
+            document.extend(
+                blocks
+                    .into_iter()
+                    .map(|(block, quantity)| block.to_markdown(Some(quantity))),
+            );
+            document.join("\n\n") + "\n"
+        }
+        StatBlockFormat::Html => {
+            let title = escape_html(title);
+            let mut document = format!(
+                "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+                 <style>.stat-block {{ break-inside: avoid; }}</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+            );
+            for (block, quantity) in blocks {
+                document.push_str(&block.to_html(Some(quantity)));
+            }
+            document.push_str("</body>\n</html>\n");
+            document
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::creature::creature_struct::Creature;
+    use crate::models::response_data::ResponseCreature;
+    use crate::models::shared::game_system_enum::GameSystem;
+    use crate::models::shearable_data::QuantifiedEntity;
+    use serde_json::json;
+
+    #[test]
+    fn identical_creatures_are_printed_once_with_their_quantity() {
+        let core = serde_json::from_value(json!({
+            "essential": {
+                "id": 1, "aon_id": null, "name": "Goblin War Chanter", "hp": 16,
+                "base_level": 1, "size": "Small", "family": "Goblin", "rarity": "Common",
+                "license": "ORC", "remaster": true, "source": "Monster Core",
+                "cr_type": "Creature", "alignment": "No Alignment", "focus_points": 0,
+                "status": "Valid"
+            },
+            "derived": {
+                "archive_link": null,
+                "attack_data": {"melee": true, "ranged": false, "spellcaster": false},
+                "role_data": {}
+            },
+            "traits": []
+        }))
+        .unwrap();
+        let creature = ResponseCreature::from(Creature::from_core(core, GameSystem::Pathfinder));
+        let encounter = HydratedEncounterContent {
+            creatures: vec![
+                QuantifiedEntity {
+                    entity: creature.clone(),
+                    quantity: 1,
+                },
+                QuantifiedEntity {
+                    entity: creature,
+                    quantity: 1,
+                },
+            ],
+            hazards: vec![],
+        };
+        let markdown = encounter_document("Ambush", &encounter, StatBlockFormat::Markdown);
+        assert!(markdown.starts_with("# Ambush\n\n### Goblin War Chanter (×2) *Creature 1*"));
+        assert_eq!(markdown.matches("### ").count(), 1);
+        let html = encounter_document("Ambush", &encounter, StatBlockFormat::Html);
+        assert!(html.contains("<h3>Goblin War Chanter (×2)</h3>"));
+        assert!(html.ends_with("</body>\n</html>\n"));
+    }
+}
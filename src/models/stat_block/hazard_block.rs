@@ -0,0 +1,137 @@
+use crate::models::foundry::inline_markup::{
+    DescriptionFormat, DescriptionRolls, render_description,
+};
+use crate::models::hazard::hazard_field_filter::HazardComplexityEnum;
+use crate::models::response_data::ResponseHazard;
+use crate::models::shared::rarity_enum::RarityEnum;
+use crate::models::stat_block::creature_block::action_line;
+use crate::models::stat_block::stat_block_struct::{StatBlock, StatLine, signed};
+use crate::traits::stat_block_renderable::StatBlockRenderable;
+
+impl StatBlockRenderable for ResponseHazard {
+    fn to_stat_block(&self) -> StatBlock {
+        let hazard = &self.core_hazard;
+        let essential = &hazard.essential;
+
+        let mut stealth = signed(essential.stealth);
+        if !essential.stealth_detail.is_empty() {
+            stealth.push_str(&format!(" ({})", essential.stealth_detail));
+        }
+        let mut detection = vec![StatLine::new("Stealth", stealth)];
+        if !essential.description.is_empty() {
+            detection.push(StatLine::new(
+                "Description",
+                render_description(&essential.description, DescriptionFormat::Text),
+            ));
+        }
+
+        let mut defense = vec![];
+        let mut ac_line = StatLine::new("AC", essential.ac.to_string());
+        for (label, save) in [
+            ("Fort", essential.fortitude),
+            ("Ref", essential.reflex),
+            ("Will", essential.will),
+        ] {
+            if let Some(save) = save {
+                ac_line = ac_line.and(label, signed(save));
+            }
+        }
+        defense.push(ac_line);
+        let mut hardness_line = StatLine::new("Hardness", essential.hardness.to_string());
+        if essential.has_health {
+            hardness_line = hardness_line.and("HP", essential.hp.to_string());
+        }
+        defense.push(hardness_line);
+
+        let mut activity: Vec<_> = hazard.actions.iter().map(action_line).collect();
+        if !essential.routine_description.is_empty() {
+            activity.push(StatLine::new(
+                "Routine",
+                render_description(&essential.routine_description, DescriptionFormat::Text),
+            ));
+        }
+        if !essential.reset_description.is_empty() {
+            activity.push(StatLine::new(
+                "Reset",
+                render_description(&essential.reset_description, DescriptionFormat::Text),
+            ));
+        }
+
+        let mut traits = vec![];
+        if essential.rarity != RarityEnum::Common {
+            traits.push(essential.rarity.to_string());
+        }
+        if essential.complexity == HazardComplexityEnum::Complex {
+            traits.push(String::from("Complex"));
+        }
+        traits.extend(hazard.traits.iter().map(|x| x.name.clone()));
+        StatBlock {
+            name: essential.name.clone(),
+            kind: String::from("Hazard"),
+            level: essential.level,
+            traits,
+            sections: vec![
+                detection,
+                vec![StatLine::new(
+                    "Disable",
+                    render_description(&essential.disable_description, DescriptionFormat::Text),
+                )],
+                defense,
+                activity,
+            ],
+            rolls: DescriptionRolls::from_descriptions(
+                [
+                    &essential.description,
+                    &essential.disable_description,
+                    &essential.routine_description,
+                    &essential.reset_description,
+                ]
+                .into_iter()
+                .chain(hazard.actions.iter().map(|x| &x.core_action.description))
+                .map(String::as_str),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::hazard::hazard_struct::Hazard;
+    use crate::models::shared::game_system_enum::GameSystem;
+    use crate::models::stat_block::stat_block_struct::StatBlockFormat;
+    use serde_json::json;
+
+    #[test]
+    fn saves_are_printed_only_when_the_hazard_has_them() {
+        let hazard: Hazard = serde_json::from_value(json!({
+            "essential": {
+                "id": 1, "name": "Spear Launcher", "ac": 18, "hardness": 8, "has_health": true,
+                "hp": 32, "stealth": 10, "stealth_detail": "trained", "description": "",
+                "disable_description": "", "reset_description": "", "routine_description": "",
+                "complexity": "Simple", "level": 2, "license": "ORC", "remaster": true,
+                "source": "GM Core", "will": null, "reflex": 5, "fortitude": 11,
+                "rarity": "Common", "size": "Medium"
+            },
+            "traits": [],
+            "actions": [{
+                "core_action": {
+                    "id": 1, "name": "Spear", "action_type": "reaction", "n_of_actions": null,
+                    "category": "Offensive", "description": "A spear launches from the wall.",
+                    "license": "ORC", "remaster": true, "source": "GM Core", "slug": null,
+                    "rarity": "Common"
+                },
+                "traits": []
+            }],
+            "game_system": "pf"
+        }))
+        .unwrap();
+        let markdown = ResponseHazard::from((hazard, GameSystem::Pathfinder))
+            .to_stat_block()
+            .render(StatBlockFormat::Markdown);
+        assert!(markdown.contains("**Stealth** +10 (trained)"));
+        assert!(markdown.contains("**AC** 18; **Fort** +11; **Ref** +5\n"));
+        assert!(markdown.contains("**Hardness** 8; **HP** 32"));
+        assert!(markdown.contains("**Spear** ⤾ A spear launches from the wall."));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::models::foundry::foundry_actor::damage_formula;
+use crate::models::foundry::inline_markup::{
+    DescriptionFormat, DescriptionRolls, render_description,
+};
+use crate::models::response_data::ResponseItem;
+use crate::models::shared::rarity_enum::RarityEnum;
+use crate::models::stat_block::stat_block_struct::{StatBlock, StatLine, signed};
+use crate::traits::stat_block_renderable::StatBlockRenderable;
+use itertools::Itertools;
+
+impl StatBlockRenderable for ResponseItem {
+    fn to_stat_block(&self) -> StatBlock {
+        let core = &self.core_item;
+        let mut details = StatLine::new("Price", price(core.price));
+        if let Some(usage) = &core.usage {
+            details = details.and("Usage", usage.replace('-', " "));
+        }
+        details = details.and("Bulk", bulk(core.bulk.into_inner()));
+        let mut lines = vec![details];
+
+        if let Some(weapon) = &self.weapon_data {
+            let damage = weapon
+                .damage_data
+                .iter()
+                .map(|x| {
+                    let formula = damage_formula(x.dice.as_ref(), x.bonus_dmg);
+                    match &x.dmg_type {
+                        Some(dmg_type) => format!("{formula} {}", dmg_type.to_lowercase()),
+                        None => formula,
+                    }
+                })
+                .join(" plus ");
+            let mut line = StatLine::new("Damage", damage);
+            if let Some(category) = &core.category {
+                line = line.and("Category", category.to_lowercase());
+            }
+            if let Some(group) = &core.group {
+                line = line.and("Group", group.to_lowercase());
+            }
+            if let Some(range) = &weapon.range {
+                line = line.and(
+                    "Range",
+                    range
+                        .increment
+                        .clone()
+                        .unwrap_or_else(|| range.value.clone()),
+                );
+            }
+            lines.push(line);
+        }
+        if let Some(armor) = &self.armor_data {
+            lines.push(
+                StatLine::new("AC Bonus", signed(armor.ac_bonus))
+                    .and("Dex Cap", signed(armor.dex_cap))
+                    .and("Check Penalty", armor.check_penalty.to_string())
+                    .and("Speed Penalty", format!("{} ft.", armor.speed_penalty)),
+            );
+        }
+        if let Some(shield) = &self.shield_data {
+            lines.push(
+                StatLine::new("AC Bonus", signed(shield.bonus_ac))
+                    .and("Speed Penalty", format!("{} ft.", shield.speed_penalty)),
+            );
+        }
+        if core.hardness > 0 || core.hp > 0 {
+            lines.push(
+                StatLine::new("Hardness", core.hardness.to_string()).and("HP", core.hp.to_string()),
+            );
+        }
+
+        let mut traits = vec![];
+        if core.rarity != RarityEnum::Common {
+            traits.push(core.rarity.to_string());
+        }
+        traits.extend(core.traits.iter().map(|x| x.name.clone()));
+        StatBlock {
+            name: core.name.clone(),
+            kind: String::from("Item"),
+            level: core.level,
+            traits,
+            sections: vec![
+                lines,
+                vec![StatLine::new(
+                    "",
+                    render_description(&core.description, DescriptionFormat::Text),
+                )],
+            ],
+            rolls: DescriptionRolls::from_descriptions([core.description.as_str()]),
+        }
+    }
+}
+
+/// Prices are stored in cp, printed with the coins they are made of, ex `1 gp, 5 sp`
+fn price(cp: i64) -> String {
+    if cp == 0 {
+        return String::from("—");
+    }
+    [(cp / 100, "gp"), (cp % 100 / 10, "sp"), (cp % 10, "cp")]
+        .into_iter()
+        .filter(|(value, _)| *value != 0)
+        .map(|(value, coin)| format!("{value} {coin}"))
+        .join(", ")
+}
+
+/// Light bulk is stored as 0.1
+fn bulk(value: f64) -> String {
+    if value == 0. {
+        String::from("—")
+    } else if value < 1. {
+        String::from("L")
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, "—")]
+    #[case(5, "5 cp")]
+    #[case(1250, "12 gp, 5 sp")]
+    fn prices_are_printed_by_coin(#[case] cp: i64, #[case] expected: &str) {
+        assert_eq!(expected, price(cp));
+    }
+
+    #[rstest]
+    #[case(0., "—")]
+    #[case(0.1, "L")]
+    #[case(2., "2")]
+    fn bulk_values(#[case] value: f64, #[case] expected: &str) {
+        assert_eq!(expected, bulk(value));
+    }
+}
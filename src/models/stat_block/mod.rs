@@ -0,0 +1,5 @@
+pub mod creature_block;
+pub mod document;
+pub mod hazard_block;
+pub mod item_block;
+pub mod stat_block_struct;
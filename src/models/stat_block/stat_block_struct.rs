@@ -0,0 +1,299 @@
+use crate::models::foundry::inline_markup::DescriptionRolls;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, ToSchema, Display, Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub enum StatBlockFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+/// Action cost, as printed next to the name of an activity
+#[derive(Serialize, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum ActionGlyph {
+    OneAction,
+    TwoActions,
+    ThreeActions,
+    Reaction,
+    FreeAction,
+}
+
+impl ActionGlyph {
+    /// Passive abilities have no glyph
+    pub fn from_action(action_type: &str, n_of_actions: Option<i32>) -> Option<Self> {
+        match (action_type.to_lowercase().as_str(), n_of_actions) {
+            ("reaction", _) => Some(Self::Reaction),
+            ("free", _) => Some(Self::FreeAction),
+            ("action", Some(1)) => Some(Self::OneAction),
+            ("action", Some(2)) => Some(Self::TwoActions),
+            ("action", Some(3)) => Some(Self::ThreeActions),
+            _ => None,
+        }
+    }
+
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::OneAction => "◆",
+            Self::TwoActions => "◆◆",
+            Self::ThreeActions => "◆◆◆",
+            Self::Reaction => "⤾",
+            Self::FreeAction => "◇",
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::OneAction => "Single Action",
+            Self::TwoActions => "Two Actions",
+            Self::ThreeActions => "Three Actions",
+            Self::Reaction => "Reaction",
+            Self::FreeAction => "Free Action",
+        }
+    }
+}
+
+/// Line of a stat block, ex `Melee ◆ jaws +9 (agile); Damage 1d6+4 piercing`.
+/// Every part is a bold label followed by its text, the glyph comes after the first label.
+#[derive(Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct StatLine {
+    pub glyph: Option<ActionGlyph>,
+    pub parts: Vec<(String, String)>,
+}
+
+impl StatLine {
+    pub fn new(label: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            glyph: None,
+            parts: vec![(label.into(), text.into())],
+        }
+    }
+
+    #[must_use]
+    pub fn and(mut self, label: impl Into<String>, text: impl Into<String>) -> Self {
+        self.parts.push((label.into(), text.into()));
+        self
+    }
+
+    #[must_use]
+    pub const fn with_glyph(mut self, glyph: Option<ActionGlyph>) -> Self {
+        self.glyph = glyph;
+        self
+    }
+}
+
+/// Printable stat block, laid out as in the published books:
+/// the name line, the traits, then sections separated by rules.
+#[derive(Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct StatBlock {
+    pub name: String,
+    /// Creature, Hazard or Item, printed along with the level on the name line
+    pub kind: String,
+    pub level: i64,
+    pub traits: Vec<String>,
+    pub sections: Vec<Vec<StatLine>>,
+    /// Checks and damage of the descriptions, which are printed as plain text
+    pub rolls: DescriptionRolls,
+}
+
+impl StatBlock {
+    pub fn render(&self, format: StatBlockFormat) -> String {
+        match format {
+            StatBlockFormat::Markdown => self.to_markdown(None),
+            StatBlockFormat::Html => self.to_html(None),
+        }
+    }
+
+    /// The quantity is printed on the name line when there is more than one
+    pub(crate) fn to_markdown(&self, quantity: Option<i64>) -> String {
+        let mut lines = vec![format!(
+            "### {}{} *{} {}*",
+            self.name,
+            quantity_suffix(quantity),
+            self.kind,
+            self.level
+        )];
+        if !self.traits.is_empty() {
+            lines.push(
+                self.traits
+                    .iter()
+                    .map(|x| format!("`{}`", x.to_uppercase()))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            );
+        }
+        for (i, section) in self.sections.iter().filter(|x| !x.is_empty()).enumerate() {
+            if i > 0 {
+                lines.push(String::from("---"));
+            }
+            lines.extend(section.iter().map(|line| {
+                render_line(
+                    line,
+                    |label| format!("**{label}**"),
+                    |text| text.to_string(),
+                    |glyph| glyph.symbol().to_string(),
+                )
+            }));
+        }
+        lines.join("\n\n")
+    }
+
+    pub(crate) fn to_html(&self, quantity: Option<i64>) -> String {
+        let mut html = format!(
+            "<article class=\"stat-block\">\n<header><h3>{}{}</h3><span class=\"kind\">{} {}</span></header>\n",
+            escape_html(&self.name),
+            quantity_suffix(quantity),
+            escape_html(&self.kind),
+            self.level
+        );
+        if !self.traits.is_empty() {
+            html.push_str("<ul class=\"traits\">");
+            for x in &self.traits {
+                html.push_str(&format!("<li class=\"trait\">{}</li>", escape_html(x)));
+            }
+            html.push_str("</ul>\n");
+        }
+        for (i, section) in self.sections.iter().filter(|x| !x.is_empty()).enumerate() {
+            if i > 0 {
+                html.push_str("<hr>\n");
+            }
+            html.push_str("<section>\n");
+            for line in section {
+                let rendered = render_line(
+                    line,
+                    |label| format!("<strong>{}</strong>", escape_html(label)),
+                    escape_html,
+                    |glyph| {
+                        format!(
+                            "<span class=\"action-glyph\" title=\"{}\">{}</span>",
+                            glyph.label(),
+                            glyph.symbol()
+                        )
+                    },
+                );
+                html.push_str(&format!("<p>{rendered}</p>\n"));
+            }
+            html.push_str("</section>\n");
+        }
+        html.push_str("</article>\n");
+        html
+    }
+}
+
+fn render_line(
+    line: &StatLine,
+    label: impl Fn(&str) -> String,
+    text: impl Fn(&str) -> String,
+    glyph: impl Fn(ActionGlyph) -> String,
+) -> String {
+    line.parts
+        .iter()
+        .enumerate()
+        .map(|(i, (part_label, part_text))| {
+            let mut words = vec![];
+            if !part_label.is_empty() {
+                words.push(label(part_label));
+            }
+            if i == 0
+                && let Some(g) = line.glyph
+            {
+                words.push(glyph(g));
+            }
+            if !part_text.is_empty() {
+                words.push(text(part_text));
+            }
+            words.join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn quantity_suffix(quantity: Option<i64>) -> String {
+    quantity
+        .filter(|x| *x > 1)
+        .map(|x| format!(" (×{x})"))
+        .unwrap_or_default()
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Modifiers are always printed with their sign, ex `+0`
+pub fn signed(value: i64) -> String {
+    format!("{value:+}")
+}
+
+/// Spell and item ranks as printed, ex `1st`, `2nd`, `11th`
+pub fn ordinal(value: i64) -> String {
+    let suffix = match (value % 10, value % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{value}{suffix}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("action", Some(2), Some(ActionGlyph::TwoActions))]
+    #[case("Reaction", None, Some(ActionGlyph::Reaction))]
+    #[case("free", None, Some(ActionGlyph::FreeAction))]
+    #[case("passive", None, None)]
+    fn glyphs_follow_the_action_cost(
+        #[case] action_type: &str,
+        #[case] n_of_actions: Option<i32>,
+        #[case] expected: Option<ActionGlyph>,
+    ) {
+        assert_eq!(
+            expected,
+            ActionGlyph::from_action(action_type, n_of_actions)
+        );
+    }
+
+    #[rstest]
+    #[case(1, "1st")]
+    #[case(2, "2nd")]
+    #[case(3, "3rd")]
+    #[case(4, "4th")]
+    #[case(11, "11th")]
+    #[case(21, "21st")]
+    fn ordinals(#[case] value: i64, #[case] expected: &str) {
+        assert_eq!(expected, ordinal(value));
+    }
+
+    #[rstest]
+    fn lines_render_in_both_formats() {
+        let block = StatBlock {
+            name: String::from("Rat <swarm>"),
+            kind: String::from("Creature"),
+            level: 0,
+            traits: vec![String::from("Animal")],
+            sections: vec![vec![
+                StatLine::new("Melee", "jaws +6")
+                    .with_glyph(Some(ActionGlyph::OneAction))
+                    .and("Damage", "1d6 piercing"),
+            ]],
+            rolls: DescriptionRolls::default(),
+        };
+        assert_eq!(
+            block.render(StatBlockFormat::Markdown),
+            "### Rat <swarm> *Creature 0*\n\n`ANIMAL`\n\n**Melee** ◆ jaws +6; **Damage** 1d6 piercing"
+        );
+        let html = block.render(StatBlockFormat::Html);
+        assert!(html.contains("<h3>Rat &lt;swarm&gt;</h3>"));
+        assert!(html.contains(
+            "<p><strong>Melee</strong> <span class=\"action-glyph\" title=\"Single Action\">◆</span> jaws +6; <strong>Damage</strong> 1d6 piercing</p>"
+        ));
+    }
+}
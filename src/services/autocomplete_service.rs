@@ -0,0 +1,17 @@
+use crate::AppState;
+use crate::db::autocomplete_proxy;
+use crate::models::shared::autocomplete::{AutocompleteQuery, AutocompleteSuggestion};
+use crate::models::shared::game_system_enum::GameSystem;
+
+/// Returns the top name suggestions for the query across creatures, hazards, items and traits.
+pub async fn get_autocomplete_suggestions(
+    app_state: &AppState,
+    query: &AutocompleteQuery,
+    gs: GameSystem,
+) -> Vec<AutocompleteSuggestion> {
+    if query.query.trim().is_empty() {
+        return vec![];
+    }
+    autocomplete_proxy::get_autocomplete_suggestions(app_state, gs, &query.query, query.limit())
+        .await
+}
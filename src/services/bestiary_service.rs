@@ -4,6 +4,10 @@ use crate::db::bestiary_proxy::get_creatures_passing_all_filters;
 use crate::models::bestiary_structs::{
     BestiaryFilterQuery, BestiaryPaginatedRequest, BestiaryRanges,
 };
+use crate::models::creature::creature_comparison::{
+    CreatureComparisonRequest, CreatureComparisonResponse, MAX_CREATURES_TO_COMPARE,
+    MIN_CREATURES_TO_COMPARE,
+};
 use crate::models::creature::creature_field_filter::CreatureFieldFilters;
 use crate::models::creature::creature_filter_enum::CreatureFilter;
 use crate::models::creature::creature_metadata::creature_role::CreatureRoleEnum;
@@ -12,8 +16,9 @@ use crate::models::creature::creature_struct::Creature;
 pub use crate::models::response_data::{
     BestiaryResponse, CreatureResponseDataModifiers, ResponseCreature, convert_result_to_response,
 };
+use crate::models::shared::facets::BestiaryFacets;
 use crate::models::shared::game_system_enum::GameSystem;
-use anyhow::Result;
+use anyhow::{Result, bail};
 use std::collections::HashMap;
 
 pub async fn get_creature(
@@ -52,6 +57,49 @@ pub async fn get_weak_creature(
     }
 }
 
+/// Fetches every requested creature with all its data, applying the requested variant and PWL,
+/// and compares them statistic by statistic.
+///
+/// Deltas and gained/lost traits are relative
+/// to the first creature of the request.
+pub async fn get_creatures_comparison(
+    app_state: &AppState,
+    comparison_request: &CreatureComparisonRequest,
+    gs: GameSystem,
+) -> Result<CreatureComparisonResponse> {
+    if !comparison_request.is_valid() {
+        bail!(
+            "Creature comparison requires between {MIN_CREATURES_TO_COMPARE} and {MAX_CREATURES_TO_COMPARE} creatures"
+        );
+    }
+    let mut creatures = Vec::with_capacity(comparison_request.creatures.len());
+    for entry in &comparison_request.creatures {
+        let is_pwl_on = entry.is_pwl_on.unwrap_or(false);
+        let response_data_mods = CreatureResponseDataModifiers {
+            is_pwl_on: Some(is_pwl_on),
+            extra_data: Some(true),
+            combat_data: Some(true),
+            spellcasting_data: Some(true),
+        };
+        let Some(creature) = bestiary_proxy::get_creature_by_id(
+            app_state,
+            gs,
+            entry.id,
+            entry.variant.unwrap_or_default(),
+            &response_data_mods,
+        )
+        .await
+        else {
+            bail!("Creature with id {} could not be found", entry.id);
+        };
+        creatures.push((creature, is_pwl_on));
+    }
+    let scales = bestiary_proxy::get_creature_scales(app_state).await?;
+    Ok(CreatureComparisonResponse::from_creatures(
+        &creatures, &scales,
+    ))
+}
+
 pub async fn get_bestiary_listing(
     app_state: &AppState,
     field_filter: &CreatureFieldFilters,
@@ -64,6 +112,16 @@ pub async fn get_bestiary_listing(
     )
 }
 
+/// Counts, for each filterable dimension, how many creatures every value would lead to
+/// when combined with the other active filters.
+pub async fn get_bestiary_facets(
+    app_state: &AppState,
+    field_filter: &CreatureFieldFilters,
+    gs: GameSystem,
+) -> Option<BestiaryFacets> {
+    bestiary_proxy::get_bestiary_facets(app_state, gs, field_filter).await
+}
+
 pub async fn get_families_list(app_state: &AppState, gs: GameSystem) -> Vec<String> {
     bestiary_proxy::get_all_possible_values_of_filter(app_state, gs, CreatureFilter::Family).await
 }
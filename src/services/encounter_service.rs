@@ -19,6 +19,7 @@ use crate::services::encounter_handler::encounter_calculator::{
     get_encounter_info, get_hazard_lvl_combinations, get_scaled_exp,
 };
 use crate::services::hazard_service::get_filtered_hazards;
+use crate::traits::proficiency_without_level::ProficiencyWithoutLevel;
 use anyhow::{bail, ensure};
 use itertools::Itertools;
 use std::collections::BTreeMap;
@@ -125,6 +126,25 @@ async fn calculate_random_creature_encounter(
                     || vec![true, false],
                     |x| vec![*x.get("spellcaster").unwrap_or(&false)],
                 ),
+                immunity_whitelist_filter: enc_data.immunity_whitelist_filter.unwrap_or_default(),
+                immunity_blacklist_filter: enc_data.immunity_blacklist_filter.unwrap_or_default(),
+                resistance_whitelist_filter: enc_data
+                    .resistance_whitelist_filter
+                    .unwrap_or_default(),
+                resistance_blacklist_filter: enc_data
+                    .resistance_blacklist_filter
+                    .unwrap_or_default(),
+                weakness_whitelist_filter: enc_data.weakness_whitelist_filter.unwrap_or_default(),
+                weakness_blacklist_filter: enc_data.weakness_blacklist_filter.unwrap_or_default(),
+                speed_type_filter: enc_data.speed_type_filter.unwrap_or_default(),
+                min_speed_filter: enc_data.min_speed_filter,
+                sense_filter: enc_data.sense_filter.unwrap_or_default(),
+                language_filter: enc_data.language_filter.unwrap_or_default(),
+                spell_tradition_filter: enc_data.spell_tradition_filter.unwrap_or_default(),
+                spellcaster_type_filter: enc_data.spellcaster_type_filter.unwrap_or_default(),
+                spell_name_filter: enc_data.spell_name_filter.unwrap_or_default(),
+                min_spell_dc_filter: enc_data.min_spell_dc_filter,
+                max_spell_dc_filter: enc_data.max_spell_dc_filter,
                 supported_version: enc_data
                     .game_system_version
                     .unwrap_or_default()
@@ -133,6 +153,7 @@ async fn calculate_random_creature_encounter(
             },
             trait_whitelist_filter: enc_data.trait_whitelist_filter.unwrap_or_default(),
             trait_blacklist_filter: enc_data.trait_blacklist_filter.unwrap_or_default(),
+            trait_expression_filter: enc_data.trait_expression_filter,
         },
         enc_data.allow_weak_variants.is_some_and(|x| x),
         enc_data.allow_elite_variants.is_some_and(|x| x),
@@ -150,6 +171,7 @@ async fn calculate_random_creature_encounter(
     Ok(RandomCreatureGeneratorResponse {
         count: chosen_encounter.len(),
         results: Some(
+            // Only the core data is fetched, PWL leaves it as is
             chosen_encounter
                 .into_iter()
                 .map(ResponseCreature::from)
@@ -212,6 +234,7 @@ async fn calculate_random_hazard_encounter(
             },
             trait_whitelist_filter: enc_data.trait_whitelist_filter.unwrap_or_default(),
             trait_blacklist_filter: enc_data.trait_blacklist_filter.unwrap_or_default(),
+            trait_expression_filter: enc_data.trait_expression_filter,
         },
         gs,
     )
@@ -294,6 +317,12 @@ async fn calculate_random_encounter(
     let (hazards, hz_count) = hazard_result
         .inspect_err(|e| debug!("Failed to calculate hazard encounter: {e}"))
         .map_or((None, 0), |e| (e.results, e.count));
+    // PWL is chosen along with the creatures, it applies to the whole encounter
+    let hazards = hazards.map(|hz| {
+        hz.into_iter()
+            .map(|x| x.with_pwl(is_pwl_on))
+            .collect::<Vec<_>>()
+    });
 
     if creatures.is_none() && hazards.is_none() {
         bail!("Both creature and hazard encounter failed to generate");
@@ -0,0 +1,78 @@
+use crate::AppState;
+use crate::db::{bestiary_proxy, hazard_proxy};
+use crate::models::creature::creature_metadata::variant_enum::CreatureVariant;
+use crate::models::foundry::compendium::FoundryPack;
+use crate::models::response_data::{
+    CreatureResponseDataModifiers, RandomEncounterGeneratorResponse, ShopListingResponse,
+};
+use crate::models::shared::game_system_enum::GameSystem;
+use crate::services::share_service;
+use crate::traits::foundry_exportable::FoundryExportable;
+use crate::traits::proficiency_without_level::ProficiencyWithoutLevel;
+use serde_json::Value;
+
+/// Exports the creature, with its variant and PWL applied, as a Foundry actor.
+/// Every section of the creature is fetched, so that the actor is complete.
+pub async fn export_creature(
+    app_state: &AppState,
+    id: i64,
+    variant: CreatureVariant,
+    is_pwl_on: bool,
+    gs: GameSystem,
+) -> Option<Value> {
+    bestiary_proxy::get_creature_by_id(
+        app_state,
+        gs,
+        id,
+        variant,
+        &CreatureResponseDataModifiers::all(is_pwl_on),
+    )
+    .await
+    .map(|x| x.to_foundry_actor())
+}
+
+/// Exports the hazard, with PWL applied, as a Foundry actor.
+pub async fn export_hazard(
+    app_state: &AppState,
+    id: i64,
+    is_pwl_on: bool,
+    gs: GameSystem,
+) -> Option<Value> {
+    hazard_proxy::get_hazard_by_id(app_state, gs, id)
+        .await
+        .map(|x| x.with_pwl(is_pwl_on).core_hazard.to_foundry_actor())
+}
+
+/// Exports a generated encounter as a folder of actors, exported with the data it was generated with.
+pub fn export_generated_encounter(
+    name: &str,
+    encounter: &RandomEncounterGeneratorResponse,
+) -> FoundryPack {
+    FoundryPack::from_encounter(name, &encounter.results)
+}
+
+/// Exports a shared encounter, named as it was shared, as a folder of actors.
+/// Creatures are fetched with all their data, so that the actors are complete.
+pub async fn export_shared_encounter(
+    app_state: &AppState,
+    share_code: String,
+    is_pwl_on: bool,
+    gs: GameSystem,
+) -> anyhow::Result<FoundryPack> {
+    let encounter = share_service::hydrate_shared_encounter(
+        app_state,
+        share_code,
+        vec![],
+        &CreatureResponseDataModifiers::all(is_pwl_on),
+        gs,
+    )
+    .await?;
+    Ok(FoundryPack::from_shared_encounter(
+        &encounter.encounter_name,
+        &encounter.results,
+    ))
+}
+
+pub fn export_shop(name: &str, shop: &ShopListingResponse) -> FoundryPack {
+    FoundryPack::from_shop(name, shop)
+}
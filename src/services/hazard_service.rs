@@ -10,18 +10,24 @@ use crate::models::hazard::hazard_struct::{Hazard, HazardRanges};
 use crate::models::response_data::{
     HazardListingResponse, ResponseHazard, convert_result_to_response,
 };
+use crate::models::shared::facets::HazardFacets;
 use crate::models::shared::game_system_enum::GameSystem;
+use crate::traits::proficiency_without_level::ProficiencyWithoutLevel;
 use anyhow::Result;
 use std::collections::HashMap;
 
+/// With PWL on, the level is removed from AC, saving throws, stealth and DCs
 pub async fn get_hazard(
     app_state: &AppState,
     id: i64,
     gs: GameSystem,
+    is_pwl_on: bool,
 ) -> HashMap<String, Option<ResponseHazard>> {
     hashmap! {
         String::from("results") =>
-        hazard_proxy::get_hazard_by_id(app_state, gs, id).await
+        hazard_proxy::get_hazard_by_id(app_state, gs, id)
+            .await
+            .map(|x| x.with_pwl(is_pwl_on))
     }
 }
 
@@ -37,6 +43,16 @@ pub async fn get_hazard_listing(
     )
 }
 
+/// Counts, for each filterable dimension, how many hazards every value would lead to
+/// when combined with the other active filters.
+pub async fn get_hazard_facets(
+    app_state: &AppState,
+    field_filter: &HazardFieldFilters,
+    gs: GameSystem,
+) -> Option<HazardFacets> {
+    hazard_proxy::get_hazard_facets(app_state, gs, field_filter).await
+}
+
 pub async fn get_traits_list(app_state: &AppState, gs: GameSystem) -> Vec<String> {
     hazard_proxy::get_all_traits(app_state, gs).await
 }
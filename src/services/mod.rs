@@ -1,7 +1,12 @@
+pub mod autocomplete_service;
 pub mod bestiary_service;
 pub mod encounter_handler;
 pub mod encounter_service;
+pub mod foundry_service;
 pub mod hazard_service;
 pub mod npc_service;
+pub mod share_qr_service;
+pub mod share_service;
 pub mod shop_service;
+pub mod stat_block_service;
 pub mod url_calculator;
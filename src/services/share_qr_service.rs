@@ -0,0 +1,218 @@
+use crate::AppState;
+use crate::models::share_envelope::{
+    DecodedShare, SharePayload, SharedPayload, decode_any_share_with_limits, encode_share,
+};
+use crate::models::share_limits::ShareDecodeLimits;
+use crate::models::share_qr::{QrImages, QrOptions, ShareQrCode};
+use crate::models::share_signing::{ShareSigningKeys, ShareVerification};
+use crate::models::shared::game_system_enum::GameSystem;
+use crate::traits::base64::base64_encode::Base64Encode;
+use anyhow::{bail, ensure};
+use qrcode::render::svg;
+use qrcode::{Color, QrCode};
+use tracing::warn;
+
+/// Modules of light border around the code, the minimum required by the QR spec
+const QUIET_ZONE_MODULES: usize = 4;
+/// Side of the largest PNG rendered, in pixels, so that one is at most 4 MiB in memory
+const MAX_PNG_SIDE: usize = 2048;
+
+/// Renders a share code as a QR code. Codes too long to fit are shortened by dropping
+/// the names of the shared encounter, shop or npc list.
+pub async fn generate_share_qr_code(
+    app_state: &AppState,
+    share_code: String,
+    options: &QrOptions,
+) -> anyhow::Result<ShareQrCode> {
+    if fits_in_qr_code(&share_code, options) {
+        return Ok(ShareQrCode {
+            images: render_qr_code(&share_code, options)?,
+            share_code,
+            shortened: false,
+        });
+    }
+    warn!(
+        "Share code of {} characters exceeds the QR code capacity, dropping its names",
+        share_code.len()
+    );
+    let shortened = shorten_share_code(
+        &share_code,
+        &app_state.share_decode_limits,
+        &app_state.share_signing_keys,
+    )
+    .await?;
+    ensure!(
+        fits_in_qr_code(&shortened, options),
+        "The share code is too long for a QR code, even without names"
+    );
+    Ok(ShareQrCode {
+        images: render_qr_code(&shortened, options)?,
+        share_code: shortened,
+        shortened: true,
+    })
+}
+
+pub fn fits_in_qr_code(data: &str, options: &QrOptions) -> bool {
+    QrCode::with_error_correction_level(data, options.error_correction.into()).is_ok()
+}
+
+pub fn render_qr_code(data: &str, options: &QrOptions) -> anyhow::Result<QrImages> {
+    ensure!(
+        (1..=64).contains(&options.module_size),
+        "The module size must be between 1 and 64 pixels"
+    );
+    let code = QrCode::with_error_correction_level(data, options.error_correction.into())?;
+    let svg = code
+        .render::<svg::Color>()
+        .quiet_zone(true)
+        .module_dimensions(options.module_size, options.module_size)
+        .build();
+    let (png, size) = render_png(&code, options.module_size)?;
+    Ok(QrImages { svg, png, size })
+}
+
+/// Grayscale PNG, one byte per pixel
+fn render_png(code: &QrCode, module_size: u32) -> anyhow::Result<(Vec<u8>, u32)> {
+    let modules = code.width() + 2 * QUIET_ZONE_MODULES;
+    let scale = module_size as usize;
+    let side = modules * scale;
+    ensure!(
+        side <= MAX_PNG_SIDE,
+        "The QR code would be {side} pixels wide, at most {MAX_PNG_SIDE} are allowed, use a smaller module size"
+    );
+    let colors = code.to_colors();
+    let mut pixels = vec![u8::MAX; side * side];
+    for (i, color) in colors.iter().enumerate() {
+        if *color == Color::Light {
+            continue;
+        }
+        let x = (i % code.width() + QUIET_ZONE_MODULES) * scale;
+        let y = (i / code.width() + QUIET_ZONE_MODULES) * scale;
+        for row in y..y + scale {
+            pixels[row * side + x..row * side + x + scale].fill(0);
+        }
+    }
+    let side = u32::try_from(side)?;
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, side, side);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+    Ok((png, side))
+}
+
+/// Re-encodes the share code without names. Codes are signed again only when their signature
+/// was verified, so that shortening can not be used to sign arbitrary codes.
+async fn shorten_share_code(
+    share_code: &str,
+    limits: &ShareDecodeLimits,
+    keys: &ShareSigningKeys,
+) -> anyhow::Result<String> {
+    let DecodedShare {
+        game,
+        verification,
+        payload,
+        ..
+    } = decode_any_share_with_limits(share_code, limits, keys).await?;
+    let shortened = match payload {
+        SharedPayload::Encounter(mut x) => {
+            x.encounter_name = String::new();
+            encode_in_same_format(x, game).await?
+        }
+        SharedPayload::Shop(mut x) => {
+            x.shop_name = String::new();
+            encode_in_same_format(x, game).await?
+        }
+        SharedPayload::NpcList(mut x) => {
+            x.list_name = String::new();
+            encode_in_same_format(x, game).await?
+        }
+        SharedPayload::GeneratorRecipe(_) => {
+            bail!("Generator recipes have no optional fields to drop")
+        }
+    };
+    Ok(match verification {
+        ShareVerification::Verified { .. } => keys.sign(shortened),
+        ShareVerification::Unverified => shortened,
+    })
+}
+
+/// Codes without envelope are kept without it, they do not say which game system they are for
+async fn encode_in_same_format<T: SharePayload + Base64Encode>(
+    payload: T,
+    game: Option<GameSystem>,
+) -> anyhow::Result<String> {
+    match game {
+        Some(game) => encode_share(payload, game).await,
+        None => payload.encode().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::share_qr::QrErrorCorrection;
+    use crate::models::shearable_data::{ShareableItem, ShareableShop};
+    use nanorand::{Rng, WyRand};
+
+    #[test]
+    fn renders_svg_and_png() {
+        let options = QrOptions::default();
+        let images = render_qr_code("KLUv_QBYWQAAA09zaQIAAQABAQA=", &options).unwrap();
+        assert!(images.svg.contains("<svg"));
+        assert!(images.png.starts_with(b"\x89PNG"));
+        assert_eq!(images.size % options.module_size, 0);
+        assert!(
+            render_qr_code(
+                "osi",
+                &QrOptions {
+                    module_size: 0,
+                    ..options
+                }
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn oversized_png_are_rejected() {
+        let options = QrOptions {
+            module_size: 64,
+            ..QrOptions::default()
+        };
+        assert!(render_qr_code("osi", &options).is_ok());
+        assert!(render_qr_code(&"A".repeat(2000), &options).is_err());
+    }
+
+    #[tokio::test]
+    async fn long_codes_are_shortened_by_dropping_names() {
+        let options = QrOptions {
+            error_correction: QrErrorCorrection::High,
+            ..QrOptions::default()
+        };
+        let mut rng = WyRand::new_seed(42);
+        let shop = ShareableShop {
+            // Random letters, so that compression can not make the name short
+            shop_name: (0..3000)
+                .map(|_| char::from(b'a' + rng.generate_range(0..26_u8)))
+                .collect(),
+            items_data: vec![ShareableItem {
+                id: 1,
+                qty: 1,
+                game: GameSystem::Pathfinder,
+            }],
+        };
+        let code = encode_share(shop, GameSystem::Pathfinder).await.unwrap();
+        assert!(!fits_in_qr_code(&code, &options));
+        let shortened = shorten_share_code(
+            &code,
+            &ShareDecodeLimits::default(),
+            &ShareSigningKeys::default(),
+        )
+        .await
+        .unwrap();
+        assert!(fits_in_qr_code(&shortened, &options));
+    }
+}
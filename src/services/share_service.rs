@@ -0,0 +1,500 @@
+use crate::AppState;
+use crate::db::{bestiary_proxy, hazard_proxy, shop_proxy};
+use crate::models::creature::creature_struct::Creature;
+use crate::models::encounter_structs::{
+    CreatureEncounterParams, EncounterParams, HazardEncounterElement, HazardEncounterParams,
+};
+use crate::models::response_data::{
+    CreatureResponseDataModifiers, ResponseCreature, ResponseHazard, ResponseItem,
+    ShopListingResponse,
+};
+use crate::models::share_envelope::{DecodedShare, decode_verified_share, encode_signed_share};
+use crate::models::share_limits::ShareDecodeLimits;
+use crate::models::shared::game_system_enum::GameSystem;
+use crate::models::shared::trait_filter_expression::TraitFilterExpression;
+use crate::models::shearable_data::{
+    HydratedEncounterContent, HydratedEncounterResponse, HydratedShopResponse,
+    ImportedGeneratorRecipe, MissingRecipeValues, QuantifiedEntity, ShareableEncounter,
+    ShareableGeneratorRecipe, ShareableNpcList, ShareableShop, UnresolvedShareIds,
+};
+use crate::services::encounter_handler::encounter_calculator::get_encounter_info;
+use crate::services::{bestiary_service, hazard_service, shop_service};
+use crate::traits::proficiency_without_level::ProficiencyWithoutLevel;
+use anyhow::ensure;
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::iter::repeat_n;
+
+/// Decodes a shared encounter, of any share format version and verifying its signature if any,
+/// and fetches its creatures, with their variant applied, and hazards, with PWL applied to both.
+///
+/// The encounter info is computed only when `party_levels` is not empty.
+/// Entries of another game system, or whose id does not resolve anymore, are reported as unresolved.
+pub async fn hydrate_shared_encounter(
+    app_state: &AppState,
+    share_code: String,
+    party_levels: Vec<i64>,
+    response_data_mods: &CreatureResponseDataModifiers,
+    gs: GameSystem,
+) -> anyhow::Result<HydratedEncounterResponse> {
+    let DecodedShare {
+        payload: encounter,
+        verification,
+        ..
+    } = decode_verified_share::<ShareableEncounter>(
+        &share_code,
+        &app_state.share_decode_limits,
+        &app_state.share_signing_keys,
+    )
+    .await?;
+    let mut fetched_creatures: HashMap<_, Creature> = HashMap::new();
+    let creatures_by_variant = encounter
+        .creatures_data
+        .iter()
+        .filter(|x| x.game == gs)
+        .into_group_map_by(|x| x.variant);
+    for (variant, entries) in creatures_by_variant {
+        let ids = valid_ids(entries.iter().map(|x| x.id));
+        for cr in
+            bestiary_proxy::get_creatures_by_ids(app_state, gs, &ids, variant, response_data_mods)
+                .await?
+        {
+            fetched_creatures.insert((variant, cr.core_data.essential.id), cr);
+        }
+    }
+    let hazard_ids = valid_ids(
+        encounter
+            .hazards_data
+            .iter()
+            .filter(|x| x.game == gs)
+            .map(|x| x.id),
+    );
+    let fetched_hazards: HashMap<i64, ResponseHazard> =
+        hazard_proxy::get_hazards_by_ids(app_state, gs, &hazard_ids)
+            .await?
+            .into_iter()
+            .map(|x| (x.core_hazard.essential.id, x))
+            .collect();
+
+    let limits = &app_state.share_decode_limits;
+    let (creatures, unresolved_creatures) = pair_with_quantity(
+        "creatures",
+        limits,
+        encounter.creatures_data.iter().map(|x| {
+            let creature = i64::try_from(x.id)
+                .ok()
+                .filter(|_| x.game == gs)
+                .and_then(|id| fetched_creatures.get(&(x.variant, id)))
+                .cloned()
+                .map(ResponseCreature::from);
+            (x.id, x.qty, creature)
+        }),
+    )?;
+    let is_pwl_on = response_data_mods.is_pwl_on.unwrap_or(false);
+    let (hazards, unresolved_hazards) = pair_with_quantity(
+        "hazards",
+        limits,
+        encounter.hazards_data.iter().map(|x| {
+            let hazard = i64::try_from(x.id)
+                .ok()
+                .filter(|_| x.game == gs)
+                .and_then(|id| fetched_hazards.get(&id))
+                .cloned()
+                .map(|hz| hz.with_pwl(is_pwl_on));
+            (x.id, x.qty, hazard)
+        }),
+    )?;
+
+    let encounter_info = (!party_levels.is_empty()).then(|| {
+        get_encounter_info(&EncounterParams {
+            party_levels,
+            creatures_params: Some(CreatureEncounterParams {
+                enemy_levels: creatures
+                    .iter()
+                    .flat_map(|x| repeat_n(x.entity.variant_data.level, units(x.quantity)))
+                    .collect(),
+                is_pwl_on,
+            }),
+            hazards_params: Some(HazardEncounterParams {
+                hazards: hazards
+                    .iter()
+                    .flat_map(|x| {
+                        let element = HazardEncounterElement {
+                            complexity: x.entity.core_hazard.essential.complexity,
+                            level: x.entity.core_hazard.essential.level,
+                        };
+                        repeat_n(element, units(x.quantity))
+                    })
+                    .collect(),
+            }),
+        })
+    });
+    Ok(HydratedEncounterResponse {
+        encounter_name: encounter.encounter_name,
+        count: creatures
+            .iter()
+            .map(|x| units(x.quantity))
+            .chain(hazards.iter().map(|x| units(x.quantity)))
+            .sum(),
+        results: HydratedEncounterContent { creatures, hazards },
+        encounter_info,
+        unresolved: UnresolvedShareIds {
+            creatures: unresolved_creatures,
+            hazards: unresolved_hazards,
+            items: vec![],
+        },
+        game: gs,
+        verification,
+    })
+}
+
+/// Decodes a shared shop and fetches its items, each one with the quantity it was shared with.
+/// Entries of another game system, or whose id does not resolve anymore, are reported as unresolved.
+pub async fn hydrate_shared_shop(
+    app_state: &AppState,
+    share_code: String,
+    gs: GameSystem,
+) -> anyhow::Result<HydratedShopResponse> {
+    let DecodedShare {
+        payload: shop,
+        verification,
+        ..
+    } = decode_verified_share::<ShareableShop>(
+        &share_code,
+        &app_state.share_decode_limits,
+        &app_state.share_signing_keys,
+    )
+    .await?;
+    let item_ids = valid_ids(
+        shop.items_data
+            .iter()
+            .filter(|x| x.game == gs)
+            .map(|x| x.id),
+    );
+    let fetched_items: HashMap<i64, ResponseItem> =
+        shop_proxy::get_items_by_ids(app_state, gs, &item_ids)
+            .await?
+            .into_iter()
+            .map(|x| (x.core_item.id, x))
+            .collect();
+    let mut items = Vec::with_capacity(shop.items_data.len());
+    let mut unresolved_items = Vec::new();
+    for entry in &shop.items_data {
+        let item = i64::try_from(entry.id)
+            .ok()
+            .filter(|_| entry.game == gs)
+            .and_then(|id| fetched_items.get(&id));
+        match item {
+            Some(item) => {
+                let mut item = item.clone();
+                item.core_item.quantity = i64::try_from(entry.qty).unwrap_or(i64::MAX);
+                items.push(item);
+            }
+            None => unresolved_items.push(entry.id),
+        }
+    }
+    let total_price = items.iter().fold(0i64, |total, x| {
+        total.saturating_add(x.core_item.price.saturating_mul(x.core_item.quantity))
+    });
+    Ok(HydratedShopResponse {
+        shop_name: shop.shop_name,
+        listing: ShopListingResponse {
+            count: items.len(),
+            total: items.len(),
+            results: Some(items),
+            game: gs,
+            next: None,
+            search_matches: None,
+        },
+        total_price,
+        unresolved: UnresolvedShareIds {
+            items: unresolved_items.into_iter().unique().collect(),
+            ..UnresolvedShareIds::default()
+        },
+        verification,
+    })
+}
+
+/// Shared npc lists already carry every npc, there is nothing left to fetch.
+pub async fn hydrate_shared_npc_list(
+    app_state: &AppState,
+    share_code: String,
+) -> anyhow::Result<DecodedShare<ShareableNpcList>> {
+    decode_verified_share(
+        &share_code,
+        &app_state.share_decode_limits,
+        &app_state.share_signing_keys,
+    )
+    .await
+}
+
+/// Encodes the input of a generator, signing it if signing keys are configured
+pub async fn share_generator_recipe(
+    app_state: &AppState,
+    recipe: ShareableGeneratorRecipe,
+    gs: GameSystem,
+) -> anyhow::Result<String> {
+    ensure!(recipe.is_valid(), "The generator recipe is not valid");
+    encode_signed_share(recipe, gs, &app_state.share_signing_keys).await
+}
+
+/// Decodes a shared generator recipe, so that it can be fed to its generator as is.
+/// Sources, traits and families it filters on that no longer exist are reported as missing.
+pub async fn import_generator_recipe(
+    app_state: &AppState,
+    share_code: String,
+    gs: GameSystem,
+) -> anyhow::Result<ImportedGeneratorRecipe> {
+    let decoded = decode_verified_share::<ShareableGeneratorRecipe>(
+        &share_code,
+        &app_state.share_decode_limits,
+        &app_state.share_signing_keys,
+    )
+    .await?;
+    ensure!(
+        decoded.game == Some(gs),
+        "The generator recipe was not shared for {gs}"
+    );
+    let recipe = decoded.payload;
+    ensure!(recipe.is_valid(), "The generator recipe is not valid");
+    let missing = match &recipe {
+        ShareableGeneratorRecipe::Encounter(x) => {
+            let creature_data = x.creature_data.clone().unwrap_or_default();
+            let hazard_data = x.hazard_data.clone().unwrap_or_default();
+            let creature_traits = bestiary_service::get_traits_list(app_state, gs).await;
+            let hazard_traits = hazard_service::get_traits_list(app_state, gs).await;
+            let creature_sources = bestiary_service::get_sources_list(app_state, gs).await;
+            let hazard_sources = hazard_service::get_sources_list(app_state, gs).await;
+            MissingRecipeValues {
+                sources: missing_values(&creature_data.source_filter, &creature_sources)
+                    .into_iter()
+                    .chain(missing_values(&hazard_data.source_filter, &hazard_sources))
+                    .unique()
+                    .collect(),
+                traits: missing_traits(
+                    [
+                        &creature_data.trait_whitelist_filter,
+                        &creature_data.trait_blacklist_filter,
+                    ],
+                    creature_data.trait_expression_filter.as_ref(),
+                    &creature_traits,
+                )
+                .into_iter()
+                .chain(missing_traits(
+                    [
+                        &hazard_data.trait_whitelist_filter,
+                        &hazard_data.trait_blacklist_filter,
+                    ],
+                    hazard_data.trait_expression_filter.as_ref(),
+                    &hazard_traits,
+                ))
+                .unique()
+                .collect(),
+                families: missing_values(
+                    &creature_data.family_filter,
+                    &bestiary_service::get_families_list(app_state, gs).await,
+                ),
+            }
+        }
+        ShareableGeneratorRecipe::PfShop(x) => {
+            shop_missing_values(
+                app_state,
+                gs,
+                &x.source_filter,
+                [&x.trait_whitelist_filter, &x.trait_blacklist_filter],
+                x.trait_expression_filter.as_ref(),
+            )
+            .await
+        }
+        ShareableGeneratorRecipe::SfShop(x) => {
+            shop_missing_values(
+                app_state,
+                gs,
+                &x.source_filter,
+                [&x.trait_whitelist_filter, &x.trait_blacklist_filter],
+                x.trait_expression_filter.as_ref(),
+            )
+            .await
+        }
+        // Npc recipes only filter on values known at compile time
+        ShareableGeneratorRecipe::PfNpc(_) | ShareableGeneratorRecipe::SfNpc(_) => {
+            MissingRecipeValues::default()
+        }
+    };
+    Ok(ImportedGeneratorRecipe {
+        recipe,
+        missing,
+        verification: decoded.verification,
+    })
+}
+
+async fn shop_missing_values(
+    app_state: &AppState,
+    gs: GameSystem,
+    source_filter: &Option<Vec<String>>,
+    trait_filters: [&Option<Vec<String>>; 2],
+    trait_expression_filter: Option<&TraitFilterExpression>,
+) -> MissingRecipeValues {
+    MissingRecipeValues {
+        sources: missing_values(
+            source_filter,
+            &shop_service::get_sources_list(app_state, gs).await,
+        ),
+        traits: missing_traits(
+            trait_filters,
+            trait_expression_filter,
+            &shop_service::get_traits_list(app_state, gs).await,
+        ),
+        families: vec![],
+    }
+}
+
+fn missing_traits(
+    trait_filters: [&Option<Vec<String>>; 2],
+    trait_expression_filter: Option<&TraitFilterExpression>,
+    existing: &[String],
+) -> Vec<String> {
+    let requested = trait_filters
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(String::as_str)
+        .chain(
+            trait_expression_filter
+                .map(TraitFilterExpression::trait_names)
+                .unwrap_or_default(),
+        );
+    missing_from(requested, existing)
+}
+
+fn missing_values(requested: &Option<Vec<String>>, existing: &[String]) -> Vec<String> {
+    missing_from(requested.iter().flatten().map(String::as_str), existing)
+}
+
+/// Values are compared case-insensitively, as the filters do
+fn missing_from<'a>(requested: impl Iterator<Item = &'a str>, existing: &[String]) -> Vec<String> {
+    requested
+        .filter(|x| !existing.iter().any(|e| e.eq_ignore_ascii_case(x)))
+        .unique_by(|x| x.to_lowercase())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Ids too big to be stored in the db can not resolve, they are left to be reported as such.
+fn valid_ids(ids: impl Iterator<Item = u64>) -> Vec<i64> {
+    ids.filter_map(|id| i64::try_from(id).ok())
+        .unique()
+        .collect()
+}
+
+/// Pairs every resolved entity with its quantity, keeping the shared order and leaving out
+/// the ones shared 0 times, and collects the ids of the entries that could not be resolved.
+/// Quantities over the share limit are rejected, whatever the share code was decoded with.
+fn pair_with_quantity<T>(
+    element: &'static str,
+    limits: &ShareDecodeLimits,
+    entries: impl IntoIterator<Item = (u64, u64, Option<T>)>,
+) -> anyhow::Result<(Vec<QuantifiedEntity<T>>, Vec<u64>)> {
+    let mut resolved = Vec::new();
+    let mut unresolved = Vec::new();
+    for (id, quantity, entity) in entries {
+        limits.check_quantities(element, std::iter::once(quantity))?;
+        match entity {
+            Some(_) if quantity == 0 => {}
+            Some(entity) => resolved.push(QuantifiedEntity { entity, quantity }),
+            None => unresolved.push(id),
+        }
+    }
+    Ok((resolved, unresolved.into_iter().unique().collect()))
+}
+
+fn units(quantity: u64) -> usize {
+    usize::try_from(quantity).unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::creature::creature_metadata::variant_enum::CreatureVariant;
+    use crate::models::share_envelope::encode_share;
+    use crate::models::share_signing::ShareSigningKeys;
+    use crate::models::shearable_data::SharableCreature;
+
+    #[test]
+    fn imported_encounters_pair_every_entity_with_its_quantity() {
+        let encounter = ShareableEncounter {
+            encounter_name: "Ambush".to_string(),
+            creatures_data: [(1, 2), (7, 1), (3, 1), (7, 3), (4, 0)]
+                .into_iter()
+                .map(|(id, qty)| SharableCreature {
+                    id,
+                    qty,
+                    variant: CreatureVariant::Base,
+                    game: GameSystem::Pathfinder,
+                })
+                .collect(),
+            hazards_data: vec![],
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let code = runtime
+            .block_on(encode_share(encounter, GameSystem::Pathfinder))
+            .unwrap();
+        let imported = runtime
+            .block_on(decode_verified_share::<ShareableEncounter>(
+                &code,
+                &ShareDecodeLimits::DEFAULT,
+                &ShareSigningKeys::default(),
+            ))
+            .unwrap()
+            .payload;
+        let (resolved, unresolved) = pair_with_quantity(
+            "creatures",
+            &ShareDecodeLimits::DEFAULT,
+            imported
+                .creatures_data
+                .iter()
+                .map(|x| (x.id, x.qty, (x.id != 7).then_some(x.id))),
+        )
+        .unwrap();
+        assert_eq!(
+            resolved,
+            vec![
+                QuantifiedEntity {
+                    entity: 1,
+                    quantity: 2
+                },
+                QuantifiedEntity {
+                    entity: 3,
+                    quantity: 1
+                },
+            ]
+        );
+        assert_eq!(unresolved, vec![7]);
+    }
+
+    #[test]
+    fn quantities_over_the_limit_are_rejected() {
+        let limits = ShareDecodeLimits::DEFAULT;
+        let entries = [(1, limits.max_quantity + 1, Some("goblin"))];
+        assert!(pair_with_quantity("creatures", &limits, entries).is_err());
+    }
+
+    #[test]
+    fn missing_traits_include_the_expression_ones() {
+        let existing = vec!["Undead".to_string(), "Fire".to_string()];
+        let expression: TraitFilterExpression = "undead & !mindless".parse().unwrap();
+        assert_eq!(
+            missing_traits(
+                [
+                    &Some(vec!["fire".to_string(), "Mindless".to_string()]),
+                    &None
+                ],
+                Some(&expression),
+                &existing,
+            ),
+            vec!["Mindless"]
+        );
+    }
+}
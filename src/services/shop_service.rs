@@ -6,7 +6,8 @@ use crate::models::item::shop_structs::{
     ShopPaginatedRequest, ShopRanges, ShopTemplateData,
 };
 use crate::models::response_data::{ResponseItem, ShopListingResponse, convert_result_to_response};
-use crate::models::routers_validator_structs::Dice;
+use crate::models::shared::dice_expression::DiceExpression;
+use crate::models::shared::facets::ShopFacets;
 use crate::models::shared::game_system_enum::GameSystem;
 use crate::traits::template_enum::{GenericTemplate, ItemTemplate};
 use anyhow::{Context, bail};
@@ -52,6 +53,16 @@ pub async fn get_shop_listing(
     )
 }
 
+/// Counts, for each filterable dimension, how many items every value would lead to
+/// when combined with the other active filters.
+pub async fn get_shop_facets(
+    app_state: &AppState,
+    field_filter: &ItemFieldFilters,
+    gs: GameSystem,
+) -> Option<ShopFacets> {
+    shop_proxy::get_shop_facets(app_state, gs, field_filter).await
+}
+
 pub async fn get_shop_ranges(app_state: &AppState, gs: GameSystem) -> Option<ShopRanges> {
     shop_proxy::get_shop_ranges(app_state, gs).await
 }
@@ -71,14 +82,8 @@ pub async fn generate_random_shop_listing<T: GenericTemplate + ItemTemplate>(
         |x| (x.get_allowed_item_types(), x.get_allowed_rarities()),
     );
     let shop_type = shop_data.shop_template.clone().unwrap_or_default();
-    let n_of_consumables = i64::from(
-        shop_data
-            .consumable_dices
-            .iter()
-            .map(Dice::roll)
-            .sum::<u32>(),
-    );
-    let n_of_equippables = shop_data.equippable_dices.iter().map(Dice::roll).sum();
+    let n_of_consumables = i64::from(roll_total(&shop_data.consumable_dices));
+    let n_of_equippables = roll_total(&shop_data.equippable_dices);
     // The request is correct, but will result in an empty list.
     if n_of_consumables == 0 && n_of_equippables == 0 {
         return ShopListingResponse::default_with_system(gs);
@@ -133,6 +138,7 @@ pub async fn generate_random_shop_listing<T: GenericTemplate + ItemTemplate>(
                     .trait_blacklist_filter
                     .clone()
                     .unwrap_or_default(),
+                trait_expression_filter: shop_data.trait_expression_filter.clone(),
                 n_of_equipment,
                 n_of_consumables,
                 n_of_weapons,
@@ -156,6 +162,7 @@ pub async fn generate_random_shop_listing<T: GenericTemplate + ItemTemplate>(
                         next: None,
                         total: n_of_items,
                         game: gs,
+                        search_matches: None,
                     }
                 },
             )
@@ -164,6 +171,19 @@ pub async fn generate_random_shop_listing<T: GenericTemplate + ItemTemplate>(
     }
 }
 
+/// Most items rolled for a shop, as many as the largest roll of the 255 dice of size 255 that used to be allowed
+const MAX_ROLLED_ITEMS: u32 = 255 * 255;
+
+/// Sum of the rolls, expressions going below zero count as zero
+/// and the total is capped at `MAX_ROLLED_ITEMS`
+fn roll_total(dices: &[DiceExpression]) -> u32 {
+    let total = dices
+        .iter()
+        .map(DiceExpression::roll)
+        .fold(0, i64::saturating_add);
+    u32::try_from(total.clamp(0, i64::from(MAX_ROLLED_ITEMS))).unwrap_or(MAX_ROLLED_ITEMS)
+}
+
 /// Gets the n of: equipment, weapons, armors, shields (in this order).
 /// Changing order is considered a BREAKING CHANGE.
 pub fn calculate_n_of_equippable_values(
@@ -288,6 +308,15 @@ mod tests {
         assert_eq!(expected, result.unwrap());
     }
 
+    #[rstest]
+    #[case(&["1000000", "1000000"], MAX_ROLLED_ITEMS)]
+    #[case(&["2d6*0-5"], 0)]
+    #[case(&["3", "4"], 7)]
+    fn roll_total_is_bounded(#[case] dices: &[&str], #[case] expected: u32) {
+        let dices: Vec<DiceExpression> = dices.iter().map(|x| x.parse().unwrap()).collect();
+        assert_eq!(roll_total(&dices), expected);
+    }
+
     #[rstest]
     #[case(0, (0,0,0,0), (25, 25, 25, 25))]
     fn calculate_equippable_values_with_all_0(
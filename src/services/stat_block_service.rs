@@ -0,0 +1,85 @@
+use crate::AppState;
+use crate::db::{bestiary_proxy, hazard_proxy, shop_proxy};
+use crate::models::creature::creature_metadata::variant_enum::CreatureVariant;
+use crate::models::response_data::{CreatureResponseDataModifiers, ResponseCreature};
+use crate::models::shared::game_system_enum::GameSystem;
+use crate::models::stat_block::document::{encounter_document, shop_document};
+use crate::models::stat_block::stat_block_struct::StatBlockFormat;
+use crate::services::share_service;
+use crate::traits::stat_block_renderable::StatBlockRenderable;
+
+/// Renders the stat block of the creature, with its variant and PWL applied
+pub async fn render_creature(
+    app_state: &AppState,
+    id: i64,
+    variant: CreatureVariant,
+    is_pwl_on: bool,
+    format: StatBlockFormat,
+    gs: GameSystem,
+) -> Option<String> {
+    bestiary_proxy::get_creature_by_id(
+        app_state,
+        gs,
+        id,
+        variant,
+        &CreatureResponseDataModifiers::all(is_pwl_on),
+    )
+    .await
+    .map(|x| ResponseCreature::from(x).to_stat_block().render(format))
+}
+
+pub async fn render_hazard(
+    app_state: &AppState,
+    id: i64,
+    format: StatBlockFormat,
+    gs: GameSystem,
+) -> Option<String> {
+    hazard_proxy::get_hazard_by_id(app_state, gs, id)
+        .await
+        .map(|x| x.to_stat_block().render(format))
+}
+
+pub async fn render_item(
+    app_state: &AppState,
+    id: i64,
+    format: StatBlockFormat,
+    gs: GameSystem,
+) -> Option<String> {
+    shop_proxy::get_item_by_id(app_state, gs, id)
+        .await
+        .map(|x| x.to_stat_block().render(format))
+}
+
+/// Renders every creature and hazard of a shared encounter in a single document
+pub async fn render_shared_encounter(
+    app_state: &AppState,
+    share_code: String,
+    is_pwl_on: bool,
+    format: StatBlockFormat,
+    gs: GameSystem,
+) -> anyhow::Result<String> {
+    let encounter = share_service::hydrate_shared_encounter(
+        app_state,
+        share_code,
+        vec![],
+        &CreatureResponseDataModifiers::all(is_pwl_on),
+        gs,
+    )
+    .await?;
+    Ok(encounter_document(
+        &encounter.encounter_name,
+        &encounter.results,
+        format,
+    ))
+}
+
+/// Renders every item of a shared shop in a single document
+pub async fn render_shared_shop(
+    app_state: &AppState,
+    share_code: String,
+    format: StatBlockFormat,
+    gs: GameSystem,
+) -> anyhow::Result<String> {
+    let shop = share_service::hydrate_shared_shop(app_state, share_code, gs).await?;
+    Ok(shop_document(&shop.shop_name, &shop.listing, format))
+}
@@ -4,26 +4,32 @@ fn get_website_url() -> String {
     env::var("BACKEND_URL").unwrap_or_else(|_| "http://api.bybe.app".to_string())
 }
 
-pub fn next_url<T: PaginatedRequestExt>(pagination: &T, next_cursor: u32) -> String {
+/// `page_token` is the keyset cursor of the last row, url safe. It is kept next to the numeric
+/// cursor so that clients reading the offset from the url keep working.
+pub fn next_url<T: PaginatedRequestExt>(
+    pagination: &T,
+    sort_spec: &str,
+    next_cursor: u32,
+    page_token: Option<&str>,
+) -> String {
     let base_url = format!("{}/{}", get_website_url(), T::base_path());
-    let pagination_query = prepare_pagination_path(
-        next_cursor,
-        pagination.page_size(),
-        &pagination.sort_by(),
-        &pagination.order_by(),
-    );
+    let pagination_query =
+        prepare_pagination_path(next_cursor, pagination.page_size(), sort_spec, page_token);
     format!("{base_url}{pagination_query}")
 }
 
 fn prepare_pagination_path(
     next_cursor: u32,
     page_size: i16,
-    sort_by_key: &str,
-    order_by_key: &str,
+    sort_spec: &str,
+    page_token: Option<&str>,
 ) -> String {
+    let page_token_query = page_token
+        .map(|token| format!("&page_token={token}"))
+        .unwrap_or_default();
     format!(
-        "?cursor={}&page_size={}&sort_by={}&order_by={}",
-        next_cursor, page_size, sort_by_key, order_by_key
+        "?cursor={}&page_size={}&sort={}{}",
+        next_cursor, page_size, sort_spec, page_token_query
     )
 }
 
@@ -1,3 +1,5 @@
+use crate::models::share_limits::{ShareDecodeError, ShareDecodeLimits};
+use crate::models::share_signing::{ShareSigningKeys, ShareVerification};
 use async_compression::tokio::bufread::ZstdDecoder;
 use async_trait::async_trait;
 use base64ct::{Base64Url, Encoding};
@@ -9,16 +11,39 @@ use tokio::io::AsyncReadExt;
 #[async_trait]
 pub trait Base64Decode: DeserializeOwned {
     async fn decode(base64_data: String) -> anyhow::Result<Self> {
+        Self::decode_with_limits(base64_data, &ShareDecodeLimits::default()).await
+    }
+
+    async fn decode_with_limits(
+        base64_data: String,
+        limits: &ShareDecodeLimits,
+    ) -> anyhow::Result<Self> {
+        limits.check_input_len(base64_data.len())?;
         let compressed_binary = Base64Url::decode_vec(base64_data.as_str())?;
-        let decompressed_binary = Self::decompress_binary(compressed_binary).await?;
+        let decompressed_binary =
+            Self::decompress_binary(compressed_binary, limits.max_decompressed_size).await?;
         Ok(from_bytes(&decompressed_binary)?)
     }
 
-    async fn decompress_binary(compressed: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    /// Decodes a code that may be signed, rejecting it if it was tampered with after being signed
+    async fn decode_verified(
+        base64_data: String,
+        keys: &ShareSigningKeys,
+    ) -> anyhow::Result<(Self, ShareVerification)> {
+        let (code, verification) = keys.verify(&base64_data)?;
+        Ok((Self::decode(code.to_string()).await?, verification))
+    }
+
+    /// Stops reading as soon as more than `max_size` bytes come out,
+    /// a few bytes of input can otherwise expand to gigabytes.
+    async fn decompress_binary(compressed: Vec<u8>, max_size: usize) -> anyhow::Result<Vec<u8>> {
         let cursor = Cursor::new(compressed.as_slice());
-        let mut decoder = ZstdDecoder::new(cursor);
+        let mut decoder = ZstdDecoder::new(cursor).take((max_size as u64).saturating_add(1));
         let mut decompressed = Vec::new();
         decoder.read_to_end(&mut decompressed).await?;
+        if decompressed.len() > max_size {
+            return Err(ShareDecodeError::DecompressedTooLarge { max: max_size }.into());
+        }
         Ok(decompressed)
     }
 }
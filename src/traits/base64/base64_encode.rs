@@ -1,3 +1,4 @@
+use crate::models::share_signing::ShareSigningKeys;
 use async_compression::tokio::write::ZstdEncoder;
 use async_trait::async_trait;
 use base64ct::{Base64Url, Encoding};
@@ -15,6 +16,11 @@ pub trait Base64Encode: Serialize {
         Ok(Base64Url::encode_string(compressed_binary.as_slice()))
     }
 
+    /// Signs the code with the first of the given keys, it is left unsigned if there are none
+    async fn encode_signed(&self, keys: &ShareSigningKeys) -> anyhow::Result<String> {
+        Ok(keys.sign(self.encode().await?))
+    }
+
     async fn compress_binary_data(input: Vec<u8>) -> anyhow::Result<Vec<u8>> {
         let mut encoder = ZstdEncoder::new(Vec::new());
         encoder.write_all(input.as_slice()).await?;
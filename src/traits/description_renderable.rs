@@ -0,0 +1,18 @@
+use crate::models::foundry::inline_markup::DescriptionFormat;
+
+/// Entities with descriptions stored as Foundry HTML, inline macros included.
+pub trait DescriptionRenderable {
+    fn render_descriptions(&mut self, format: DescriptionFormat);
+
+    /// Descriptions are left as stored when no format is requested
+    #[must_use]
+    fn with_description_format(mut self, format: Option<DescriptionFormat>) -> Self
+    where
+        Self: Sized,
+    {
+        if let Some(format) = format {
+            self.render_descriptions(format);
+        }
+        self
+    }
+}
@@ -0,0 +1,7 @@
+use serde_json::Value;
+
+/// Entities that can be imported in Foundry VTT as actors of the PF2e system.
+/// The SF2e system shares the same actor data model.
+pub trait FoundryExportable {
+    fn to_foundry_actor(&self) -> Value;
+}
@@ -1,14 +1,18 @@
 pub mod base64;
 pub mod class_enum;
+pub mod description_renderable;
 pub mod filter;
 pub mod filterable;
+pub mod foundry_exportable;
 pub mod has_complexity;
 pub mod has_level;
 pub mod job_enum;
 pub mod name_system;
 pub mod origin;
+pub mod proficiency_without_level;
 pub mod random_enum;
 pub mod response;
+pub mod stat_block_renderable;
 pub mod template_enum;
 pub mod traits_enrichable;
 pub mod url;
@@ -0,0 +1,30 @@
+/// Proficiency Without Level variant rule: the level is removed from every statistic
+/// that adds it, ex AC, saving throws, attack modifiers, skills and DCs.
+pub trait ProficiencyWithoutLevel: Sized {
+    /// Level removed by the conversion, negative levels remove nothing
+    fn pwl_mod(&self) -> u64;
+
+    /// Lowers every statistic that adds the level by `pwl_mod`
+    #[must_use]
+    fn remove_pwl_mod(self, pwl_mod: u64) -> Self;
+
+    #[must_use]
+    fn convert_to_pwl(self) -> Self {
+        let pwl_mod = self.pwl_mod();
+        self.remove_pwl_mod(pwl_mod)
+    }
+
+    /// Converts only when PWL is on, so that it can be chained on any response
+    #[must_use]
+    fn with_pwl(self, is_pwl_on: bool) -> Self {
+        if is_pwl_on {
+            self.convert_to_pwl()
+        } else {
+            self
+        }
+    }
+}
+
+pub fn pwl_mod_from_level(level: i64) -> u64 {
+    u64::try_from(level).unwrap_or(0)
+}
@@ -1,3 +1,5 @@
+use crate::models::shared::text_search::TextSearchMatch;
+
 pub trait ListingResponse: Default {
     type Item;
 
@@ -6,5 +8,6 @@ pub trait ListingResponse: Default {
         count: usize,
         next: Option<String>,
         total: usize,
+        search_matches: Option<Vec<TextSearchMatch>>,
     ) -> Self;
 }
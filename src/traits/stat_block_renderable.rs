@@ -0,0 +1,6 @@
+use crate::models::stat_block::stat_block_struct::StatBlock;
+
+/// Entities that can be printed as a stat block, as laid out in the published books.
+pub trait StatBlockRenderable {
+    fn to_stat_block(&self) -> StatBlock;
+}
@@ -1,8 +1,23 @@
 use crate::models::routers_validator_structs::OrderEnum;
+use crate::models::shared::sort_spec::{SortKeySpec, parse_sort_spec};
+use anyhow::Result;
+use std::str::FromStr;
 
 pub trait HasSortFields {
-    type SortBy: Default + ToString + Clone;
+    type SortBy: Default + ToString + Clone + FromStr + PartialEq;
 
     fn sort_by_field(&self) -> &Option<Self::SortBy>;
     fn order_by_field(&self) -> &Option<OrderEnum>;
+    fn sort_field(&self) -> &Option<String>;
+
+    /// The keys of the multi-key sort when given, the single `sort_by` and `order_by` otherwise
+    fn sort_keys(&self) -> Result<Vec<SortKeySpec<Self::SortBy>>> {
+        match self.sort_field() {
+            Some(spec) if !spec.trim().is_empty() => parse_sort_spec(spec),
+            _ => Ok(vec![SortKeySpec::new(
+                self.sort_by_field().clone().unwrap_or_default(),
+                self.order_by_field().unwrap_or_default(),
+            )]),
+        }
+    }
 }
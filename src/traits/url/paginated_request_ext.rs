@@ -1,5 +1,7 @@
 use crate::models::routers_validator_structs::PaginatedRequest;
+use crate::models::shared::listing_cursor::{KeysetCursor, ListingCursor};
 use crate::traits::url::sort_data_ext::SortDataExt;
+use anyhow::{Result, ensure};
 
 pub trait PaginatedRequestExt {
     type Sort: SortDataExt;
@@ -18,4 +20,23 @@ pub trait PaginatedRequestExt {
     fn page_size(&self) -> i16 {
         self.paginated_request().page_size
     }
+    fn sort_spec(&self) -> Result<String> {
+        self.sort_data().sort_spec()
+    }
+    /// Where the requested page starts, after the row of the page token if there is one,
+    /// at the cursor offset otherwise.
+    fn listing_cursor(&self) -> Result<ListingCursor> {
+        let request = self.paginated_request();
+        match &request.page_token {
+            Some(token) => {
+                let cursor = KeysetCursor::from_token(token)?;
+                ensure!(
+                    cursor.sort == self.sort_spec()?,
+                    "The page token was generated with a different sort"
+                );
+                Ok(ListingCursor::Keyset(cursor))
+            }
+            None => Ok(ListingCursor::Offset(request.cursor)),
+        }
+    }
 }
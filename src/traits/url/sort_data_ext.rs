@@ -1,8 +1,12 @@
+use crate::models::shared::sort_spec::format_sort_spec;
 use crate::traits::url::has_sort_fields::HasSortFields;
+use anyhow::Result;
 
 pub trait SortDataExt {
     fn sort_by(&self) -> String;
     fn order_by(&self) -> String;
+    /// The whole sort, in the multi-key format. Fails like `sort_keys` on an invalid sort
+    fn sort_spec(&self) -> Result<String>;
 }
 
 impl<T: HasSortFields> SortDataExt for T {
@@ -12,4 +16,7 @@ impl<T: HasSortFields> SortDataExt for T {
     fn order_by(&self) -> String {
         self.order_by_field().unwrap_or_default().to_string()
     }
+    fn sort_spec(&self) -> Result<String> {
+        Ok(format_sort_spec(&self.sort_keys()?))
+    }
 }